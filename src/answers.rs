@@ -0,0 +1,184 @@
+use aoc2022::parse::{self, Line, ParseError};
+use std::collections::BTreeMap;
+
+/// The expected answers, read from a TOML file such as:
+///
+/// ```toml
+/// [day5]
+/// part1 = "CMZ"
+/// part2 = 12
+/// ```
+///
+/// Only this subset of TOML is understood: one table per day, integers, basic strings and
+/// multi-line basic strings, and comments.
+pub struct Answers(BTreeMap<(u8, u8), String>);
+
+impl Answers {
+    pub fn parse(values: &str) -> Result<Self, ParseError> {
+        let mut answers = BTreeMap::new();
+        let mut day = None;
+
+        let mut lines = parse::lines(values);
+        while let Some(line) = lines.next() {
+            let text = strip_comment(line.text).trim();
+            if text.is_empty() {
+                continue;
+            }
+
+            if let Some(table) = text.strip_prefix('[') {
+                let name = table
+                    .strip_suffix(']')
+                    .ok_or_else(|| line.error_after(table, "expected `]`"))?;
+                let n = line.strip_prefix(name, "day")?;
+                day = match n.parse::<u8>() {
+                    Ok(n @ 1..=25) => Some(n),
+                    _ => return Err(line.error(n, format!("invalid day `{n}`"))),
+                };
+                continue;
+            }
+
+            let (key, value) = line.split_once(text, "=")?;
+            let key = key.trim_end();
+            let part = line.strip_prefix(key, "part")?;
+            let part = match part.parse::<u8>() {
+                Ok(n @ 1..=2) => n,
+                _ => return Err(line.error(part, format!("invalid part `{part}`"))),
+            };
+            let day = day.ok_or_else(|| line.error(key, "expected a `[dayN]` table first"))?;
+
+            let value = value.trim_start();
+            let answer = if value.starts_with("\"\"\"") {
+                multiline_string(&line, value, &mut lines)?
+            } else if value.starts_with('"') {
+                let (answer, rest) = basic_string(&line, value)?;
+                if !rest.trim().is_empty() {
+                    return Err(line.error(rest.trim(), "unexpected characters after the string"));
+                }
+                answer
+            } else {
+                line.parse::<i64>(value, "integer or string")?.to_string()
+            };
+
+            if answers.insert((day, part), answer).is_some() {
+                return Err(line.error(key, format!("duplicate answer for {day}.{part}")));
+            }
+        }
+
+        Ok(Self(answers))
+    }
+
+    /// The expected answer of the given part, if known.
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        self.0.get(&(day, part)).map(String::as_str)
+    }
+}
+
+/// Removes a trailing comment, unless the `#` is part of a string.
+fn strip_comment(text: &str) -> &str {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (offset, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &text[..offset],
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Reads a string starting at `value`, a slice of `line`, returning it and what follows it.
+fn basic_string<'a>(line: &Line<'a>, value: &'a str) -> Result<(String, &'a str), ParseError> {
+    let mut string = String::new();
+    let mut chars = value.char_indices().skip(1);
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '"' => return Ok((string, &value[offset + 1..])),
+            '\\' => {
+                let (offset, c) = chars
+                    .next()
+                    .ok_or_else(|| line.error_after(value, "unterminated string"))?;
+                string.push(match c {
+                    'n' => '\n',
+                    't' => '\t',
+                    '"' => '"',
+                    '\\' => '\\',
+                    _ => return Err(line.error(&value[offset..], "unsupported escape")),
+                });
+            }
+            c => string.push(c),
+        }
+    }
+    Err(line.error_after(value, "unterminated string"))
+}
+
+/// Reads a multi-line string starting at `value`, a slice of `line`, up to its closing `"""`.
+/// Escapes are not supported, and a newline right after the opening quotes is dropped.
+fn multiline_string(
+    line: &Line,
+    value: &str,
+    lines: &mut parse::Lines,
+) -> Result<String, ParseError> {
+    let mut string = value[3..].to_string();
+    if string.is_empty() {
+        // the first line is only made of the opening quotes
+    } else if let Some(end) = string.find("\"\"\"") {
+        string.truncate(end);
+        return Ok(string);
+    } else {
+        string.push('\n');
+    }
+
+    loop {
+        let next = lines.next().ok_or_else(|| {
+            line.error(value, "unterminated multi-line string, expected `\"\"\"`")
+        })?;
+        if let Some(end) = next.text.find("\"\"\"") {
+            string.push_str(&next.text[..end]);
+            return Ok(string);
+        }
+        string.push_str(next.text);
+        string.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse(
+            "# expected answers\n\
+             [day1]\n\
+             part1 = 24000 # sum\n\
+             part2 = \"a # b\"\n\
+             \n\
+             [day10]\n\
+             part2 = \"\"\"\n\
+             ##..\n\
+             ..##\n\
+             \"\"\"\n",
+        )
+        .unwrap();
+        assert_eq!(answers.get(1, 1), Some("24000"));
+        assert_eq!(answers.get(1, 2), Some("a # b"));
+        assert_eq!(answers.get(10, 1), None);
+        assert_eq!(answers.get(10, 2), Some("##..\n..##\n"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = Answers::parse("part1 = 1\n").map(|_| ()).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(1, 1, "expected a `[dayN]` table first")
+        );
+        let err = Answers::parse("[day2]\npart3 = 1\n")
+            .map(|_| ())
+            .unwrap_err();
+        assert_eq!(err, ParseError::new(2, 5, "invalid part `3`"));
+    }
+}
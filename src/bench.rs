@@ -0,0 +1,181 @@
+use aoc2022::json;
+use aoc2022::parse::ParseError;
+use aoc2022::solution::Solution;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// The system allocator, counting every allocation made through it, on every thread.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// The measurements of one part over every iteration.
+pub struct Report {
+    pub day: u8,
+    pub part: u8,
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+    /// Allocations made by a single iteration; only the median is kept as it barely varies.
+    pub allocations: usize,
+    /// Bytes allocated by a single iteration, including reallocations.
+    pub allocated: usize,
+}
+
+/// Solves the given part `iterations` times, stopping at the first error, or returns `None` if
+/// the puzzle has no such part.
+pub fn measure(
+    solution: &dyn Solution,
+    part: u8,
+    values: &str,
+    iterations: usize,
+) -> Option<Result<Report, ParseError>> {
+    let mut times = Vec::with_capacity(iterations);
+    let mut allocations = Vec::with_capacity(iterations);
+    let mut allocated = Vec::with_capacity(iterations);
+
+    for _ in 0..iterations {
+        let allocations_before = ALLOCATIONS.load(Ordering::Relaxed);
+        let allocated_before = ALLOCATED.load(Ordering::Relaxed);
+        let start = Instant::now();
+        if let Err(err) = std::hint::black_box(solution.part(part, values)?) {
+            return Some(Err(err));
+        }
+        times.push(start.elapsed());
+        allocations.push(ALLOCATIONS.load(Ordering::Relaxed) - allocations_before);
+        allocated.push(ALLOCATED.load(Ordering::Relaxed) - allocated_before);
+    }
+
+    times.sort();
+    allocations.sort();
+    allocated.sort();
+    Some(Ok(Report {
+        day: solution.day(),
+        part,
+        min: times[0],
+        median: times[iterations / 2],
+        max: times[iterations - 1],
+        allocations: allocations[iterations / 2],
+        allocated: allocated[iterations / 2],
+    }))
+}
+
+/// Formats the reports as a markdown table.
+pub fn markdown(reports: &[Report]) -> String {
+    let mut out = String::from(
+        "| day | part | min | median | max | allocations | allocated |\n\
+         |----:|-----:|----:|-------:|----:|------------:|----------:|\n",
+    );
+    for report in reports {
+        out.push_str(&format!(
+            "| {} | {} | {:.2?} | {:.2?} | {:.2?} | {} | {} |\n",
+            report.day,
+            report.part,
+            report.min,
+            report.median,
+            report.max,
+            report.allocations,
+            bytes(report.allocated),
+        ));
+    }
+    out
+}
+
+/// Formats the reports as a JSON array, with times in nanoseconds and sizes in bytes.
+pub fn json(reports: &[Report]) -> String {
+    let reports = reports
+        .iter()
+        .map(|report| {
+            json::object(&[
+                ("day", report.day.to_string()),
+                ("part", report.part.to_string()),
+                ("min_ns", report.min.as_nanos().to_string()),
+                ("median_ns", report.median.as_nanos().to_string()),
+                ("max_ns", report.max.as_nanos().to_string()),
+                ("allocations", report.allocations.to_string()),
+                ("allocated_bytes", report.allocated.to_string()),
+            ])
+        })
+        .collect::<Vec<_>>();
+    json::array(&reports)
+}
+
+/// Formats a size in bytes with a binary prefix.
+fn bytes(size: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = size as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{size} {}", UNITS[unit])
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> Report {
+        Report {
+            day: 1,
+            part: 2,
+            min: Duration::from_micros(1500),
+            median: Duration::from_millis(2),
+            max: Duration::from_millis(3),
+            allocations: 4,
+            allocated: 2048,
+        }
+    }
+
+    #[test]
+    fn test_markdown() {
+        let table = markdown(&[report()]);
+        assert_eq!(
+            table.lines().last(),
+            Some("| 1 | 2 | 1.50ms | 2.00ms | 3.00ms | 4 | 2.0 KiB |")
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!(
+            json(&[report()]),
+            "[{\"day\":1,\"part\":2,\"min_ns\":1500000,\"median_ns\":2000000,\
+             \"max_ns\":3000000,\"allocations\":4,\"allocated_bytes\":2048}]"
+        );
+    }
+}
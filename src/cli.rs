@@ -0,0 +1,639 @@
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage:
+    aoc2022 run (--all | --day <N>) [options] [--render [--render-every <K>] [--ansi] | --stream]
+                [--raster] [--format <F>]
+    aoc2022 replay (--all | --day <N>) [options] [--fps <F> | --dump <DIR>] [--every <K>] [--ansi]
+    aoc2022 bench (--all | --day <N>) [options] [--iterations <K>] [--format <F>]
+    aoc2022 verify (--all | --day <N>) [options] [--answers <PATH>]
+    aoc2022 generate --day <N> [--seed <S>] [--size <K>]
+    aoc2022 shell [--input <PATH> | --dir <PATH>] [--batch <PATH>]
+    aoc2022 scan --dir <PATH> [--format <F>] [--limit <B>] [--capacity <B>] [--needed <B>]
+    aoc2022 list
+    aoc2022 help
+
+Options for `run`, `replay`, `bench` and `verify`:
+    --all               run every registered day
+    --day <N>           run day N only (1-25)
+    --part <P>          run part P only (1 or 2)
+    --input <PATH>      read the input of the selected day from PATH
+    --stdin             read the input of the selected day from stdin
+    --skip-slow         skip the days marked as slow
+
+Options for `run`:
+    --render            draw the final state of the days simulating one
+    --render-every <K>  also draw every K-th intermediate state
+    --ansi              colour the drawings with ANSI escape codes
+    --stream            read the input as it is solved on the days able to, for inputs too large
+                        to be held whole
+    --raster            print the pixels of the screens some answers are drawn on, rather
+                        than the letters read on them
+    --format <F>        print the answers as `text` (default) or `json`, an array of
+                        records with the time taken by each part
+
+Options for `replay`:
+    --fps <F>           play F frames per second in the terminal (default 10)
+    --dump <DIR>        write the frames to DIR, one file per state, instead of playing them
+    --every <K>         only show every K-th state (default 1)
+    --ansi              colour the frames with ANSI escape codes
+
+Options for `bench`:
+    --iterations <K>    solve each part K times (default 10)
+    --format <F>        print the results as `markdown` (default) or `json`
+
+Options for `verify`:
+    --answers <PATH>    read the expected answers from PATH (default `answers.toml`)
+
+Options for `generate`:
+    --day <N>           write a random input for day N, if it has a generator
+    --seed <S>          seed the random generator with S (default 0)
+    --size <K>          make the input of about K of the items it lists (default 10)
+
+Options for `shell`:
+    --input <PATH>      explore the filesystem of the day 7 transcript at PATH
+    --dir <PATH>        explore the directory at PATH on disk instead
+    --batch <PATH>      run the commands of the script at PATH rather than those typed at the
+                        prompt, or piped to stdin
+
+Options for `scan`:
+    --dir <PATH>        measure the directory at PATH on disk, as day 7 does its filesystem
+    --format <F>        print the day 7 answers over it as `answers` (default), or the whole
+                        tree as a day 7 `transcript` or as `json`
+    --limit <B>         add up the folders of at most B bytes (default 100000)
+    --capacity <B>      find the smallest folder to delete on a disk of B bytes
+                        (default 70000000)
+    --needed <B>        for B bytes to be free on it (default 30000000)
+
+Without --input or --stdin, the input of day N is read from `src/dayN/input.txt`.";
+
+/// Which days to run.
+pub enum Days {
+    All,
+    One(u8),
+}
+
+/// Where to read the puzzle input from.
+pub enum Input {
+    /// `src/dayN/input.txt`, relative to the working directory.
+    Default,
+    File(PathBuf),
+    Stdin,
+}
+
+/// Which days and parts to solve, and on which input: shared by `run`, `replay`, `bench` and
+/// `verify`.
+pub struct Selection {
+    pub days: Days,
+    pub part: Option<u8>,
+    pub input: Input,
+    pub skip_slow: bool,
+}
+
+/// How to draw the states of the simulations.
+pub struct Render {
+    pub every: Option<usize>,
+    pub ansi: bool,
+}
+
+/// How to print the answers.
+pub enum AnswerFormat {
+    Text,
+    Json,
+}
+
+/// Options of the `run` subcommand.
+pub struct Run {
+    pub selection: Selection,
+    pub render: Option<Render>,
+    pub stream: bool,
+    pub raster: bool,
+    pub format: AnswerFormat,
+}
+
+/// Where to show the frames of a replay.
+pub enum Playback {
+    Terminal { fps: f64 },
+    Directory(PathBuf),
+}
+
+/// Options of the `replay` subcommand.
+pub struct Replay {
+    pub selection: Selection,
+    pub playback: Playback,
+    pub every: usize,
+    pub ansi: bool,
+}
+
+/// How to print the benchmark results.
+pub enum Format {
+    Markdown,
+    Json,
+}
+
+/// Options of the `bench` subcommand.
+pub struct Bench {
+    pub selection: Selection,
+    pub iterations: usize,
+    pub format: Format,
+}
+
+/// Options of the `verify` subcommand.
+pub struct Verify {
+    pub selection: Selection,
+    pub answers: PathBuf,
+}
+
+/// Options of the `generate` subcommand.
+pub struct Generate {
+    pub day: u8,
+    pub seed: u64,
+    pub size: usize,
+}
+
+/// Options of the `shell` subcommand.
+pub struct Shell {
+    pub input: Option<PathBuf>,
+    pub dir: Option<PathBuf>,
+    pub batch: Option<PathBuf>,
+}
+
+/// What to print about a scanned directory.
+pub enum ScanFormat {
+    Answers,
+    Transcript,
+    Json,
+}
+
+/// Options of the `scan` subcommand.
+pub struct Scan {
+    pub dir: PathBuf,
+    pub format: ScanFormat,
+    pub limit: u64,
+    pub capacity: u64,
+    pub needed: u64,
+}
+
+pub enum Command {
+    Run(Run),
+    /// Shows every state of the simulations, one frame at a time.
+    Replay(Replay),
+    Bench(Bench),
+    Verify(Verify),
+    /// Writes a random input in the format of a day.
+    Generate(Generate),
+    /// Runs shell commands over the filesystem of a day 7 transcript.
+    Shell(Shell),
+    /// Measures a directory on disk like the filesystem of day 7.
+    Scan(Scan),
+    /// Lists the registered days.
+    List,
+    Help,
+}
+
+impl Command {
+    /// Parses the command line arguments, program name excluded.
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        match args.next().as_deref() {
+            Some("run") => Run::parse(args).map(Command::Run),
+            Some("replay") => Replay::parse(args).map(Command::Replay),
+            Some("bench") => Bench::parse(args).map(Command::Bench),
+            Some("verify") => Verify::parse(args).map(Command::Verify),
+            Some("generate") => Generate::parse(args).map(Command::Generate),
+            Some("shell") => Shell::parse(args).map(Command::Shell),
+            Some("scan") => Scan::parse(args).map(Command::Scan),
+            Some("list") => Ok(Command::List),
+            Some("help") | Some("--help") | Some("-h") => Ok(Command::Help),
+            Some(other) => Err(format!("unrecognised subcommand `{other}`")),
+            None => Err("missing subcommand".to_string()),
+        }
+    }
+}
+
+impl Selection {
+    /// Parses the selection options, handing any other option to `other` along with the
+    /// remaining arguments so it can consume its value.
+    fn parse<I: Iterator<Item = String>>(
+        mut args: I,
+        mut other: impl FnMut(&str, &mut I) -> Result<(), String>,
+    ) -> Result<Self, String> {
+        let mut all = false;
+        let mut day = None;
+        let mut part = None;
+        let mut input = Input::Default;
+        let mut skip_slow = false;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--day" if day.is_some() => return Err("--day given twice".to_string()),
+                "--part" if part.is_some() => return Err("--part given twice".to_string()),
+                "--all" if all => return Err("--all given twice".to_string()),
+                "--skip-slow" if skip_slow => return Err("--skip-slow given twice".to_string()),
+                "--input" | "--stdin" if !matches!(input, Input::Default) => {
+                    return Err("only one of --input and --stdin may be given, once".to_string())
+                }
+                _ => {}
+            }
+            match arg.as_str() {
+                "--all" => all = true,
+                "--day" => {
+                    let value = value_of(&mut args, "--day")?;
+                    match value.parse::<u8>() {
+                        Ok(n @ 1..=25) => day = Some(n),
+                        _ => return Err(format!("invalid day `{value}`")),
+                    }
+                }
+                "--part" => {
+                    let value = value_of(&mut args, "--part")?;
+                    match value.parse::<u8>() {
+                        Ok(n @ 1..=2) => part = Some(n),
+                        _ => return Err(format!("invalid part `{value}`")),
+                    }
+                }
+                "--input" => input = Input::File(value_of(&mut args, "--input")?.into()),
+                "--stdin" => input = Input::Stdin,
+                "--skip-slow" => skip_slow = true,
+                _ => other(&arg, &mut args)?,
+            }
+        }
+
+        let days = match (all, day) {
+            (true, None) => Days::All,
+            (false, Some(n)) => Days::One(n),
+            (true, Some(_)) => return Err("--all and --day are mutually exclusive".to_string()),
+            (false, None) => return Err("either --all or --day is required".to_string()),
+        };
+        if matches!(days, Days::All) && !matches!(input, Input::Default) {
+            return Err("--input and --stdin require a single --day".to_string());
+        }
+
+        Ok(Self {
+            days,
+            part,
+            input,
+            skip_slow,
+        })
+    }
+}
+
+impl Run {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut render = false;
+        let mut every = None;
+        let mut ansi = false;
+        let mut stream = false;
+        let mut raster = false;
+        let mut format = None;
+
+        let selection = Selection::parse(args, |arg, args| {
+            let given = match arg {
+                "--render" => render,
+                "--stream" => stream,
+                "--raster" => raster,
+                "--format" => format.is_some(),
+                "--render-every" => every.is_some(),
+                "--ansi" => ansi,
+                _ => false,
+            };
+            if given {
+                return Err(format!("{arg} given twice"));
+            }
+            match arg {
+                "--render" => render = true,
+                "--stream" => stream = true,
+                "--raster" => raster = true,
+                "--format" => {
+                    let value = value_of(args, "--format")?;
+                    format = Some(match value.as_str() {
+                        "text" => AnswerFormat::Text,
+                        "json" => AnswerFormat::Json,
+                        _ => return Err(format!("invalid format `{value}`")),
+                    })
+                }
+                "--render-every" => {
+                    let value = value_of(args, "--render-every")?;
+                    match value.parse::<usize>() {
+                        Ok(n) if n > 0 => every = Some(n),
+                        _ => return Err(format!("invalid number of steps `{value}`")),
+                    }
+                }
+                "--ansi" => ansi = true,
+                _ => return Err(format!("unrecognised option `{arg}`")),
+            }
+            Ok(())
+        })?;
+
+        let format = format.unwrap_or(AnswerFormat::Text);
+        if !render && (every.is_some() || ansi) {
+            return Err("--render-every and --ansi require --render".to_string());
+        }
+        if render && stream {
+            return Err("--render and --stream are mutually exclusive".to_string());
+        }
+        if stream && matches!(selection.input, Input::Stdin) && selection.part.is_none() {
+            return Err("--stream reads stdin once, so it requires --part".to_string());
+        }
+        if render && matches!(format, AnswerFormat::Json) {
+            return Err("--render draws text, so it cannot be used with --format json".to_string());
+        }
+
+        Ok(Self {
+            selection,
+            render: render.then_some(Render { every, ansi }),
+            stream,
+            raster,
+            format,
+        })
+    }
+}
+
+impl Replay {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut fps = None;
+        let mut dump = None;
+        let mut every = None;
+        let mut ansi = false;
+
+        let selection = Selection::parse(args, |arg, args| {
+            let given = match arg {
+                "--fps" => fps.is_some(),
+                "--dump" => dump.is_some(),
+                "--every" => every.is_some(),
+                "--ansi" => ansi,
+                _ => false,
+            };
+            if given {
+                return Err(format!("{arg} given twice"));
+            }
+            match arg {
+                "--fps" => {
+                    let value = value_of(args, "--fps")?;
+                    match value.parse::<f64>() {
+                        Ok(n) if n > 0.0 && n.is_finite() => fps = Some(n),
+                        _ => return Err(format!("invalid frame rate `{value}`")),
+                    }
+                }
+                "--dump" => dump = Some(value_of(args, "--dump")?.into()),
+                "--every" => {
+                    let value = value_of(args, "--every")?;
+                    match value.parse::<usize>() {
+                        Ok(n) if n > 0 => every = Some(n),
+                        _ => return Err(format!("invalid number of steps `{value}`")),
+                    }
+                }
+                "--ansi" => ansi = true,
+                _ => return Err(format!("unrecognised option `{arg}`")),
+            }
+            Ok(())
+        })?;
+
+        let playback = match (fps, dump) {
+            (Some(_), Some(_)) => return Err("--fps and --dump are mutually exclusive".to_string()),
+            (_, Some(dir)) => Playback::Directory(dir),
+            (fps, None) => Playback::Terminal {
+                fps: fps.unwrap_or(10.0),
+            },
+        };
+
+        Ok(Self {
+            selection,
+            playback,
+            every: every.unwrap_or(1),
+            ansi,
+        })
+    }
+}
+
+impl Bench {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut iterations = None;
+        let mut format = None;
+
+        let selection = Selection::parse(args, |arg, args| {
+            let given = match arg {
+                "--iterations" => iterations.is_some(),
+                "--format" => format.is_some(),
+                _ => false,
+            };
+            if given {
+                return Err(format!("{arg} given twice"));
+            }
+            match arg {
+                "--iterations" => {
+                    let value = value_of(args, "--iterations")?;
+                    match value.parse::<usize>() {
+                        Ok(n) if n > 0 => iterations = Some(n),
+                        _ => return Err(format!("invalid number of iterations `{value}`")),
+                    }
+                }
+                "--format" => {
+                    let value = value_of(args, "--format")?;
+                    format = Some(match value.as_str() {
+                        "markdown" => Format::Markdown,
+                        "json" => Format::Json,
+                        _ => return Err(format!("invalid format `{value}`")),
+                    })
+                }
+                _ => return Err(format!("unrecognised option `{arg}`")),
+            }
+            Ok(())
+        })?;
+
+        Ok(Self {
+            selection,
+            iterations: iterations.unwrap_or(10),
+            format: format.unwrap_or(Format::Markdown),
+        })
+    }
+}
+
+impl Verify {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut answers = None;
+
+        let selection = Selection::parse(args, |arg, args| {
+            match arg {
+                "--answers" if answers.is_some() => return Err("--answers given twice".to_string()),
+                "--answers" => answers = Some(value_of(args, "--answers")?.into()),
+                _ => return Err(format!("unrecognised option `{arg}`")),
+            }
+            Ok(())
+        })?;
+
+        let answers = answers.unwrap_or_else(|| PathBuf::from("answers.toml"));
+        Ok(Self { selection, answers })
+    }
+}
+
+impl Generate {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut day = None;
+        let mut seed = None;
+        let mut size = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--day" if day.is_some() => return Err("--day given twice".to_string()),
+                "--seed" if seed.is_some() => return Err("--seed given twice".to_string()),
+                "--size" if size.is_some() => return Err("--size given twice".to_string()),
+                "--day" => {
+                    let value = value_of(&mut args, "--day")?;
+                    match value.parse::<u8>() {
+                        Ok(n @ 1..=25) => day = Some(n),
+                        _ => return Err(format!("invalid day `{value}`")),
+                    }
+                }
+                "--seed" => {
+                    let value = value_of(&mut args, "--seed")?;
+                    match value.parse::<u64>() {
+                        Ok(n) => seed = Some(n),
+                        _ => return Err(format!("invalid seed `{value}`")),
+                    }
+                }
+                "--size" => {
+                    let value = value_of(&mut args, "--size")?;
+                    match value.parse::<usize>() {
+                        Ok(n) if n > 0 => size = Some(n),
+                        _ => return Err(format!("invalid size `{value}`")),
+                    }
+                }
+                _ => return Err(format!("unrecognised option `{arg}`")),
+            }
+        }
+
+        Ok(Self {
+            day: day.ok_or("--day is required")?,
+            seed: seed.unwrap_or(0),
+            size: size.unwrap_or(10),
+        })
+    }
+}
+
+impl Shell {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut input = None;
+        let mut dir = None;
+        let mut batch = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--input" if input.is_some() => return Err("--input given twice".to_string()),
+                "--dir" if dir.is_some() => return Err("--dir given twice".to_string()),
+                "--batch" if batch.is_some() => return Err("--batch given twice".to_string()),
+                "--input" => input = Some(PathBuf::from(value_of(&mut args, "--input")?)),
+                "--dir" => dir = Some(PathBuf::from(value_of(&mut args, "--dir")?)),
+                "--batch" => batch = Some(PathBuf::from(value_of(&mut args, "--batch")?)),
+                _ => return Err(format!("unrecognised option `{arg}`")),
+            }
+        }
+        if input.is_some() && dir.is_some() {
+            return Err("--input and --dir are mutually exclusive".to_string());
+        }
+
+        Ok(Self { input, dir, batch })
+    }
+}
+
+impl Scan {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut dir = None;
+        let mut format = None;
+        let mut limit = None;
+        let mut capacity = None;
+        let mut needed = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--dir" if dir.is_some() => return Err("--dir given twice".to_string()),
+                "--format" if format.is_some() => return Err("--format given twice".to_string()),
+                "--limit" if limit.is_some() => return Err("--limit given twice".to_string()),
+                "--capacity" if capacity.is_some() => {
+                    return Err("--capacity given twice".to_string())
+                }
+                "--needed" if needed.is_some() => return Err("--needed given twice".to_string()),
+                "--dir" => dir = Some(PathBuf::from(value_of(&mut args, "--dir")?)),
+                "--format" => {
+                    let value = value_of(&mut args, "--format")?;
+                    format = Some(match value.as_str() {
+                        "answers" => ScanFormat::Answers,
+                        "transcript" => ScanFormat::Transcript,
+                        "json" => ScanFormat::Json,
+                        _ => return Err(format!("invalid format `{value}`")),
+                    });
+                }
+                "--limit" => limit = Some(bytes(&mut args, "--limit")?),
+                "--capacity" => capacity = Some(bytes(&mut args, "--capacity")?),
+                "--needed" => needed = Some(bytes(&mut args, "--needed")?),
+                _ => return Err(format!("unrecognised option `{arg}`")),
+            }
+        }
+
+        Ok(Self {
+            dir: dir.ok_or("--dir is required")?,
+            format: format.unwrap_or(ScanFormat::Answers),
+            limit: limit.unwrap_or(100_000),
+            capacity: capacity.unwrap_or(70_000_000),
+            needed: needed.unwrap_or(30_000_000),
+        })
+    }
+}
+
+/// Reads the value of `option` as a number of bytes.
+fn bytes(args: &mut impl Iterator<Item = String>, option: &str) -> Result<u64, String> {
+    let value = value_of(args, option)?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid number of bytes `{value}`"))
+}
+
+fn value_of(args: &mut impl Iterator<Item = String>, option: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("{option} requires a value"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(line: &str) -> Option<String> {
+        Command::parse(line.split_whitespace().map(String::from)).err()
+    }
+
+    #[test]
+    fn test_repeated_options() {
+        let repeated = [
+            ("run --all --all", "--all"),
+            ("run --day 1 --skip-slow --skip-slow", "--skip-slow"),
+            ("run --day 1 --format json --format text", "--format"),
+            ("run --day 1 --render --render", "--render"),
+            (
+                "run --day 1 --render --render-every 2 --render-every 3",
+                "--render-every",
+            ),
+            ("run --day 1 --stream --part 1 --stream", "--stream"),
+            ("replay --day 14 --fps 5 --fps 5", "--fps"),
+            ("replay --day 14 --dump a --dump b", "--dump"),
+            ("replay --day 14 --every 2 --every 2", "--every"),
+            ("replay --day 14 --ansi --ansi", "--ansi"),
+            ("bench --all --iterations 3 --iterations 4", "--iterations"),
+            ("bench --all --format json --format markdown", "--format"),
+            (
+                "verify --all --answers a.toml --answers b.toml",
+                "--answers",
+            ),
+        ];
+        for (line, option) in repeated {
+            assert_eq!(error(line), Some(format!("{option} given twice")), "{line}");
+        }
+
+        let once = [
+            "run --day 1 --format json --raster",
+            "replay --day 14 --every 2 --ansi",
+            "bench --all --iterations 3 --format json",
+            "verify --all --answers a.toml",
+        ];
+        for line in once {
+            assert_eq!(error(line), None, "{line}");
+        }
+    }
+}
@@ -1,3 +1,53 @@
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
+use rand::rngs::StdRng;
+use rand::Rng;
+use rayon::prelude::*;
+use std::cmp::Reverse;
+use std::io::BufRead;
+
+/// An elf of the list: its position in it, counting from 0, and what it carries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Elf {
+    pub index: usize,
+    pub calories: u64,
+    pub items: usize,
+}
+
+/// Reads an empty-string separated list of calories line by line from `reader`,
+/// calling `visit` with every elf in turn.
+fn each_elf(reader: impl BufRead, mut visit: impl FnMut(Elf)) -> Result<(), ParseError> {
+    // current aggregate
+    let mut elf = Elf {
+        index: 0,
+        calories: 0,
+        items: 0,
+    };
+
+    let mut lines = parse::stream(reader);
+    while let Some(line) = lines.next_line()? {
+        if line.text.is_empty() {
+            // on empty lines, store the aggregated value, and start a new one
+            visit(elf);
+            elf = Elf {
+                index: elf.index + 1,
+                calories: 0,
+                items: 0,
+            };
+        } else {
+            // increase the aggregate by the value in this line
+            let calories = line.parse::<u64>(line.text, "calories")?;
+            elf.calories += calories;
+            elf.items += 1;
+        }
+    }
+
+    // remember to store the last elf!
+    visit(elf);
+
+    Ok(())
+}
+
 /// Structure responsible for storing the total calories of N elves.
 /// These are kept in descending order.
 ///
@@ -11,28 +61,44 @@ impl<const N: usize> ChunkiestElves<N> {
     /// aggregating by elf and remembering the top N.
     ///
     /// See `mock.txt` or `input.txt` for examples on the formatting.
-    fn parse(values: &str) -> Self {
-        let mut this = Self { elves: [0; N] };
+    fn parse(values: &str) -> Result<Self, ParseError> {
+        Self::read(values.as_bytes())
+    }
 
-        // current aggregate
-        let mut elf = 0;
-
-        for line in values.lines() {
-            if line.is_empty() {
-                // on empty lines, store the aggregated value, and start a new one
-                this.push(elf);
-                elf = 0;
-            } else {
-                // increase the aggregate by the value in this line
-                let calories = line.parse::<u64>().expect("Cannot parse value");
-                elf += calories;
-            }
-        }
+    /// Same as `parse`, reading the list line by line from `reader`.
+    fn read(reader: impl BufRead) -> Result<Self, ParseError> {
+        let mut this = Self { elves: [0; N] };
+        each_elf(reader, |elf| this.push(elf.calories))?;
+        Ok(this)
+    }
 
-        // remember to store the last elf!
-        this.push(elf);
+    /// Same as `parse`, reading chunks of the list in parallel.
+    fn par_parse(values: &str) -> Result<Self, ParseError> {
+        Self::par_parse_in(values, rayon::current_num_threads() * 4)
+    }
 
-        this
+    /// Same as `parse`, reading about `count` chunks of the list in parallel, then merging
+    /// their top N in order, for the first error to be the one reported.
+    fn par_parse_in(values: &str, count: usize) -> Result<Self, ParseError> {
+        chunks(values, count)
+            .into_par_iter()
+            .map(|(start, chunk)| {
+                Self::parse(chunk).map_err(|mut err| {
+                    // the chunk numbers its lines from its start
+                    err.line += values[..start].matches('\n').count();
+                    err
+                })
+            })
+            .reduce(
+                || Ok(Self { elves: [0; N] }),
+                |this, other| {
+                    let (mut this, other) = (this?, other?);
+                    for elf in other.elves {
+                        this.push(elf);
+                    }
+                    Ok(this)
+                },
+            )
     }
 
     /// Consider the given Elf, and store the value at the correct position if it is amongst the top N.
@@ -49,33 +115,255 @@ impl<const N: usize> ChunkiestElves<N> {
     }
 }
 
-pub fn star_one() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-    ChunkiestElves::<1>::parse(values).elves[0]
+/// Splits the list in about `count` chunks of whole elves, at blank lines which are left out,
+/// along with the offset of each chunk.
+fn chunks(values: &str, count: usize) -> Vec<(usize, &str)> {
+    let size = values.len() / count.max(1) + 1;
+    // the start of the first line from `offset` on
+    let line_start = |offset: usize| match offset {
+        0 => 0,
+        _ if offset >= values.len() || values.as_bytes()[offset - 1] == b'\n' => offset,
+        _ => values.as_bytes()[offset..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(values.len(), |i| offset + i + 1),
+    };
+
+    let mut chunks = vec![];
+    let mut start = 0;
+    loop {
+        let mut offset = line_start((start + size).min(values.len()));
+        let blank = values[offset..].split_inclusive('\n').find_map(|line| {
+            let blank = line.trim_end().is_empty();
+            offset += line.len();
+            blank.then_some((offset - line.len(), offset))
+        });
+        match blank {
+            Some((end, next)) => {
+                chunks.push((start, &values[start..end]));
+                start = next;
+            }
+            None => {
+                chunks.push((start, &values[start..]));
+                return chunks;
+            }
+        }
+    }
+}
+
+/// Every elf of the list, ranked by total calories, most first. Elves carrying as much keep
+/// the order of the list.
+///
+/// Unlike `ChunkiestElves`, the number of elves to rank is only chosen once the list is read,
+/// and may change from a query to the next.
+pub struct Ranking {
+    elves: Vec<Elf>,
+}
+
+impl Ranking {
+    /// Reads an empty-string separated list of calories, ranking every elf.
+    pub fn parse(values: &str) -> Result<Self, ParseError> {
+        Self::read(values.as_bytes())
+    }
+
+    /// Same as `parse`, reading the list line by line from `reader`.
+    pub fn read(reader: impl BufRead) -> Result<Self, ParseError> {
+        let mut elves = vec![];
+        each_elf(reader, |elf| elves.push(elf))?;
+        elves.sort_by_key(|elf| Reverse(elf.calories));
+        Ok(Self { elves })
+    }
+
+    /// Every elf, in ranking order. There is always one at least.
+    pub fn elves(&self) -> &[Elf] {
+        &self.elves
+    }
+
+    /// The `n` elves carrying the most calories, or every elf if there are fewer.
+    pub fn top(&self, n: usize) -> &[Elf] {
+        &self.elves[..n.min(self.elves.len())]
+    }
+
+    /// The calories carried by the `n` elves carrying the most.
+    pub fn total(&self, n: usize) -> u64 {
+        self.top(n).iter().map(|elf| elf.calories).sum()
+    }
+
+    /// The calories carried by an elf, on average.
+    pub fn mean(&self) -> f64 {
+        self.total(self.elves.len()) as f64 / self.elves.len() as f64
+    }
+
+    /// The calories carried by the middle elf, or the mean of the middle two.
+    pub fn median(&self) -> f64 {
+        let len = self.elves.len();
+        let middle = self.elves[len / 2].calories as f64;
+        match len % 2 {
+            0 => (self.elves[len / 2 - 1].calories as f64 + middle) / 2.0,
+            _ => middle,
+        }
+    }
+
+    /// The fewest calories carried by an elf along with `p`% of the elves carrying less or as
+    /// much (nearest-rank method), or None unless `p` is between 0 and 100.
+    pub fn percentile(&self, p: f64) -> Option<u64> {
+        if !(0.0..=100.0).contains(&p) {
+            return None;
+        }
+        let len = self.elves.len();
+        let rank = ((p / 100.0 * len as f64).ceil() as usize).clamp(1, len);
+        Some(self.elves[len - rank].calories)
+    }
+}
+
+pub fn star_one(values: &str) -> Result<u64, ParseError> {
+    Ok(ChunkiestElves::<1>::par_parse(values)?.elves[0])
+}
+
+pub fn star_two(values: &str) -> Result<u64, ParseError> {
+    Ok(ChunkiestElves::<3>::par_parse(values)?
+        .elves
+        .into_iter()
+        .sum())
+}
+
+/// Same as `star_one`, reading the list line by line from `reader`.
+pub fn star_one_from(reader: impl BufRead) -> Result<u64, ParseError> {
+    Ok(ChunkiestElves::<1>::read(reader)?.elves[0])
 }
 
-pub fn star_two() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-    ChunkiestElves::<3>::parse(values).elves.into_iter().sum()
+/// Same as `star_two`, reading the list line by line from `reader`.
+pub fn star_two_from(reader: impl BufRead) -> Result<u64, ParseError> {
+    Ok(ChunkiestElves::<3>::read(reader)?.elves.into_iter().sum())
+}
+
+/// A list of `size` elves, carrying 1 to 15 items of 1 to 60000 calories each.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    let elves = (0..size).map(|_| {
+        let items = rng.gen_range(1..=15);
+        (0..items)
+            .map(|_| format!("{}\n", rng.gen_range(1..=60_000)))
+            .collect::<String>()
+    });
+    elves.collect::<Vec<_>>().join("\n")
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        1
+    }
+
+    fn title(&self) -> &'static str {
+        "Calorie Counting"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn stream(&self, part: u8, reader: &mut dyn BufRead) -> Option<Result<Answer, ParseError>> {
+        match part {
+            1 => Some(star_one_from(reader).map(Answer::from)),
+            2 => Some(star_two_from(reader).map(Answer::from)),
+            _ => None,
+        }
+    }
+
+    fn generate(&self, rng: &mut StdRng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 24000);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(24000));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 45000);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(45000));
+    }
+
+    #[test]
+    fn test_ranking() {
+        let ranking = Ranking::parse(include_str!("mock.txt")).unwrap();
+        let elves = ranking
+            .elves()
+            .iter()
+            .map(|elf| (elf.index, elf.calories, elf.items));
+        let expected = [
+            (3, 24000, 3),
+            (2, 11000, 2),
+            (4, 10000, 1),
+            (0, 6000, 3),
+            (1, 4000, 1),
+        ];
+        assert_eq!(elves.collect::<Vec<_>>(), expected);
+        assert_eq!(ranking.total(1), 24000);
+        assert_eq!(ranking.total(3), 45000);
+        assert_eq!(ranking.top(9).len(), 5);
+        assert_eq!(ranking.mean(), 11000.0);
+        assert_eq!(ranking.median(), 10000.0);
+        let percentiles = [0.0, 25.0, 50.0, 90.0, 100.0].map(|p| ranking.percentile(p));
+        assert_eq!(percentiles, [4000, 6000, 10000, 24000, 24000].map(Some));
+        assert_eq!(ranking.percentile(-1.0), None);
+        assert_eq!(ranking.percentile(100.5), None);
+        assert_eq!(ranking.percentile(f64::NAN), None);
+
+        let ranking = Ranking::parse("5\n\n1\n\n5\n\n2\n").unwrap();
+        let indices = ranking.elves().iter().map(|elf| elf.index);
+        assert_eq!(indices.collect::<Vec<_>>(), [0, 2, 3, 1]);
+        assert_eq!(ranking.median(), 3.5);
+    }
+
+    #[test]
+    fn test_par_parse() {
+        let top = |values: &str, count| {
+            let sequential = ChunkiestElves::<3>::parse(values).map(|this| this.elves);
+            let parallel = ChunkiestElves::<3>::par_parse_in(values, count).map(|this| this.elves);
+            (parallel, sequential)
+        };
+        for count in 1..=8 {
+            testing::differential(
+                20,
+                |rng| generate(rng, 12),
+                |values| top(values, count).0,
+                |values| top(values, count).1,
+            );
+        }
+
+        // blank lines of whitespace, runs of blank lines, and errors late in the list
+        let values = "1\n2\n \n\n\n7\r\n\r\n3\n\n4\n\n5\n\n6\n\n";
+        let error = "1\n\n2\n\n3\n\n4\n\n5\nfive\n\n6\n";
+        for count in 1..=values.len() {
+            let (parallel, sequential) = top(values, count);
+            assert_eq!(parallel, sequential);
+            assert_eq!(parallel, Ok([7, 6, 5]));
+            let (parallel, sequential) = top(error, count);
+            assert_eq!(parallel, sequential);
+            assert_eq!(
+                parallel,
+                Err(ParseError::new(10, 1, "expected calories, found `five`"))
+            );
+        }
+    }
+
+    #[test]
+    fn test_stream() {
+        // a tiny buffer, for lines to span several reads
+        let reader = || std::io::BufReader::with_capacity(3, include_str!("mock.txt").as_bytes());
+        assert_eq!(star_one_from(reader()), Ok(24000));
+        assert_eq!(star_two_from(reader()), Ok(45000));
     }
 }
@@ -0,0 +1,158 @@
+use super::cpu::{self, Handheld, Instruction, Isa, Registers};
+use crate::parse::{self, ParseError};
+
+const WIDTH: usize = 40;
+const HEIGHT: usize = 6;
+
+/// The values of `X` worth trying: further left or right, the sprite lights nothing either.
+const XS: std::ops::RangeInclusive<i64> = -2..=WIDTH as i64 + 1;
+
+/// Compiles an image of the screen, rows of `#` and `.`, into a program drawing it.
+/// Fails on the first pixel which cannot be drawn along with those before it, as the sprite
+/// only moves at the end of an `addx`.
+pub fn assemble(values: &str) -> Result<String, ParseError> {
+    let mut lit = Vec::with_capacity(WIDTH * HEIGHT);
+    let mut rows = 0;
+    for line in parse::lines(values) {
+        if rows == HEIGHT {
+            return Err(line.error_at(0, format!("expected {HEIGHT} rows")));
+        }
+        for (offset, pixel) in line.text.char_indices() {
+            match pixel {
+                '#' => lit.push(true),
+                '.' => lit.push(false),
+                _ => return Err(line.error_at(offset, "expected `#` or `.`")),
+            }
+        }
+        let width = line.text.chars().count();
+        if width != WIDTH {
+            let message = format!("expected {WIDTH} pixels, found {width}");
+            return Err(line.error_at(line.text.len(), message));
+        }
+        rows += 1;
+    }
+    if rows < HEIGHT {
+        let message = format!("expected {HEIGHT} rows, found {rows}");
+        return Err(ParseError::new(rows + 1, 1, message));
+    }
+
+    let covers = |cycle: usize, x: i64| lit[cycle] == (x.abs_diff((cycle % WIDTH) as i64) < 2);
+    let index = |x: i64| (x - XS.start()) as usize;
+
+    // the values of `X` possible at the start of each instruction, with the start and value
+    // of `X` of the one before
+    let cycles = lit.len();
+    let mut parents = vec![vec![None; XS.count()]; cycles + 1];
+    parents[0][index(1)] = Some((0, 1));
+    let mut furthest = 0;
+    for start in 0..cycles {
+        for x in XS {
+            if parents[start][index(x)].is_none() || !covers(start, x) {
+                continue;
+            }
+            furthest = furthest.max(start + 1);
+            parents[start + 1][index(x)].get_or_insert((start, x));
+            if start + 1 < cycles && covers(start + 1, x) {
+                furthest = furthest.max(start + 2);
+                for next in XS {
+                    parents[start + 2][index(next)].get_or_insert((start, x));
+                }
+            }
+        }
+    }
+
+    let Some(mut x) = XS.clone().find(|&x| parents[cycles][index(x)].is_some()) else {
+        let pixel = furthest.min(cycles - 1);
+        let (row, column) = (pixel / WIDTH, pixel % WIDTH);
+        let message = "this pixel cannot be drawn along with those before it";
+        return Err(ParseError::new(row + 1, column + 1, message));
+    };
+    let mut program = vec![];
+    let mut end = cycles;
+    while end > 0 {
+        let (start, previous) = parents[end][index(x)].unwrap();
+        program.push(match end - start {
+            1 => Instruction::Noop,
+            _ => Instruction::Addx(x - previous),
+        });
+        (end, x) = (start, previous);
+    }
+
+    let program = program
+        .iter()
+        .rev()
+        .map(|instruction| format!("{instruction}\n"));
+    Ok(program.collect())
+}
+
+/// Pretty-prints a program, one instruction per line followed by a comment telling during
+/// which cycles it runs and the value of `X` meanwhile. The listing runs like the program.
+pub fn disassemble(values: &str) -> Result<String, ParseError> {
+    let mut listing = String::new();
+    let mut registers = Registers::default();
+    let mut cycle = 1;
+    for line in parse::lines(values) {
+        let line = cpu::code(line);
+        if line.text.is_empty() {
+            continue;
+        }
+        let instruction = Handheld.decode(&line)?;
+        let cycles = Handheld.cycles(instruction);
+        let span = match cycles {
+            1 => format!("cycle {cycle}"),
+            _ => format!("cycles {cycle}-{}", cycle + cycles - 1),
+        };
+        let x = registers.x;
+        listing.push_str(&format!(
+            "{:<10} ; {span}, X = {x}\n",
+            instruction.to_string()
+        ));
+        Handheld.execute(instruction, &mut registers);
+        cycle += cycles;
+    }
+    Ok(listing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day10::screen;
+
+    #[test]
+    fn test_assemble() {
+        let image = screen(include_str!("mock.txt")).unwrap();
+        let values = image
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&lit| if lit { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let program = assemble(&values).unwrap();
+        assert_eq!(screen(&program), Ok(image));
+
+        // `X` is 1 during the first two cycles
+        let values = values.replacen("##..", "#...", 1);
+        let error = ParseError::new(
+            1,
+            2,
+            "this pixel cannot be drawn along with those before it",
+        );
+        assert_eq!(assemble(&values), Err(error));
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let listing = disassemble("noop\naddx 3\n  addx   -5\n").unwrap();
+        let expected = "\
+noop       ; cycle 1, X = 1
+addx 3     ; cycles 2-3, X = 1
+addx -5    ; cycles 4-5, X = 4
+";
+        assert_eq!(listing, expected);
+        assert_eq!(disassemble(&listing), Ok(listing.clone()));
+        assert_eq!(screen(&listing), screen("noop\naddx 3\naddx -5\n"));
+    }
+}
@@ -0,0 +1,322 @@
+use crate::parse::{self, Line, ParseError, Stream};
+use std::fmt;
+use std::io::BufRead;
+
+/// The registers of the CPU. `pc` is the index in the program of the next instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Registers {
+    pub x: i64,
+    pub y: i64,
+    pub pc: usize,
+}
+
+impl Default for Registers {
+    fn default() -> Self {
+        Self { x: 1, y: 0, pc: 0 }
+    }
+}
+
+/// An instruction set: how its instructions are read, how long they take and what they do.
+pub trait Isa {
+    type Instruction: Copy;
+
+    /// Whether `execute` may move `pc` elsewhere than past the instruction, so that the
+    /// program must be kept to go back in it. Otherwise it runs as it is read.
+    const JUMPS: bool = false;
+
+    fn decode(&self, line: &Line) -> Result<Self::Instruction, ParseError>;
+
+    /// How many cycles the instruction takes, at least one.
+    fn cycles(&self, instruction: Self::Instruction) -> usize;
+
+    /// Applies the instruction once its last cycle is over, `pc` already pointing past it.
+    fn execute(&self, instruction: Self::Instruction, registers: &mut Registers);
+}
+
+/// An instruction of the handheld device of the puzzle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    Noop,
+    Addx(i64),
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Noop => write!(f, "noop"),
+            Instruction::Addx(value) => write!(f, "addx {value}"),
+        }
+    }
+}
+
+/// The code of a line, without its comment, from a `;` on, nor trailing whitespace.
+pub fn code<'a>(line: Line<'a>) -> Line<'a> {
+    let text = line.text.split(';').next().unwrap_or_default().trim_end();
+    Line { text, ..line }
+}
+
+/// The instruction set of the handheld device of the puzzle.
+pub struct Handheld;
+
+impl Isa for Handheld {
+    type Instruction = Instruction;
+
+    fn decode(&self, line: &Line) -> Result<Instruction, ParseError> {
+        let mut tokens = line.tokens();
+        let instruction = match tokens.expect("instruction")? {
+            "noop" => Instruction::Noop,
+            "addx" => Instruction::Addx(tokens.parse("addx operand")?),
+            token => return Err(line.error(token, format!("unrecognised instruction `{token}`"))),
+        };
+        tokens.end()?;
+        Ok(instruction)
+    }
+
+    fn cycles(&self, instruction: Instruction) -> usize {
+        match instruction {
+            Instruction::Noop => 1,
+            Instruction::Addx(_) => 2,
+        }
+    }
+
+    fn execute(&self, instruction: Instruction, registers: &mut Registers) {
+        if let Instruction::Addx(value) = instruction {
+            registers.x += value;
+        }
+    }
+}
+
+/// An instruction of `Extended`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExtendedInstruction {
+    Handheld(Instruction),
+    Addy(i64),
+    /// Moves by the given number of instructions from the jump itself.
+    Jmp(isize),
+}
+
+/// The instruction set of the handheld device, along with a `Y` register and relative jumps.
+pub struct Extended;
+
+impl Isa for Extended {
+    type Instruction = ExtendedInstruction;
+
+    const JUMPS: bool = true;
+
+    fn decode(&self, line: &Line) -> Result<ExtendedInstruction, ParseError> {
+        let mut tokens = line.tokens();
+        let instruction = match tokens.next() {
+            Some("addy") => ExtendedInstruction::Addy(tokens.parse("addy operand")?),
+            Some("jmp") => ExtendedInstruction::Jmp(tokens.parse("jump offset")?),
+            _ => return Handheld.decode(line).map(ExtendedInstruction::Handheld),
+        };
+        tokens.end()?;
+        Ok(instruction)
+    }
+
+    fn cycles(&self, instruction: ExtendedInstruction) -> usize {
+        match instruction {
+            ExtendedInstruction::Handheld(instruction) => Handheld.cycles(instruction),
+            ExtendedInstruction::Addy(_) => 2,
+            ExtendedInstruction::Jmp(_) => 1,
+        }
+    }
+
+    fn execute(&self, instruction: ExtendedInstruction, registers: &mut Registers) {
+        match instruction {
+            ExtendedInstruction::Handheld(instruction) => Handheld.execute(instruction, registers),
+            ExtendedInstruction::Addy(value) => registers.y += value,
+            // jumping before the program halts it like jumping past its end does
+            ExtendedInstruction::Jmp(offset) => {
+                registers.pc = (registers.pc - 1)
+                    .checked_add_signed(offset)
+                    .unwrap_or(usize::MAX)
+            }
+        }
+    }
+}
+
+/// Something watching the value of `X` during every cycle of an execution.
+pub trait Observer {
+    fn observe(&mut self, cycle: usize, x: i64);
+}
+
+/// Sums the signal strengths during the 20th cycle then every 40 cycles.
+#[derive(Default)]
+pub struct SignalStrength {
+    pub sum: i64,
+}
+
+impl Observer for SignalStrength {
+    fn observe(&mut self, cycle: usize, x: i64) {
+        if cycle % 40 == 20 {
+            self.sum += cycle as i64 * x;
+        }
+    }
+}
+
+/// Draws a pixel per cycle on a screen 40 pixels wide, lit when the sprite centred on `X`
+/// covers it.
+#[derive(Default)]
+pub struct Crt {
+    pub pixels: Vec<Vec<bool>>,
+}
+
+impl Observer for Crt {
+    fn observe(&mut self, cycle: usize, x: i64) {
+        let column = (cycle - 1) % 40;
+        if column == 0 {
+            self.pixels.push(Vec::with_capacity(40));
+        }
+        let row = self.pixels.last_mut().unwrap();
+        row.push(x.abs_diff(column as i64) < 2);
+    }
+}
+
+/// Runs a program read line by line, decoding each line the first time it is reached, and
+/// yields `(cycle, X)` during every cycle, counting from 1. Stops at the first error.
+/// Comments and blank lines are skipped.
+pub struct Cpu<I: Isa, R> {
+    isa: I,
+    lines: Stream<R>,
+    /// The instructions read so far, kept only if the instruction set jumps.
+    program: Vec<I::Instruction>,
+    registers: Registers,
+    cycle: usize,
+    /// The instruction being executed and how many cycles it still takes.
+    current: Option<(I::Instruction, usize)>,
+    halted: bool,
+}
+
+impl<I: Isa, R: BufRead> Cpu<I, R> {
+    pub fn new(isa: I, reader: R) -> Self {
+        Self {
+            isa,
+            lines: parse::stream(reader),
+            program: vec![],
+            registers: Registers::default(),
+            cycle: 0,
+            current: None,
+            halted: false,
+        }
+    }
+
+    pub fn registers(&self) -> Registers {
+        self.registers
+    }
+
+    /// Runs the program to its end, showing every cycle to every observer.
+    pub fn run(&mut self, observers: &mut [&mut dyn Observer]) -> Result<(), ParseError> {
+        for state in self {
+            let (cycle, x) = state?;
+            for observer in observers.iter_mut() {
+                observer.observe(cycle, x);
+            }
+        }
+        Ok(())
+    }
+
+    /// The instruction at `pc`, reading the program up to it if needed, or None past its end.
+    fn fetch(&mut self) -> Result<Option<I::Instruction>, ParseError> {
+        if !I::JUMPS {
+            // `pc` only ever steps past the last instruction read, to the next one
+            return self.read();
+        }
+        let pc = self.registers.pc;
+        while self.program.len() <= pc {
+            match self.read()? {
+                Some(instruction) => self.program.push(instruction),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(self.program[pc]))
+    }
+
+    /// The next instruction of the program, or None at its end.
+    fn read(&mut self) -> Result<Option<I::Instruction>, ParseError> {
+        while let Some(line) = self.lines.next_line()? {
+            let line = code(line);
+            if !line.text.is_empty() {
+                return self.isa.decode(&line).map(Some);
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<I: Isa, R: BufRead> Iterator for Cpu<I, R> {
+    type Item = Result<(usize, i64), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.halted {
+            return None;
+        }
+        let (instruction, cycles) = match self.current {
+            Some(current) => current,
+            None => match self.fetch() {
+                Ok(Some(instruction)) => {
+                    self.registers.pc += 1;
+                    (instruction, self.isa.cycles(instruction).max(1))
+                }
+                Ok(None) => {
+                    self.halted = true;
+                    return None;
+                }
+                Err(err) => {
+                    self.halted = true;
+                    return Some(Err(err));
+                }
+            },
+        };
+
+        self.cycle += 1;
+        let state = (self.cycle, self.registers.x);
+        self.current = match cycles {
+            1 => {
+                self.isa.execute(instruction, &mut self.registers);
+                None
+            }
+            _ => Some((instruction, cycles - 1)),
+        };
+        Some(Ok(state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_states() {
+        let mut cpu = Cpu::new(Handheld, "noop\naddx 3\n; x\naddx -5\n".as_bytes());
+        let states = cpu.by_ref().collect::<Result<Vec<_>, _>>();
+        assert_eq!(states, Ok(vec![(1, 1), (2, 1), (3, 1), (4, 4), (5, 4)]));
+        // it cannot jump, so nothing was kept of the program
+        assert!(cpu.program.is_empty());
+        assert_eq!(cpu.registers().pc, 3);
+    }
+
+    #[test]
+    fn test_extended() {
+        // skips `addx 100`, then loops over `addy 5` and `addx 1`
+        let program = "addx 1\njmp 2\naddx 100\naddy 5\naddx 1\njmp -2\n";
+        let mut cpu = Cpu::new(Extended, program.as_bytes());
+        let states = cpu
+            .by_ref()
+            .take(12)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let xs = states.iter().map(|&(_, x)| x).collect::<Vec<_>>();
+        assert_eq!(xs, [1, 1, 2, 2, 2, 2, 2, 3, 3, 3, 3, 3]);
+        assert_eq!(cpu.registers().y, 10);
+    }
+
+    #[test]
+    fn test_error() {
+        let mut cpu = Cpu::new(Handheld, "noop\naddy 1\n".as_bytes());
+        assert_eq!(cpu.next(), Some(Ok((1, 1))));
+        let error = ParseError::new(2, 1, "unrecognised instruction `addy`");
+        assert_eq!(cpu.next(), Some(Err(error)));
+        assert_eq!(cpu.next(), None);
+    }
+}
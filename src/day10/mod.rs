@@ -1,146 +1,204 @@
-pub fn star_one() -> i64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    enum Command {
-        Noop,
-        Addx { value: i64, elapsed: bool },
-    }
+pub mod asm;
+pub mod cpu;
 
-    let mut signal = 0;
-    let mut x = 1i64;
-    let mut cycle = 1i64;
-    let mut command = None;
+use crate::parse::ParseError;
+use crate::solution::{Answer, Solution};
+use cpu::{Cpu, Crt, Handheld, SignalStrength};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use std::io::BufRead;
 
-    let mut lines = values.lines();
-    loop {
-        if cycle % 40 == 20 {
-            signal += cycle * x;
-        }
-        if command.is_none() {
-            let Some(line) = lines.next() else { break; };
-            let mut tokens = line.split_whitespace();
-            let token = tokens.next().expect("command should not be empty");
-            match token {
-                "addx" => {
-                    let value = tokens
-                        .next()
-                        .expect("addx should have an operand")
-                        .parse::<i64>()
-                        .expect("operand should be a number");
-                    command = Some(Command::Addx {
-                        value,
-                        elapsed: false,
-                    });
-                }
-                "noop" => {
-                    command = Some(Command::Noop);
-                }
-                _ => unreachable!("unrecognised command"),
-            }
-        }
-        match command.take() {
-            Some(cmd) => match cmd {
-                Command::Noop => {}
-                Command::Addx { value, elapsed } => {
-                    if elapsed {
-                        x += value;
-                    } else {
-                        command = Some(Command::Addx {
-                            value,
-                            elapsed: true,
-                        });
-                    }
-                }
-            },
-            _ => unreachable!("command should have been loaded"),
-        }
-        cycle += 1;
-    }
+pub fn star_one(values: &str) -> Result<i64, ParseError> {
+    star_one_from(values.as_bytes())
+}
 
-    signal
+/// The capital letters drawn on the screen.
+pub fn star_two(values: &str) -> Result<String, ParseError> {
+    star_two_from(values.as_bytes())
 }
 
-pub fn star_two() -> String {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
+/// The pixels of the screen, row by row, lit where the sprite was being drawn.
+pub fn screen(values: &str) -> Result<Vec<Vec<bool>>, ParseError> {
+    screen_from(values.as_bytes())
+}
+
+/// The 4×6 glyphs of the capital letters the screens draw, rows top to bottom.
+const FONT: [(char, [&str; 6]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
 
-    enum Command {
-        Noop,
-        Addx { value: i64, elapsed: bool },
+/// Reads the letters drawn on a screen six pixels high, each four pixels wide and followed by
+/// a blank column. Glyphs out of the font are read as `?`.
+pub fn read(pixels: &[Vec<bool>]) -> String {
+    let width = pixels.iter().map(Vec::len).max().unwrap_or(0);
+    (0..width.div_ceil(5))
+        .map(|i| {
+            let glyph = FONT.iter().find(|(_, glyph)| {
+                pixels.len() == glyph.len()
+                    && pixels.iter().zip(glyph).all(|(row, glyph)| {
+                        (0..4).all(|j| {
+                            row.get(5 * i + j).copied().unwrap_or(false)
+                                == (glyph.as_bytes()[j] == b'#')
+                        })
+                    })
+            });
+            glyph.map_or('?', |&(letter, _)| letter)
+        })
+        .collect()
+}
+
+/// Same as `star_one`, reading the program line by line from `reader`.
+pub fn star_one_from(reader: impl BufRead) -> Result<i64, ParseError> {
+    let mut signal = SignalStrength::default();
+    Cpu::new(Handheld, reader).run(&mut [&mut signal])?;
+    Ok(signal.sum)
+}
+
+/// Same as `star_two`, reading the program line by line from `reader`.
+pub fn star_two_from(reader: impl BufRead) -> Result<String, ParseError> {
+    screen_from(reader).map(|pixels| read(&pixels))
+}
+
+/// Same as `screen`, reading the program line by line from `reader`.
+pub fn screen_from(reader: impl BufRead) -> Result<Vec<Vec<bool>>, ParseError> {
+    let mut crt = Crt::default();
+    Cpu::new(Handheld, reader).run(&mut [&mut crt])?;
+    Ok(crt.pixels)
+}
+
+/// A program drawing `size` random letters of the font, but at most 8, the rest of the screen
+/// left blank. As `X` starts at 1, the first letter is one of those starting with two pixels.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    let firsts = FONT.iter().filter(|(_, glyph)| glyph[0].starts_with("##"));
+    let mut letters = vec![*firsts.collect::<Vec<_>>().choose(rng).unwrap()];
+    letters.extend((1..size.clamp(1, 8)).map(|_| FONT.choose(rng).unwrap()));
+    let rows = (0..6).map(|row| {
+        let glyphs = (0..8).map(|i| match letters.get(i) {
+            Some((_, glyph)) => format!("{}.", glyph[row]),
+            None => ".....".to_string(),
+        });
+        glyphs.collect::<String>()
+    });
+    let image = rows.collect::<Vec<_>>().join("\n");
+    asm::assemble(&image).expect("the letters of the font should be drawable")
+}
+
+/// The letters on the screen, keeping its pixels for those who would rather see them.
+fn answer(pixels: Vec<Vec<bool>>) -> Answer {
+    Answer::Screen {
+        letters: read(&pixels),
+        pixels,
     }
+}
 
-    let mut output = "\n".to_string();
-    let mut x = 1i64;
-    let mut cycle = 1i64;
-    let mut command = None;
-
-    let mut lines = values.lines();
-    loop {
-        if command.is_none() {
-            let Some(line) = lines.next() else { break; };
-            let mut tokens = line.split_whitespace();
-            let token = tokens.next().expect("command should not be empty");
-            match token {
-                "addx" => {
-                    let value = tokens
-                        .next()
-                        .expect("addx should have an operand")
-                        .parse::<i64>()
-                        .expect("operand should be a number");
-                    command = Some(Command::Addx {
-                        value,
-                        elapsed: false,
-                    });
-                }
-                "noop" => {
-                    command = Some(Command::Noop);
-                }
-                _ => unreachable!("unrecognised command"),
-            }
-        }
+pub struct Solver;
 
-        if i64::abs_diff(x, (cycle - 1) % 40) < 2 {
-            output.push('#');
-        } else {
-            output.push('.');
-        }
-        if cycle % 40 == 0 {
-            output.push('\n');
-        }
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        10
+    }
 
-        match command.take() {
-            Some(cmd) => match cmd {
-                Command::Noop => {}
-                Command::Addx { value, elapsed } => {
-                    if elapsed {
-                        x += value;
-                    } else {
-                        command = Some(Command::Addx {
-                            value,
-                            elapsed: true,
-                        });
-                    }
-                }
-            },
-            _ => unreachable!("command should have been loaded"),
+    fn title(&self) -> &'static str {
+        "Cathode-Ray Tube"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(screen(input).map(answer))
+    }
+
+    fn stream(&self, part: u8, reader: &mut dyn BufRead) -> Option<Result<Answer, ParseError>> {
+        match part {
+            1 => Some(star_one_from(reader).map(Answer::from)),
+            2 => Some(screen_from(reader).map(answer)),
+            _ => None,
         }
-        cycle += 1;
     }
 
-    output
+    fn generate(&self, rng: &mut StdRng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 13140);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(13140));
+    }
+
+    #[test]
+    fn test_screen() {
+        let expected = [
+            "##..##..##..##..##..##..##..##..##..##..",
+            "###...###...###...###...###...###...###.",
+            "####....####....####....####....####....",
+            "#####.....#####.....#####.....#####.....",
+            "######......######......######......####",
+            "#######.......#######.......#######.....",
+        ];
+        let expected = expected.map(|row| row.bytes().map(|b| b == b'#').collect::<Vec<_>>());
+        assert_eq!(screen(include_str!("mock.txt")), Ok(expected.to_vec()));
+        assert_eq!(
+            star_two(include_str!("mock.txt")),
+            Ok("????????".to_string())
+        );
+    }
+
+    #[test]
+    fn test_read() {
+        let pixels = |rows: [&str; 6]| rows.map(|row| row.bytes().map(|b| b == b'#').collect());
+        for (letter, glyph) in FONT {
+            assert_eq!(read(&pixels(glyph)), letter.to_string());
+        }
+        let pixels = pixels([
+            "####...##..##..",
+            "#.......#.#..#.",
+            "###.....#.#....",
+            "#.......#.#....",
+            "#....#..#.#..#.",
+            "####..##...##..",
+        ]);
+        assert_eq!(read(&pixels), "EJC");
+    }
+
+    #[test]
+    fn test_generate() {
+        testing::check(
+            20,
+            |rng| generate(rng, 8),
+            |values| {
+                let letters = star_two(values).map_err(|err| err.to_string())?;
+                match letters
+                    .chars()
+                    .all(|letter| FONT.iter().any(|&(l, _)| l == letter))
+                {
+                    true => Ok(()),
+                    false => Err(format!("read `{letters}`")),
+                }
+            },
+        );
     }
 }
@@ -1,3 +1,8 @@
+use crate::parse::{self, Line, Lines, ParseError};
+use crate::solution::{Answer, Solution};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use std::collections::VecDeque;
 
 type Worry = u64;
@@ -10,85 +15,106 @@ struct Monkey {
 }
 
 impl Monkey {
-    fn new(
-        line_items: &str,
-        line_op: &str,
-        line_test: &str,
-        line_true: &str,
-        line_false: &str,
-    ) -> Self {
-        let items = line_items
-            .split_whitespace()
-            .skip(2)
-            .map(|item| {
-                item.strip_suffix(",")
-                    .unwrap_or(item)
-                    .parse::<Worry>()
-                    .unwrap()
-            })
-            .collect::<VecDeque<_>>();
-        let op = {
-            let mut ops = line_op.split_whitespace().skip(4);
-            let sign = match ops.next().unwrap() {
-                "+" => "+",
-                "*" => "*",
-                _ => unreachable!(),
-            };
-            let op = ops.next().unwrap().parse::<Worry>().ok();
-            Box::new(move |old: Worry| match sign {
-                "+" => old + op.unwrap_or(old),
-                "*" => old * op.unwrap_or(old),
-                _ => unreachable!(),
-            })
+    /// Reads the five lines following a `Monkey N:` header, also returning the lines of the
+    /// targets so they can be checked once every monkey is known.
+    fn new<'a>(lines: &mut Lines<'a>) -> Result<(Self, [Line<'a>; 2]), ParseError> {
+        let line = lines.expect("starting items")?;
+        let mut tokens = line.tokens();
+        tokens.keyword("Starting")?;
+        tokens.keyword("items:")?;
+        let items = tokens
+            .map(|item| line.parse::<Worry>(item.strip_suffix(',').unwrap_or(item), "worry level"))
+            .collect::<Result<VecDeque<_>, _>>()?;
+
+        let line = lines.expect("operation")?;
+        let mut tokens = line.tokens();
+        tokens.keyword("Operation:")?;
+        tokens.keyword("new")?;
+        tokens.keyword("=")?;
+        tokens.keyword("old")?;
+        let sign = match tokens.expect("operator")? {
+            "+" => "+",
+            "*" => "*",
+            token => return Err(line.error(token, format!("unrecognised operator `{token}`"))),
         };
-        let test = line_test
-            .split_whitespace()
-            .last()
-            .unwrap()
-            .parse::<Worry>()
-            .unwrap();
-        let if_true = line_true
-            .split_whitespace()
-            .last()
-            .unwrap()
-            .parse::<usize>()
-            .unwrap();
-        let if_false = line_false
-            .split_whitespace()
-            .last()
-            .unwrap()
-            .parse::<usize>()
-            .unwrap();
-        Self {
+        let op = match tokens.expect("operand")? {
+            "old" => None,
+            token => Some(line.parse::<Worry>(token, "operand")?),
+        };
+        tokens.end()?;
+        let op = Box::new(move |old: Worry| match sign {
+            "+" => old + op.unwrap_or(old),
+            "*" => old * op.unwrap_or(old),
+            _ => unreachable!(),
+        });
+
+        let line = lines.expect("test")?;
+        let mut tokens = line.tokens();
+        tokens.keyword("Test:")?;
+        tokens.keyword("divisible")?;
+        tokens.keyword("by")?;
+        let token = tokens.expect("divisor")?;
+        let test = line.parse::<Worry>(token, "divisor")?;
+        if test == 0 {
+            return Err(line.error(token, "no worry level is divisible by 0"));
+        }
+        tokens.end()?;
+
+        let mut target = |condition: &str| -> Result<(usize, Line<'a>), ParseError> {
+            let line = lines.expect(&format!("`If {condition}` target"))?;
+            let mut tokens = line.tokens();
+            tokens.keyword("If")?;
+            tokens.keyword(condition)?;
+            tokens.keyword("throw")?;
+            tokens.keyword("to")?;
+            tokens.keyword("monkey")?;
+            let target = tokens.parse::<usize>("monkey")?;
+            tokens.end()?;
+            Ok((target, line))
+        };
+        let (if_true, line_true) = target("true:")?;
+        let (if_false, line_false) = target("false:")?;
+
+        let monkey = Self {
             items,
             op,
             test,
             targets: [if_true, if_false],
-        }
+        };
+        Ok((monkey, [line_true, line_false]))
     }
 }
 
-pub fn star_one() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
+fn parse(values: &str) -> Result<Vec<Monkey>, ParseError> {
     let mut monkeys = vec![];
+    let mut target_lines = vec![];
 
-    let mut lines = values.lines();
+    let mut lines = parse::lines(values);
     while let Some(line) = lines.next() {
-        if line.starts_with("Monkey") {
-            monkeys.push(Monkey::new(
-                lines.next().unwrap(),
-                lines.next().unwrap(),
-                lines.next().unwrap(),
-                lines.next().unwrap(),
-                lines.next().unwrap(),
-            ));
+        if line.text.starts_with("Monkey") {
+            let (monkey, lines) = Monkey::new(&mut lines)?;
+            monkeys.push(monkey);
+            target_lines.push(lines);
+        } else if !line.text.is_empty() {
+            return Err(line.error_at(0, "expected `Monkey`"));
         }
     }
 
+    for (monkey, lines) in monkeys.iter().zip(target_lines) {
+        for (&target, line) in monkey.targets.iter().zip(lines) {
+            if target >= monkeys.len() {
+                let at = line.text.trim_end().rsplit(' ').next().unwrap_or_default();
+                return Err(line.error(at, format!("there is no monkey {target}")));
+            }
+        }
+    }
+
+    Ok(monkeys)
+}
+
+pub fn star_one(values: &str) -> Result<u64, ParseError> {
+    let mut monkeys = parse(values)?;
+
     let mut inspections = monkeys.iter().map(|_| 0u64).collect::<Vec<_>>();
 
     for _ in 0..20 {
@@ -112,34 +138,11 @@ pub fn star_one() -> u64 {
     }
 
     inspections.sort();
-    inspections
-        .into_iter()
-        .rev()
-        .take(2)
-        .reduce(|biz, x| biz * x)
-        .unwrap()
+    Ok(inspections.into_iter().rev().take(2).product())
 }
 
-pub fn star_two() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut monkeys = vec![];
-
-    let mut lines = values.lines();
-    while let Some(line) = lines.next() {
-        if line.starts_with("Monkey") {
-            monkeys.push(Monkey::new(
-                lines.next().unwrap(),
-                lines.next().unwrap(),
-                lines.next().unwrap(),
-                lines.next().unwrap(),
-                lines.next().unwrap(),
-            ));
-        }
-    }
+pub fn star_two(values: &str) -> Result<u64, ParseError> {
+    let mut monkeys = parse(values)?;
 
     let common_multiple = monkeys.iter().fold(1u64, |cm, monkey| cm * monkey.test);
 
@@ -166,25 +169,139 @@ pub fn star_two() -> u64 {
     }
 
     inspections.sort();
-    inspections
-        .into_iter()
-        .rev()
-        .take(2)
-        .reduce(|biz, x| biz * x)
-        .unwrap()
+    Ok(inspections.into_iter().rev().take(2).product())
+}
+
+/// Random monkeys, `size` of them but between 2 and 9, each testing a distinct prime and one
+/// of them squaring the worry levels. Monkeys whose 20 rounds of the first part would overflow
+/// are thrown away and drawn again.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    let n = size.clamp(2, 9);
+    loop {
+        let mut primes = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+        primes.shuffle(rng);
+        let squaring = rng.gen_range(0..n);
+        let monkeys = (0..n)
+            .map(|i| {
+                let items = (0..rng.gen_range(1..=4))
+                    .map(|_| rng.gen_range(50..=99))
+                    .collect::<Vec<Worry>>();
+                let op = match i == squaring {
+                    true => None,
+                    false if rng.gen_bool(0.5) => Some(("+", rng.gen_range(1..=8))),
+                    false => Some(("*", rng.gen_range(2..=19))),
+                };
+                let if_true = (i + rng.gen_range(1..n)) % n;
+                let if_false = loop {
+                    let target = (i + rng.gen_range(1..n)) % n;
+                    if target != if_true || n == 2 {
+                        break target;
+                    }
+                };
+                (items, op, primes[i], [if_true, if_false])
+            })
+            .collect::<Vec<_>>();
+
+        let mut items = monkeys.iter().map(|m| m.0.clone()).collect::<Vec<_>>();
+        let overflows = (0..20 * n).any(|turn| {
+            let (_, op, test, targets) = monkeys[turn % n];
+            let thrown = std::mem::take(&mut items[turn % n]);
+            thrown.into_iter().any(|old| {
+                let new = match op {
+                    None => old.checked_mul(old),
+                    Some(("+", k)) => old.checked_add(k),
+                    Some((_, k)) => old.checked_mul(k),
+                };
+                let Some(new) = new.map(|new| new / 3) else {
+                    return true;
+                };
+                items[targets[usize::from(new % test != 0)]].push(new);
+                false
+            })
+        });
+        if overflows {
+            continue;
+        }
+
+        let monkeys = monkeys.into_iter().enumerate().map(|(i, monkey)| {
+            let (items, op, test, [if_true, if_false]) = monkey;
+            let items = items.iter().map(Worry::to_string).collect::<Vec<_>>();
+            let op = match op {
+                None => "* old".to_string(),
+                Some((sign, k)) => format!("{sign} {k}"),
+            };
+            format!(
+                "Monkey {i}:\n  Starting items: {}\n  Operation: new = old {op}\n  \
+                 Test: divisible by {test}\n    If true: throw to monkey {if_true}\n    \
+                 If false: throw to monkey {if_false}\n",
+                items.join(", ")
+            )
+        });
+        return monkeys.collect::<Vec<_>>().join("\n");
+    }
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        11
+    }
+
+    fn title(&self) -> &'static str {
+        "Monkey in the Middle"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn generate(&self, rng: &mut StdRng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 10605);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(10605));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 2713310158);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(2713310158));
+    }
+
+    #[test]
+    fn test_zero_divisor() {
+        let values = include_str!("mock.txt").replacen("divisible by 23", "divisible by 0", 1);
+        let error = ParseError::new(4, 22, "no worry level is divisible by 0");
+        assert_eq!(star_one(&values), Err(error));
+    }
+
+    #[test]
+    fn test_generate() {
+        testing::check(
+            20,
+            |rng| generate(rng, 9),
+            |values| {
+                let monkeys = parse(values).map_err(|err| err.to_string())?;
+                let mut divisors = monkeys.iter().map(|m| m.test).collect::<Vec<_>>();
+                divisors.sort();
+                divisors.dedup();
+                if divisors.len() != monkeys.len() {
+                    return Err("the monkeys share divisors".to_string());
+                }
+                star_one(values).map(|_| ()).map_err(|err| err.to_string())
+            },
+        );
     }
 }
@@ -1,171 +1,121 @@
-use std::collections::BTreeMap;
+use crate::grid::{self, Dense, Grid, Point};
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
 use std::collections::VecDeque;
 
-struct GridMap<T> {
-    data: BTreeMap<(usize, usize), T>,
-    rows: usize,
-    columns: usize,
-}
+/// Reads the heightmap, along with the start and end positions.
+fn parse(values: &str) -> Result<(Dense<u8>, Point, Point), ParseError> {
+    let mut start = None;
+    let mut end = None;
 
-impl<T> GridMap<T> {
-    fn new() -> Self {
-        Self {
-            data: BTreeMap::new(),
-            rows: 0,
-            columns: 0,
+    let map = grid::parse(values, |point, c| match c {
+        'S' => {
+            start = Some(point);
+            Ok(Some(0))
         }
-    }
-
-    fn get(&self, row: usize, column: usize) -> Option<&T> {
-        self.data.get(&(row, column))
-    }
-
-    fn set(&mut self, row: usize, column: usize, value: T) {
-        self.data.insert((row, column), value);
-        self.rows = usize::max(self.rows, row + 1);
-        self.columns = usize::max(self.columns, column + 1);
-    }
+        'E' => {
+            end = Some(point);
+            Ok(Some(25))
+        }
+        'a'..='z' => Ok(Some(c as u8 - b'a')),
+        _ => Err("heights must be lowercase letters".to_string()),
+    })?;
+
+    let rows = parse::lines(values).count();
+    let missing = |what: &str| ParseError::new(rows + 1, 1, format!("the heightmap has no {what}"));
+    let start = start.ok_or_else(|| missing("start `S`"))?;
+    let end = end.ok_or_else(|| missing("end `E`"))?;
+    Ok((map, start, end))
 }
 
-pub fn star_one() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut map = GridMap::<u8>::new();
-    let mut start = (0usize, 0usize, 0u64);
-    let mut end = (0usize, 0usize);
-
-    for (row, line) in values.lines().enumerate() {
-        for (column, c) in line.chars().enumerate() {
-            match c {
-                'S' => {
-                    map.set(row, column, 0);
-                    start = (row, column, 0u64);
-                }
-                'E' => {
-                    map.set(row, column, 25);
-                    end = (row, column);
+/// Walks the map from `start`, returning the fewest steps to every reachable position.
+/// `climbable` tells whether a step between two heights is allowed.
+fn explore(map: &Dense<u8>, start: Point, climbable: impl Fn(u8, u8) -> bool) -> Dense<u64> {
+    let mut paths = Dense::new(map.area());
+    paths.insert(start, 0);
+
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((from, steps)) = queue.pop_front() {
+        let from_height = *map.get(from).unwrap();
+        let neighbours = map
+            .neighbours4(from)
+            .filter(|&(to, &to_height)| {
+                if climbable(from_height, to_height) {
+                    if let Some(&to_steps) = paths.get(to) {
+                        steps + 1 < to_steps
+                    } else {
+                        true
+                    }
+                } else {
+                    false
                 }
-                '\n' => {}
-                _ => map.set(row, column, c as u8 - 97),
-            }
+            })
+            .map(|(to, _)| to)
+            .collect::<Vec<_>>();
+        for to in neighbours {
+            paths.insert(to, steps + 1);
+            queue.push_back((to, steps + 1));
         }
     }
 
-    let mut paths = GridMap::<u64>::new();
-    paths.set(start.0, start.1, 0);
+    paths
+}
 
-    let mut queue = VecDeque::from([start]);
-    while let Some((row, column, steps)) = queue.pop_front() {
-        let mut neighbours = vec![];
-        if row > 0 {
-            neighbours.push((row - 1, column));
-        }
-        if row + 1 < map.rows {
-            neighbours.push((row + 1, column));
-        }
-        if column > 0 {
-            neighbours.push((row, column - 1));
-        }
-        if column + 1 < map.columns {
-            neighbours.push((row, column + 1));
-        }
-        neighbours.retain(|(to_row, to_column)| {
-            let from_height = *map.get(row, column).unwrap();
-            let to_height = *map.get(*to_row, *to_column).unwrap();
-            if to_height <= from_height + 1 {
-                if let Some(&to_steps) = paths.get(*to_row, *to_column) {
-                    steps + 1 < to_steps
-                } else {
-                    true
-                }
-            } else {
-                false
-            }
-        });
-        for (to_row, to_column) in neighbours {
-            paths.set(to_row, to_column, steps + 1);
-            queue.push_back((to_row, to_column, steps + 1));
-        }
-    }
+/// An error located at `point` of the heightmap.
+fn error_at((row, column): Point, message: &str) -> ParseError {
+    ParseError::new(row as usize + 1, column as usize + 1, message)
+}
+
+pub fn star_one(values: &str) -> Result<u64, ParseError> {
+    let (map, start, end) = parse(values)?;
+
+    let paths = explore(&map, start, |from, to| to <= from + 1);
 
-    *paths.get(end.0, end.1).unwrap()
+    paths
+        .get(end)
+        .copied()
+        .ok_or_else(|| error_at(end, "the end `E` cannot be reached from the start `S`"))
 }
 
-pub fn star_two() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
+pub fn star_two(values: &str) -> Result<u64, ParseError> {
+    let (map, _, end) = parse(values)?;
 
-    let mut map = GridMap::<u8>::new();
-    let mut end = (0usize, 0usize);
+    // walking backwards from the end, to every possible start at once
+    let paths = explore(&map, end, |from, to| to + 1 >= from);
 
-    for (row, line) in values.lines().enumerate() {
-        for (column, c) in line.chars().enumerate() {
-            match c {
-                'S' => {
-                    map.set(row, column, 0);
-                }
-                'E' => {
-                    map.set(row, column, 25);
-                    end = (row, column);
+    map.iter()
+        .filter_map(
+            |(point, &height)| {
+                if height == 0 {
+                    paths.get(point)
+                } else {
+                    None
                 }
-                '\n' => {}
-                _ => map.set(row, column, c as u8 - 97),
-            }
-        }
+            },
+        )
+        .min()
+        .copied()
+        .ok_or_else(|| error_at(end, "the end `E` cannot be reached from any `a`"))
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        12
     }
 
-    let mut paths = GridMap::<u64>::new();
+    fn title(&self) -> &'static str {
+        "Hill Climbing Algorithm"
+    }
 
-    let mut queue = VecDeque::from([(end.0, end.1, 0)]);
-    while let Some((row, column, steps)) = queue.pop_front() {
-        let mut neighbours = vec![];
-        if row > 0 {
-            neighbours.push((row - 1, column));
-        }
-        if row + 1 < map.rows {
-            neighbours.push((row + 1, column));
-        }
-        if column > 0 {
-            neighbours.push((row, column - 1));
-        }
-        if column + 1 < map.columns {
-            neighbours.push((row, column + 1));
-        }
-        neighbours.retain(|(to_row, to_column)| {
-            let from_height = *map.get(row, column).unwrap();
-            let to_height = *map.get(*to_row, *to_column).unwrap();
-            if to_height + 1 >= from_height {
-                if let Some(&to_steps) = paths.get(*to_row, *to_column) {
-                    steps + 1 < to_steps
-                } else {
-                    true
-                }
-            } else {
-                false
-            }
-        });
-        for (to_row, to_column) in neighbours {
-            paths.set(to_row, to_column, steps + 1);
-            queue.push_back((to_row, to_column, steps + 1));
-        }
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
     }
 
-    *map.data
-        .into_iter()
-        .filter_map(|((row, column), height)| {
-            if height == 0 {
-                paths.get(row, column)
-            } else {
-                None
-            }
-        })
-        .min()
-        .unwrap()
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
 }
 
 #[cfg(test)]
@@ -174,11 +124,19 @@ mod tests {
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 31);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(31));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 29);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(29));
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let err = ParseError::new(1, 3, "the end `E` cannot be reached from the start `S`");
+        assert_eq!(star_one("SzE"), Err(err));
+        let err = ParseError::new(1, 3, "the end `E` cannot be reached from any `a`");
+        assert_eq!(star_two("SzE"), Err(err));
     }
 }
@@ -1,133 +1,145 @@
+use crate::parse::{self, Line, ParseError};
+use crate::solution::{Answer, Solution};
 use std::cmp::Ordering;
+use std::str::FromStr;
 
-#[derive(PartialEq, Eq, Ord)]
-enum Value {
+/// A packet, or a part of it: an integer or a list of values, ordered as the distress signal
+/// requires.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
     Integer(u64),
-    List(Vec<Box<Value>>),
+    List(Vec<Value>),
 }
 
 impl Value {
-    fn parse(value: &str) -> Self {
+    /// Parses `value`, a slice of `line`.
+    pub fn parse(line: &Line, value: &str) -> Result<Self, ParseError> {
         if let Some(value) = value.strip_prefix('[') {
-            let value = value.strip_suffix(']').unwrap();
+            let value = value
+                .strip_suffix(']')
+                .ok_or_else(|| line.error_after(value, "expected `]`"))?;
             let mut items = Vec::<&str>::new();
             if !value.is_empty() {
                 let mut depth = 0usize;
                 let mut start = 0usize;
-                for (end, c) in value.chars().enumerate() {
+                for (end, c) in value.char_indices() {
                     match c {
                         '[' => {
                             depth += 1;
                         }
                         ']' => {
-                            depth -= 1;
+                            depth = depth
+                                .checked_sub(1)
+                                .ok_or_else(|| line.error(&value[end..], "unmatched `]`"))?;
                         }
-                        ',' => {
-                            if depth == 0 {
-                                items.push(&value[start..end]);
-                                start = end + 1;
-                            }
+                        ',' if depth == 0 => {
+                            items.push(&value[start..end]);
+                            start = end + 1;
                         }
                         _ => {}
                     }
                 }
+                if depth > 0 {
+                    return Err(line.error_after(value, "expected `]`"));
+                }
                 items.push(&value[start..]);
             }
 
-            Self::List(items.into_iter().map(Self::parse).map(Box::new).collect())
+            items
+                .into_iter()
+                .map(|item| Self::parse(line, item))
+                .collect::<Result<_, _>>()
+                .map(Self::List)
         } else {
-            Self::Integer(value.parse::<u64>().unwrap())
+            line.parse::<u64>(value, "integer or list")
+                .map(Self::Integer)
         }
     }
 }
 
+impl FromStr for Value {
+    type Err = ParseError;
+
+    /// Parses a packet such as `[1,[2,[3]]]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = Line {
+            number: 1,
+            text: s.trim_end(),
+        };
+        Self::parse(&line, line.text)
+    }
+}
+
 impl PartialOrd for Value {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
-            (Value::Integer(l), Value::Integer(r)) => Some(l.cmp(r)),
+            (Value::Integer(l), Value::Integer(r)) => l.cmp(r),
             (Value::Integer(l), Value::List(_)) => {
-                Self::partial_cmp(&Value::List(vec![Box::new(Value::Integer(*l))]), other)
+                Self::cmp(&Value::List(vec![Value::Integer(*l)]), other)
             }
             (Value::List(_), Value::Integer(r)) => {
-                Self::partial_cmp(self, &Value::List(vec![Box::new(Value::Integer(*r))]))
+                Self::cmp(self, &Value::List(vec![Value::Integer(*r)]))
             }
             (Value::List(l), Value::List(r)) => {
-                let mut l = l.into_iter();
-                let mut r = r.into_iter();
-                Some(loop {
+                let mut l = l.iter();
+                let mut r = r.iter();
+                loop {
                     let left = l.next();
                     let right = r.next();
                     match (left, right) {
                         (None, None) => break Ordering::Equal,
                         (None, Some(_)) => break Ordering::Less,
                         (Some(_), None) => break Ordering::Greater,
-                        (Some(l), Some(r)) => match Self::partial_cmp(l.as_ref(), r.as_ref()) {
-                            Some(Ordering::Less) => break Ordering::Less,
-                            Some(Ordering::Equal) => {}
-                            Some(Ordering::Greater) => break Ordering::Greater,
-                            None => unreachable!(),
+                        (Some(l), Some(r)) => match Self::cmp(l, r) {
+                            Ordering::Less => break Ordering::Less,
+                            Ordering::Equal => {}
+                            Ordering::Greater => break Ordering::Greater,
                         },
                     }
-                })
+                }
             }
         }
     }
 }
 
-pub fn star_one() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut lines = values.lines();
+pub fn star_one(values: &str) -> Result<u64, ParseError> {
+    let mut lines = parse::lines(values);
     let mut index = 1;
     let mut s = 0;
-    loop {
-        let Some(line) = lines.next() else { break };
-        let left = Value::parse(line);
-        let right = Value::parse(lines.next().unwrap());
+    while let Some(line) = lines.next() {
+        let left = Value::parse(&line, line.text)?;
+        let line = lines.expect("right packet")?;
+        let right = Value::parse(&line, line.text)?;
         if left <= right {
             s += index;
         }
         index += 1;
-        lines.next();
+        if let Some(line) = lines.next().filter(|line| !line.text.is_empty()) {
+            return Err(line.error_at(0, "expected a blank line between pairs"));
+        }
     }
 
-    s
+    Ok(s)
 }
 
-pub fn star_two() -> usize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut values = values
-        .lines()
-        .filter_map(|line| {
-            if line.is_empty() {
-                None
-            } else {
-                Some(Value::parse(line))
-            }
-        })
-        .collect::<Vec<_>>();
-    values.push(Value::List(vec![Box::new(Value::List(vec![Box::new(
-        Value::Integer(2),
-    )]))]));
-    values.push(Value::List(vec![Box::new(Value::List(vec![Box::new(
-        Value::Integer(6),
-    )]))]));
+pub fn star_two(values: &str) -> Result<usize, ParseError> {
+    let mut values = parse::lines(values)
+        .filter(|line| !line.text.is_empty())
+        .map(|line| Value::parse(&line, line.text))
+        .collect::<Result<Vec<_>, _>>()?;
+    values.push(Value::List(vec![Value::List(vec![Value::Integer(2)])]));
+    values.push(Value::List(vec![Value::List(vec![Value::Integer(6)])]));
 
     values.sort();
 
-    let start = Value::List(vec![Box::new(Value::List(vec![Box::new(Value::Integer(
-        2,
-    ))]))]);
-    let end = Value::List(vec![Box::new(Value::List(vec![Box::new(Value::Integer(
-        6,
-    ))]))]);
+    let start = Value::List(vec![Value::List(vec![Value::Integer(2)])]);
+    let end = Value::List(vec![Value::List(vec![Value::Integer(6)])]);
 
     let mut start_index = 0;
     let mut end_index = 0;
@@ -140,7 +152,27 @@ pub fn star_two() -> usize {
         }
     }
 
-    start_index * end_index
+    Ok(start_index * end_index)
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        13
+    }
+
+    fn title(&self) -> &'static str {
+        "Distress Signal"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
 }
 
 #[cfg(test)]
@@ -149,11 +181,11 @@ mod tests {
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 13);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(13));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 140);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(140));
     }
 }
@@ -1,73 +1,37 @@
-use std::collections::BTreeMap;
-
-struct GridMap<T> {
-    data: BTreeMap<(isize, isize), T>,
-    left: isize,
-    right: isize,
-    top: isize,
-    bot: isize,
-}
-
-impl<T> GridMap<T> {
-    fn new() -> Self {
-        Self {
-            data: BTreeMap::new(),
-            left: 0,
-            right: 0,
-            top: 0,
-            bot: 0,
-        }
-    }
-
-    fn get(&self, x: isize, y: isize) -> Option<&T> {
-        self.data.get(&(x, y))
-    }
+use crate::grid::{Grid, Sparse};
+use crate::parse::{self, ParseError};
+use crate::render::{Colour, Glyph, Painter, Recorder};
+use crate::solution::{Answer, Solution};
 
-    fn encloses(&self, x: isize, y: isize) -> bool {
-        self.left <= x && self.right > x && self.top <= y && self.bot > y
-    }
-
-    fn set(&mut self, x: isize, y: isize, value: T) {
-        self.data.insert((x, y), value);
-        if self.data.len() == 1 {
-            self.left = x;
-            self.top = y;
-            self.right = x + 1;
-            self.bot = y + 1;
-        } else {
-            self.left = isize::min(self.left, x);
-            self.top = isize::min(self.top, y);
-            self.right = isize::max(self.right, x + 1);
-            self.bot = isize::max(self.bot, y + 1);
-        }
-    }
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Tile {
+    Rock,
+    Sand,
+    Source,
 }
 
-pub fn star_one() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
+const SOURCE: (isize, isize) = (0, 500);
 
-    let mut map = GridMap::new();
-    let source = (500, 0);
-    map.set(source.0, source.1, ());
+/// Draws the rock paths, each a list of `x,y` points joined by horizontal or vertical lines.
+fn parse(values: &str) -> Result<Sparse<Tile>, ParseError> {
+    let mut map = Sparse::new();
 
-    for line in values.lines() {
+    for line in parse::lines(values) {
         let mut rock = None;
-        for coords in line.split(" -> ") {
-            let mut coords = coords
-                .split(",")
-                .map(|coord| coord.parse::<isize>().unwrap());
-            let x_to = coords.next().unwrap();
-            let y_to = coords.next().unwrap();
+        for coords in line.text.split(" -> ") {
+            let (x_to, y_to) = line.split_once(coords, ",")?;
+            let x_to = line.parse::<isize>(x_to, "x coordinate")?;
+            let y_to = line.parse::<isize>(y_to, "y coordinate")?;
             if let Some((x_from, y_from)) = rock.replace((x_to, y_to)) {
+                if x_from != x_to && y_from != y_to {
+                    return Err(line.error(coords, "rock paths must be horizontal or vertical"));
+                }
                 let dx = (x_to - x_from).signum();
                 let dy = (y_to - y_from).signum();
                 for i in 0.. {
                     let x = x_from + dx * i;
                     let y = y_from + dy * i;
-                    map.set(x, y, ());
+                    map.insert((y, x), Tile::Rock);
                     if x == x_to && y == y_to {
                         break;
                     };
@@ -76,109 +40,251 @@ pub fn star_one() -> u64 {
         }
     }
 
+    map.insert(SOURCE, Tile::Source);
+    Ok(map)
+}
+
+/// Draws the cave, along with its floor if it has one.
+fn draw(painter: &Painter, map: &Sparse<Tile>, floor: Option<isize>) -> String {
+    let mut bounds = map.bounds().unwrap();
+    if let Some(floor) = floor {
+        bounds.extend((floor, bounds.left));
+    }
+    // leave a column on each side so that the sand does not touch the edges of the frame
+    bounds.left -= 1;
+    bounds.right += 1;
+
+    painter.frame(bounds, |point| match map.get(point) {
+        Some(Tile::Rock) => Glyph::coloured('#', Colour::Grey),
+        Some(Tile::Sand) => Glyph::coloured('o', Colour::Yellow),
+        Some(Tile::Source) => Glyph::coloured('+', Colour::Red),
+        None if Some(point.0) == floor => Glyph::coloured('#', Colour::Grey),
+        None => '.'.into(),
+    })
+}
+
+pub fn star_one(values: &str) -> Result<u64, ParseError> {
+    pour_into_abyss(values, &mut Recorder::off())
+}
+
+/// Pours sand until it flows into the abyss or blocks the source, drawing the cave after each grain.
+fn pour_into_abyss(values: &str, recorder: &mut Recorder) -> Result<u64, ParseError> {
+    let mut map = parse(values)?;
+    recorder.step(0, |painter| draw(painter, &map, None));
+
     let mut settled = 0;
     loop {
-        let mut sand = source;
+        let mut sand = SOURCE;
         loop {
-            if !map.encloses(sand.0, sand.1) {
-                return settled;
+            if !map.bounds().unwrap().contains(sand) {
+                recorder.last(settled as usize, |painter| draw(painter, &map, None));
+                return Ok(settled);
             }
-            if map.get(sand.0, sand.1 + 1).is_none() {
-                sand.1 += 1;
+            if map.get((sand.0 + 1, sand.1)).is_none() {
+                sand.0 += 1;
                 continue;
             }
-            if map.get(sand.0 - 1, sand.1 + 1).is_none() {
-                sand.1 += 1;
-                sand.0 -= 1;
+            if map.get((sand.0 + 1, sand.1 - 1)).is_none() {
+                sand.0 += 1;
+                sand.1 -= 1;
                 continue;
             }
-            if map.get(sand.0 + 1, sand.1 + 1).is_none() {
-                sand.1 += 1;
+            if map.get((sand.0 + 1, sand.1 + 1)).is_none() {
                 sand.0 += 1;
+                sand.1 += 1;
                 continue;
             }
-            map.set(sand.0, sand.1, ());
+            map.insert(sand, Tile::Sand);
             settled += 1;
+            // the rocks may hold the sand up to the source, which stops the flow
+            if sand == SOURCE {
+                recorder.last(settled as usize, |painter| draw(painter, &map, None));
+                return Ok(settled);
+            }
+            recorder.step(settled as usize, |painter| draw(painter, &map, None));
             break;
         }
     }
 }
 
-pub fn star_two() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut map = GridMap::new();
-    let source = (500, 0);
-    map.set(source.0, source.1, ());
+pub fn star_two(values: &str) -> Result<u64, ParseError> {
+    pour_onto_floor(values, &mut Recorder::off())
+}
 
-    for line in values.lines() {
-        let mut rock = None;
-        for coords in line.split(" -> ") {
-            let mut coords = coords
-                .split(",")
-                .map(|coord| coord.parse::<isize>().unwrap());
-            let x_to = coords.next().unwrap();
-            let y_to = coords.next().unwrap();
-            if let Some((x_from, y_from)) = rock.replace((x_to, y_to)) {
-                let dx = (x_to - x_from).signum();
-                let dy = (y_to - y_from).signum();
-                for i in 0.. {
-                    let x = x_from + dx * i;
-                    let y = y_from + dy * i;
-                    map.set(x, y, ());
-                    if x == x_to && y == y_to {
-                        break;
-                    };
-                }
-            }
-        }
-    }
+/// Pours sand onto the floor until it blocks the source, drawing the cave after each grain.
+fn pour_onto_floor(values: &str, recorder: &mut Recorder) -> Result<u64, ParseError> {
+    let mut map = parse(values)?;
 
-    let bot = map.bot;
+    let bottom = map.bounds().unwrap().bottom;
+    let floor = Some(bottom + 1);
+    recorder.step(0, |painter| draw(painter, &map, floor));
     let mut settled = 0;
     loop {
-        let mut sand = source;
+        let mut sand = SOURCE;
         loop {
-            if sand.1 < bot {
-                if map.get(sand.0, sand.1 + 1).is_none() {
-                    sand.1 += 1;
+            if sand.0 < bottom {
+                if map.get((sand.0 + 1, sand.1)).is_none() {
+                    sand.0 += 1;
                     continue;
                 }
-                if map.get(sand.0 - 1, sand.1 + 1).is_none() {
-                    sand.1 += 1;
-                    sand.0 -= 1;
+                if map.get((sand.0 + 1, sand.1 - 1)).is_none() {
+                    sand.0 += 1;
+                    sand.1 -= 1;
                     continue;
                 }
-                if map.get(sand.0 + 1, sand.1 + 1).is_none() {
-                    sand.1 += 1;
+                if map.get((sand.0 + 1, sand.1 + 1)).is_none() {
                     sand.0 += 1;
+                    sand.1 += 1;
                     continue;
                 }
             }
-            map.set(sand.0, sand.1, ());
+            map.insert(sand, Tile::Sand);
             settled += 1;
-            if sand == source {
-                return settled;
+            if sand == SOURCE {
+                recorder.last(settled as usize, |painter| draw(painter, &map, floor));
+                return Ok(settled);
             }
+            recorder.step(settled as usize, |painter| draw(painter, &map, floor));
             break;
         }
     }
 }
 
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        14
+    }
+
+    fn title(&self) -> &'static str {
+        "Regolith Reservoir"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn renders(&self) -> bool {
+        true
+    }
+
+    fn render(
+        &self,
+        part: u8,
+        input: &str,
+        recorder: &mut Recorder,
+    ) -> Option<Result<Answer, ParseError>> {
+        match part {
+            1 => Some(pour_into_abyss(input, recorder).map(Answer::from)),
+            2 => Some(pour_onto_floor(input, recorder).map(Answer::from)),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing;
+    use rand::rngs::StdRng;
+    use rand::Rng;
+
+    /// A few rock paths, each a handful of horizontal or vertical lines under the source.
+    fn generate(rng: &mut StdRng) -> String {
+        let mut values = String::new();
+        for _ in 0..rng.gen_range(1..=5) {
+            let mut point = (rng.gen_range(490..=510), rng.gen_range(1..=15));
+            let mut path = vec![point];
+            for _ in 0..rng.gen_range(1..=3) {
+                if rng.gen_bool(0.5) {
+                    point.0 = rng.gen_range(490..=510);
+                } else {
+                    point.1 = rng.gen_range(1..=15);
+                }
+                path.push(point);
+            }
+            let path = path
+                .iter()
+                .map(|(x, y)| format!("{x},{y}"))
+                .collect::<Vec<_>>();
+            values.push_str(&path.join(" -> "));
+            values.push('\n');
+        }
+        values
+    }
+
+    /// Drops the grains one at a time on a plain array, with a floor two rows under the lowest
+    /// rock or none, until one falls past the lowest rock or the source is blocked.
+    fn naive(values: &str, floor: bool) -> u64 {
+        let mut cave = vec![vec![false; 1000]; 200];
+        let mut lowest = 0;
+        for path in values.lines() {
+            let points = path
+                .split(" -> ")
+                .map(|point| {
+                    let (x, y) = point.split_once(',').unwrap();
+                    (x.parse::<usize>().unwrap(), y.parse::<usize>().unwrap())
+                })
+                .collect::<Vec<_>>();
+            for pair in points.windows(2) {
+                let ((x1, y1), (x2, y2)) = (pair[0], pair[1]);
+                for row in &mut cave[y1.min(y2)..=y1.max(y2)] {
+                    row[x1.min(x2)..=x1.max(x2)].fill(true);
+                }
+                lowest = lowest.max(y1.max(y2));
+            }
+        }
+        if floor {
+            cave[lowest + 2].fill(true);
+        }
+
+        let mut settled = 0;
+        while !cave[0][500] {
+            let (mut x, mut y) = (500, 0);
+            loop {
+                if !floor && y > lowest {
+                    return settled;
+                }
+                if !cave[y + 1][x] {
+                    y += 1;
+                } else if !cave[y + 1][x - 1] {
+                    y += 1;
+                    x -= 1;
+                } else if !cave[y + 1][x + 1] {
+                    y += 1;
+                    x += 1;
+                } else {
+                    cave[y][x] = true;
+                    settled += 1;
+                    break;
+                }
+            }
+        }
+        settled
+    }
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 24);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(24));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 93);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(93));
+    }
+
+    #[test]
+    fn test_star_one_against_naive() {
+        testing::differential(200, generate, star_one, |values| Ok(naive(values, false)));
+    }
+
+    #[test]
+    fn test_star_two_against_naive() {
+        testing::differential(200, generate, star_two, |values| Ok(naive(values, true)));
     }
 }
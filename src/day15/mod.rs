@@ -1,11 +1,16 @@
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
 use rayon::prelude::*;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 
-#[derive(Clone, Copy)]
-struct Segment(isize, isize);
+/// A non-empty range of integers, stored as its first value and one past its last.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Segment(isize, isize);
 
 impl Segment {
-    fn new(from_included: isize, to_included: isize) -> Option<Self> {
+    /// The segment from `from_included` to `to_included`, unless it would be empty.
+    pub fn new(from_included: isize, to_included: isize) -> Option<Self> {
         if to_included >= from_included {
             Some(Self(from_included, to_included + 1))
         } else {
@@ -13,7 +18,17 @@ impl Segment {
         }
     }
 
-    fn subtraction(self, rhs: Self) -> Vec<Self> {
+    pub fn start(&self) -> isize {
+        self.0
+    }
+
+    /// One past the last value of the segment.
+    pub fn end(&self) -> isize {
+        self.1
+    }
+
+    /// What is left of this segment once `rhs` is removed, in order.
+    pub fn subtraction(self, rhs: Self) -> Vec<Self> {
         let (Segment(a, b), Segment(c, d)) = (self, rhs);
         match a.cmp(&c) {
             std::cmp::Ordering::Less => match b.cmp(&c) {
@@ -22,7 +37,7 @@ impl Segment {
                 std::cmp::Ordering::Greater => match b.cmp(&d) {
                     std::cmp::Ordering::Less => vec![Segment(a, c)],
                     std::cmp::Ordering::Equal => vec![Segment(a, c)],
-                    std::cmp::Ordering::Greater => vec![Segment(a, c), Segment(b, d)],
+                    std::cmp::Ordering::Greater => vec![Segment(a, c), Segment(d, b)],
                 },
             },
             std::cmp::Ordering::Equal => match b.cmp(&d) {
@@ -42,7 +57,7 @@ impl Segment {
         }
     }
 
-    fn intersection(self, rhs: Self) -> Option<Self> {
+    pub fn intersection(self, rhs: Self) -> Option<Self> {
         let (Segment(_, b), Segment(c, d)) = if self.0 < rhs.0 {
             (self, rhs)
         } else {
@@ -59,43 +74,51 @@ impl Segment {
         }
     }
 
-    fn contains(&self, x: isize) -> bool {
+    pub fn contains(&self, x: isize) -> bool {
         x >= self.0 && x < self.1
     }
 }
 
-pub fn star_one() -> isize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
+type Position = (isize, isize);
 
-    let mut map = BTreeMap::<(isize, isize), (isize, isize)>::new();
+/// Reads the position of every sensor, along with the closest beacon it detects.
+fn parse(values: &str) -> Result<BTreeMap<Position, Position>, ParseError> {
+    let mut map = BTreeMap::new();
 
-    for line in values.lines() {
-        let line = line.strip_prefix("Sensor at ").unwrap();
-        let (coords, line) = line.split_once(':').unwrap();
-
-        let coords = coords.split_once(", ").unwrap();
-        let sensor = [coords.0, coords.1].map(|coord| {
-            let (_, coord) = coord.split_once('=').unwrap();
-            coord.parse::<isize>().unwrap()
-        });
+    for line in parse::lines(values) {
+        let coords = |coords: &str| -> Result<Position, ParseError> {
+            let (x, y) = line.split_once(coords, ", ")?;
+            let x = line.strip_prefix(x, "x=")?;
+            let y = line.strip_prefix(y, "y=")?;
+            Ok((
+                line.parse(x, "x coordinate")?,
+                line.parse(y, "y coordinate")?,
+            ))
+        };
 
-        let coords = line.strip_prefix(" closest beacon is at ").unwrap();
-        let coords = coords.split_once(", ").unwrap();
-        let beacon = [coords.0, coords.1].map(|coord| {
-            let (_, coord) = coord.split_once('=').unwrap();
-            coord.parse::<isize>().unwrap()
-        });
+        let rest = line.strip_prefix(line.text, "Sensor at ")?;
+        let (sensor, rest) = line.split_once(rest, ":")?;
+        let beacon = line.strip_prefix(rest, " closest beacon is at ")?;
 
-        map.insert((sensor[0], sensor[1]), (beacon[0], beacon[1]));
+        map.insert(coords(sensor)?, coords(beacon)?);
     }
 
-    #[cfg(test)]
-    let h = 10;
-    #[cfg(not(test))]
-    let h = 2_000_000;
+    Ok(map)
+}
+
+pub fn star_one(values: &str) -> Result<isize, ParseError> {
+    excluded_on_row(values, 2_000_000)
+}
+
+/// Counts the positions on row `h` where a beacon cannot possibly be.
+fn excluded_on_row(values: &str, h: isize) -> Result<isize, ParseError> {
+    let map = parse(values)?;
+    // the positions of the beacons found on the row are known not to be free of beacons
+    let beacons = map
+        .values()
+        .filter(|beacon| beacon.1 == h)
+        .collect::<BTreeSet<_>>()
+        .len() as isize;
 
     let mut segmentation = Vec::<Segment>::new();
     for (sensor, beacon) in map {
@@ -114,43 +137,20 @@ pub fn star_one() -> isize {
         }
     }
 
-    segmentation.into_iter().map(|s| s.1 - s.0).sum::<isize>() - 1
+    Ok(segmentation.into_iter().map(|s| s.1 - s.0).sum::<isize>() - beacons)
 }
 
-pub fn star_two() -> isize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut map = BTreeMap::<(isize, isize), (isize, isize)>::new();
-
-    for line in values.lines() {
-        let line = line.strip_prefix("Sensor at ").unwrap();
-        let (coords, line) = line.split_once(':').unwrap();
-
-        let coords = coords.split_once(", ").unwrap();
-        let sensor = [coords.0, coords.1].map(|coord| {
-            let (_, coord) = coord.split_once('=').unwrap();
-            coord.parse::<isize>().unwrap()
-        });
-
-        let coords = line.strip_prefix(" closest beacon is at ").unwrap();
-        let coords = coords.split_once(", ").unwrap();
-        let beacon = [coords.0, coords.1].map(|coord| {
-            let (_, coord) = coord.split_once('=').unwrap();
-            coord.parse::<isize>().unwrap()
-        });
-
-        map.insert((sensor[0], sensor[1]), (beacon[0], beacon[1]));
-    }
+pub fn star_two(values: &str) -> Result<isize, ParseError> {
+    tuning_frequency(values, 4_000_000)
+}
 
-    #[cfg(test)]
-    const H: isize = 20;
-    #[cfg(not(test))]
-    const H: isize = 4_000_000;
+/// Finds the only uncovered position within `0..=bound` on both axes,
+/// and returns its tuning frequency.
+fn tuning_frequency(values: &str, bound: isize) -> Result<isize, ParseError> {
+    let map = parse(values)?;
+    let square = Segment::new(0, bound).unwrap();
 
-    (0..=H)
+    (0..=bound)
         .into_par_iter()
         .find_map_any(|h| {
             let mut segmentation = Vec::<Segment>::new();
@@ -159,10 +159,14 @@ pub fn star_two() -> isize {
                 let dh = isize::abs(sensor.1 - h);
                 if distance >= dh {
                     let u = distance - dh;
-                    let mut segmented_new = vec![Segment::new(sensor.0 - u, sensor.0 + u)
+                    // sensors beside the square may only cover the row outside of it
+                    let Some(covered) = Segment::new(sensor.0 - u, sensor.0 + u)
                         .unwrap()
-                        .intersection(Segment::new(0, H).unwrap())
-                        .unwrap()];
+                        .intersection(square)
+                    else {
+                        continue;
+                    };
+                    let mut segmented_new = vec![covered];
                     for &old in &segmentation {
                         segmented_new = segmented_new
                             .into_iter()
@@ -173,8 +177,8 @@ pub fn star_two() -> isize {
                 }
             }
 
-            if segmentation.iter().map(|&s| s.1 - s.0).sum::<isize>() == H {
-                for x in 0..=H {
+            if segmentation.iter().map(|&s| s.1 - s.0).sum::<isize>() == bound {
+                for x in 0..=bound {
                     if segmentation.iter().any(|s| s.contains(x)) {
                         continue;
                     }
@@ -183,20 +187,185 @@ pub fn star_two() -> isize {
             }
             None
         })
-        .unwrap()
+        .ok_or_else(|| {
+            let rows = parse::lines(values).count();
+            ParseError::new(
+                rows + 1,
+                1,
+                "the sensors leave no single position uncovered",
+            )
+        })
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        15
+    }
+
+    fn title(&self) -> &'static str {
+        "Beacon Exclusion Zone"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn slow(&self) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing;
+    use rand::rngs::StdRng;
+    use rand::Rng;
+
+    fn distance(a: Position, b: Position) -> isize {
+        (a.0 - b.0).abs() + (a.1 - b.1).abs()
+    }
+
+    fn report(sensors: &BTreeMap<Position, Position>) -> String {
+        sensors
+            .iter()
+            .map(|(s, b)| {
+                let (sx, sy, bx, by) = (s.0, s.1, b.0, b.1);
+                format!("Sensor at x={sx}, y={sy}: closest beacon is at x={bx}, y={by}\n")
+            })
+            .collect()
+    }
+
+    /// A few beacons, then sensors around them, each reporting the beacon closest to it;
+    /// sensors with two closest beacons are left out, as the puzzle guarantees there are none.
+    fn generate(rng: &mut StdRng) -> String {
+        let mut point = || (rng.gen_range(-20..=20), rng.gen_range(-20..=20));
+        let beacons = (0..6).map(|_| point()).collect::<Vec<_>>();
+        let mut sensors = BTreeMap::new();
+        for _ in 0..8 {
+            let sensor = point();
+            let mut by_distance = beacons
+                .iter()
+                .map(|&b| (distance(sensor, b), b))
+                .collect::<Vec<_>>();
+            by_distance.sort();
+            if by_distance[0].0 > 0 && by_distance[0].0 < by_distance[1].0 {
+                sensors.insert(sensor, by_distance[0].1);
+            }
+        }
+        report(&sensors)
+    }
+
+    /// Sensors covering the whole `0..=20` square but one position, each placed on or beside a
+    /// position not covered yet, possibly outside of the square, with a range reaching it but
+    /// falling short of the hidden one.
+    fn generate_hidden(rng: &mut StdRng) -> String {
+        let hidden = (rng.gen_range(0..=20), rng.gen_range(0..=20));
+        let mut sensors = BTreeMap::<Position, Position>::new();
+        loop {
+            let uncovered = (0..=20)
+                .flat_map(|x| (0..=20).map(move |y| (x, y)))
+                .filter(|&p| p != hidden)
+                .filter(|&p| {
+                    sensors
+                        .iter()
+                        .all(|(&s, &b)| distance(s, p) > distance(s, b))
+                })
+                .collect::<Vec<_>>();
+            if uncovered.is_empty() {
+                return report(&sensors);
+            }
+            let target = uncovered[rng.gen_range(0..uncovered.len())];
+            let beside = (
+                target.0 + rng.gen_range(-10..=10),
+                target.1 + rng.gen_range(-10..=10),
+            );
+            let (sensor, range) = match distance(beside, hidden) - 1 {
+                reach if distance(beside, target) <= reach => {
+                    (beside, rng.gen_range(distance(beside, target)..=reach))
+                }
+                _ => {
+                    let reach = distance(target, hidden) - 1;
+                    (target, rng.gen_range(reach / 2..=reach))
+                }
+            };
+            let dx = rng.gen_range(-range..=range);
+            let dy = (range - dx.abs()) * if rng.gen_bool(0.5) { 1 } else { -1 };
+            sensors.insert(sensor, (sensor.0 + dx, sensor.1 + dy));
+        }
+    }
+
+    /// Checks every position of row `h` within reach of some sensor.
+    fn scan_row(values: &str, h: isize) -> isize {
+        let map = parse(values).unwrap();
+        let reach = map.iter().map(|(&s, &b)| distance(s, b)).max().unwrap_or(0);
+        let left = map.keys().map(|s| s.0).min().unwrap_or(0) - reach;
+        let right = map.keys().map(|s| s.0).max().unwrap_or(0) + reach;
+        (left..=right)
+            .filter(|&x| !map.values().any(|&b| b == (x, h)))
+            .filter(|&x| {
+                map.iter()
+                    .any(|(&s, &b)| distance(s, (x, h)) <= distance(s, b))
+            })
+            .count() as isize
+    }
+
+    /// Checks every position of the square for the only one no sensor covers.
+    fn scan_square(values: &str, bound: isize) -> isize {
+        let map = parse(values).unwrap();
+        let mut uncovered = (0..=bound)
+            .flat_map(|x| (0..=bound).map(move |y| (x, y)))
+            .filter(|&p| map.iter().all(|(&s, &b)| distance(s, p) > distance(s, b)));
+        let (x, y) = uncovered.next().unwrap();
+        assert_eq!(uncovered.next(), None);
+        x * 4000000 + y
+    }
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 26);
+        assert_eq!(excluded_on_row(include_str!("mock.txt"), 10), Ok(26));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 56000011);
+        assert_eq!(tuning_frequency(include_str!("mock.txt"), 20), Ok(56000011));
+    }
+
+    #[test]
+    fn test_star_one_against_scan() {
+        let rows = -25..=25;
+        testing::differential(
+            100,
+            generate,
+            |values| {
+                rows.clone()
+                    .map(|h| excluded_on_row(values, h).unwrap())
+                    .collect::<Vec<_>>()
+            },
+            |values| rows.clone().map(|h| scan_row(values, h)).collect(),
+        );
+    }
+
+    #[test]
+    fn test_star_two_against_scan() {
+        testing::differential(
+            50,
+            generate_hidden,
+            |values| tuning_frequency(values, 20),
+            |values| Ok(scan_square(values, 20)),
+        );
+    }
+
+    #[test]
+    fn test_star_two_covered() {
+        let values = "Sensor at x=10, y=10: closest beacon is at x=30, y=10\n";
+        let error = ParseError::new(2, 1, "the sensors leave no single position uncovered");
+        assert_eq!(tuning_frequency(values, 20), Err(error));
     }
 }
@@ -1,3 +1,9 @@
+use crate::parse::{self, Line, ParseError};
+use crate::solution::{Answer, Solution};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::btree_map::Entry;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::collections::HashMap;
@@ -10,48 +16,66 @@ struct Valve {
 
 #[derive(Clone, Copy)]
 enum Action<'a> {
-    Walk(&'a str),
+    Walk,
     Open(&'a str),
 }
 
-pub fn star_one() -> usize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let valves = values
-        .lines()
-        .map(|line| {
-            let line = line.strip_prefix("Valve ").unwrap();
-            let (id, line) = line.split_once(' ').unwrap();
-            let line = line.strip_prefix("has flow rate=").unwrap();
-            let (flow, line) = line.split_once(';').unwrap();
-            let line = line.strip_prefix(" tunnel").unwrap();
-            let line = line
-                .strip_prefix("s ")
-                .or_else(|| line.strip_prefix(' '))
-                .unwrap();
-            let line = line.strip_prefix("lead").unwrap();
-            let line = line
-                .strip_prefix("s ")
-                .or_else(|| line.strip_prefix(' '))
-                .unwrap();
-            let line = line.strip_prefix("to valve").unwrap();
-            let line = line
-                .strip_prefix("s ")
-                .or_else(|| line.strip_prefix(' '))
-                .unwrap();
-            let next = line.split(", ").map(|s| s.to_string()).collect();
-            (
-                id.to_string(),
-                Valve {
-                    flow: flow.parse().unwrap(),
-                    next,
-                },
-            )
+/// Skips the plural mark if any, since "tunnel leads to valve" and "tunnels lead to valves" are
+/// both fine.
+fn plural<'a>(line: &Line<'a>, rest: &'a str) -> Result<&'a str, ParseError> {
+    rest.strip_prefix("s ")
+        .or_else(|| rest.strip_prefix(' '))
+        .ok_or_else(|| line.error(rest, "expected ` `"))
+}
+
+/// Where a path ends up: at the last valve it opens, or at AA if it opens none.
+fn last_opened<'a>(pathing: &[Action<'a>]) -> &'a str {
+    pathing
+        .iter()
+        .rev()
+        .find_map(|action| match action {
+            Action::Open(id) => Some(*id),
+            Action::Walk => None,
         })
-        .collect::<BTreeMap<_, _>>();
+        .unwrap_or("AA")
+}
+
+/// Reads the valves by id, checking that every tunnel leads to a known valve.
+fn parse(values: &str) -> Result<BTreeMap<String, Valve>, ParseError> {
+    let mut valves = BTreeMap::new();
+    let mut tunnels = vec![];
+    for line in parse::lines(values) {
+        let rest = line.strip_prefix(line.text, "Valve ")?;
+        let (id, rest) = line.split_once(rest, " ")?;
+        let rest = line.strip_prefix(rest, "has flow rate=")?;
+        let (flow, rest) = line.split_once(rest, ";")?;
+        let flow = line.parse(flow, "flow rate")?;
+        let rest = line.strip_prefix(rest, " tunnel")?;
+        let rest = plural(&line, rest)?;
+        let rest = line.strip_prefix(rest, "lead")?;
+        let rest = plural(&line, rest)?;
+        let rest = line.strip_prefix(rest, "to valve")?;
+        let rest = plural(&line, rest)?;
+        let next = rest.split(", ").collect::<Vec<_>>();
+        tunnels.extend(next.iter().map(|&to| (line, to)));
+        let next = next.into_iter().map(|s| s.to_string()).collect();
+        valves.insert(id.to_string(), Valve { flow, next });
+    }
+
+    for (line, to) in tunnels {
+        if !valves.contains_key(to) {
+            return Err(line.error(to, format!("there is no valve {to}")));
+        }
+    }
+    if !valves.contains_key("AA") {
+        return Err(ParseError::new(1, 1, "there is no valve AA to start from"));
+    }
+
+    Ok(valves)
+}
+
+pub fn star_one(values: &str) -> Result<usize, ParseError> {
+    let valves = parse(values)?;
 
     let good_valves = valves
         .iter()
@@ -63,9 +87,10 @@ pub fn star_one() -> usize {
             }
         })
         .collect::<BTreeMap<&str, &Valve>>();
+    // keyed by id too, as several valves may have the same flow
     let best_flows = good_valves
         .iter()
-        .map(|(id, valve)| (valve.flow, *id))
+        .map(|(id, valve)| ((valve.flow, *id), *id))
         .collect::<BTreeMap<_, _>>();
 
     let mut map = BTreeMap::<(&str, &str), Vec<&str>>::new();
@@ -78,10 +103,10 @@ pub fn star_one() -> usize {
             let valve_to = valves.get(id_to).unwrap();
             for id_next in &valve_to.next {
                 let id_next = id_next.as_str();
-                if !map.contains_key(&(start, id_next)) {
+                if let Entry::Vacant(entry) = map.entry((start, id_next)) {
                     let mut new_path = path_so_far.clone();
                     new_path.push(id_next);
-                    map.insert((start, id_next), new_path);
+                    entry.insert(new_path);
                     exploration.push_back(id_next);
                 }
             }
@@ -113,12 +138,12 @@ pub fn star_one() -> usize {
         }
 
         // suboptimality
-        if {
+        let suboptimal = {
             let mut pathing = pathing.clone();
             let good_ids_visited = pathing
                 .iter()
                 .filter_map(|action| match action {
-                    Action::Walk(_) => None,
+                    Action::Walk => None,
                     Action::Open(id) => Some(*id),
                 })
                 .collect::<BTreeSet<_>>();
@@ -127,7 +152,7 @@ pub fn star_one() -> usize {
                 .rev()
                 .filter(|v| !good_ids_visited.contains(*v))
             {
-                pathing.push(Action::Walk(good_valve));
+                pathing.push(Action::Walk);
                 pathing.push(Action::Open(good_valve));
             }
             let mut good_ids_visited = BTreeSet::new();
@@ -142,7 +167,8 @@ pub fn star_one() -> usize {
                         .sum::<usize>()
             });
             score_if_hopping < max
-        } {
+        };
+        if suboptimal {
             continue;
         }
 
@@ -152,9 +178,9 @@ pub fn star_one() -> usize {
                     let path_extension = map
                         .get(&(id_at, good_id_to))
                         .unwrap()
-                        .into_iter()
+                        .iter()
                         .skip(1)
-                        .map(|id| Action::Walk(*id));
+                        .map(|_| Action::Walk);
                     let mut new_pathing = pathing.clone();
                     new_pathing.extend(path_extension);
                     new_pathing.push(Action::Open(good_id_to));
@@ -163,47 +189,11 @@ pub fn star_one() -> usize {
             }
         }
     }
-    max
+    Ok(max)
 }
 
-pub fn star_two() -> usize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let valves = values
-        .lines()
-        .map(|line| {
-            let line = line.strip_prefix("Valve ").unwrap();
-            let (id, line) = line.split_once(' ').unwrap();
-            let line = line.strip_prefix("has flow rate=").unwrap();
-            let (flow, line) = line.split_once(';').unwrap();
-            let line = line.strip_prefix(" tunnel").unwrap();
-            let line = line
-                .strip_prefix("s ")
-                .or_else(|| line.strip_prefix(' '))
-                .unwrap();
-            let line = line.strip_prefix("lead").unwrap();
-            let line = line
-                .strip_prefix("s ")
-                .or_else(|| line.strip_prefix(' '))
-                .unwrap();
-            let line = line.strip_prefix("to valve").unwrap();
-            let line = line
-                .strip_prefix("s ")
-                .or_else(|| line.strip_prefix(' '))
-                .unwrap();
-            let next = line.split(", ").map(|s| s.to_string()).collect();
-            (
-                id.to_string(),
-                Valve {
-                    flow: flow.parse().unwrap(),
-                    next,
-                },
-            )
-        })
-        .collect::<BTreeMap<_, _>>();
+pub fn star_two(values: &str) -> Result<usize, ParseError> {
+    let valves = parse(values)?;
 
     let good_valves = valves
         .iter()
@@ -215,9 +205,10 @@ pub fn star_two() -> usize {
             }
         })
         .collect::<BTreeMap<&str, &Valve>>();
+    // keyed by id too, as several valves may have the same flow
     let best_flows = good_valves
         .iter()
-        .map(|(id, valve)| (valve.flow, *id))
+        .map(|(id, valve)| ((valve.flow, *id), *id))
         .collect::<BTreeMap<_, _>>();
 
     let mut map = BTreeMap::<(&str, &str), Vec<&str>>::new();
@@ -230,17 +221,19 @@ pub fn star_two() -> usize {
             let valve_to = valves.get(id_to).unwrap();
             for id_next in &valve_to.next {
                 let id_next = id_next.as_str();
-                if !map.contains_key(&(start, id_next)) {
+                if let Entry::Vacant(entry) = map.entry((start, id_next)) {
                     let mut new_path = path_so_far.clone();
                     new_path.push(id_next);
-                    map.insert((start, id_next), new_path);
+                    entry.insert(new_path);
                     exploration.push_back(id_next);
                 }
             }
         }
     }
 
-    let mut visited = BTreeMap::<(&str, &str), HashMap<BTreeSet<&str>, usize>>::new();
+    // for both positions and the valves opened, when they were reached and with which score
+    type Reached = (usize, usize, usize);
+    let mut visited = HashMap::<(&str, &str, BTreeSet<&str>), Vec<Reached>>::new();
 
     let mut max = 0;
     let mut exploration = VecDeque::<(Vec<Action>, Vec<Action>)>::from([(vec![], vec![])]);
@@ -264,32 +257,29 @@ pub fn star_two() -> usize {
         }
 
         // memoised
-        let my_id_at = match my_pathing.last() {
-            Some(Action::Open(id_at)) => *id_at,
-            None => "AA",
-            _ => unreachable!(),
+        let my_id_at = last_opened(&my_pathing);
+        let his_id_at = last_opened(&his_pathing);
+        // a state is worse than one at the same places with the same valves opened, which was
+        // reached no later with more pressure released, and no better than an identical one;
+        // mere ties are kept, as the states reached from one are tied with it when stopping
+        let me = (my_id_at, my_pathing.len());
+        let him = (his_id_at, his_pathing.len());
+        let (a, b) = if me < him { (me, him) } else { (him, me) };
+        let reached = visited
+            .entry((a.0, b.0, good_ids_visited.clone()))
+            .or_default();
+        let dominates = |(ta, tb, score): Reached, (tc, td, other): Reached| {
+            ta <= tc && tb <= td && (score > other || (ta, tb, score) == (tc, td, other))
         };
-        let his_id_at = match his_pathing.last() {
-            Some(Action::Open(id_at)) => *id_at,
-            None => "AA",
-            _ => unreachable!(),
-        };
-        if let Some(cached_flow) = visited
-            .entry(if my_id_at < his_id_at {
-                (my_id_at, his_id_at)
-            } else {
-                (his_id_at, my_id_at)
-            })
-            .or_default()
-            .insert(good_ids_visited.clone(), pathing_score)
-        {
-            if cached_flow > pathing_score {
-                continue;
-            }
+        let current = (a.1, b.1, pathing_score);
+        if reached.iter().any(|&other| dominates(other, current)) {
+            continue;
         }
+        reached.retain(|&other| !dominates(current, other));
+        reached.push(current);
 
         // suboptimality
-        if {
+        let suboptimal = {
             let mut my_pathing = my_pathing.clone();
             let mut his_pathing = his_pathing.clone();
             let mut good_ids_visited = BTreeSet::new();
@@ -309,10 +299,10 @@ pub fn star_two() -> usize {
                 .filter(|v| !good_ids_visited.contains(*v))
             {
                 if my_pathing.len() <= his_pathing.len() {
-                    my_pathing.push(Action::Walk(next_target));
+                    my_pathing.push(Action::Walk);
                     my_pathing.push(Action::Open(next_target));
                 } else {
-                    his_pathing.push(Action::Walk(next_target));
+                    his_pathing.push(Action::Walk);
                     his_pathing.push(Action::Open(next_target));
                 }
             }
@@ -332,20 +322,26 @@ pub fn star_two() -> usize {
                         .sum::<usize>()
             });
             score_if_hopping < max
-        } {
+        };
+        if suboptimal {
             continue;
         }
 
         if my_pathing.len() < 25 || his_pathing.len() < 25 {
+            // either may also stop there and leave the remaining valves to the other, so that
+            // every pair of paths can be reached from any state
             if my_pathing.len() <= his_pathing.len() {
+                let mut my_pathing_stopped = my_pathing.clone();
+                my_pathing_stopped.resize(25, Action::Walk);
+                exploration.push_front((my_pathing_stopped, his_pathing.clone()));
                 for &good_id_to in good_valves.keys() {
                     if !good_ids_visited.contains(good_id_to) {
                         let path_extension = map
                             .get(&(my_id_at, good_id_to))
                             .unwrap()
-                            .into_iter()
+                            .iter()
                             .skip(1)
-                            .map(|id| Action::Walk(*id));
+                            .map(|_| Action::Walk);
                         let mut my_pathing = my_pathing.clone();
                         my_pathing.extend(path_extension);
                         my_pathing.push(Action::Open(good_id_to));
@@ -353,14 +349,17 @@ pub fn star_two() -> usize {
                     }
                 }
             } else {
+                let mut his_pathing_stopped = his_pathing.clone();
+                his_pathing_stopped.resize(25, Action::Walk);
+                exploration.push_front((my_pathing.clone(), his_pathing_stopped));
                 for &good_id_to in good_valves.keys() {
                     if !good_ids_visited.contains(good_id_to) {
                         let path_extension = map
                             .get(&(his_id_at, good_id_to))
                             .unwrap()
-                            .into_iter()
+                            .iter()
                             .skip(1)
-                            .map(|id| Action::Walk(*id));
+                            .map(|_| Action::Walk);
                         let mut his_pathing = his_pathing.clone();
                         his_pathing.extend(path_extension);
                         his_pathing.push(Action::Open(good_id_to));
@@ -370,20 +369,170 @@ pub fn star_two() -> usize {
             }
         }
     }
-    max
+    Ok(max)
+}
+
+/// A connected network of `size` valves, at least two and AA among them, with tunnels going
+/// both ways.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    let mut ids = vec!["AA".to_string()];
+    while ids.len() < size.clamp(2, 26 * 26) {
+        let id = [rng.gen_range(b'A'..=b'Z'), rng.gen_range(b'A'..=b'Z')];
+        let id = String::from_utf8(id.to_vec()).unwrap();
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    ids.shuffle(rng);
+
+    let mut next = vec![BTreeSet::new(); ids.len()];
+    // a random spanning tree keeps every valve reachable, then a few shortcuts
+    for i in 1..ids.len() {
+        let j = rng.gen_range(0..i);
+        next[i].insert(j);
+        next[j].insert(i);
+    }
+    for _ in 0..rng.gen_range(0..ids.len()) {
+        let (i, j) = (rng.gen_range(0..ids.len()), rng.gen_range(0..ids.len()));
+        if i != j {
+            next[i].insert(j);
+            next[j].insert(i);
+        }
+    }
+
+    // like the puzzle inputs, large networks have about 15 working valves
+    let working = (15.0 / ids.len() as f64).min(0.7);
+    let mut values = String::new();
+    for (i, id) in ids.iter().enumerate() {
+        let flow = if id == "AA" || rng.gen_bool(1.0 - working) {
+            0
+        } else {
+            rng.gen_range(1..=25)
+        };
+        let next = next[i].iter().map(|&j| ids[j].as_str()).collect::<Vec<_>>();
+        let tunnels = if next.len() == 1 {
+            "tunnel leads to valve"
+        } else {
+            "tunnels lead to valves"
+        };
+        values.push_str(&format!(
+            "Valve {id} has flow rate={flow}; {tunnels} {}\n",
+            next.join(", ")
+        ));
+    }
+    values
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        16
+    }
+
+    fn title(&self) -> &'static str {
+        "Proboscidea Volcanium"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn generate(&self, rng: &mut StdRng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing;
+    use rand::Rng;
+
+    /// A small network, for the exhaustive search to go through quickly.
+    fn generate_small(rng: &mut StdRng) -> String {
+        let size = rng.gen_range(2..=8);
+        generate(rng, size)
+    }
+
+    /// Tries every move of every minute, keeping the best pressure released for each set of
+    /// positions and opened valves, with `actors` of them opening valves for `minutes`.
+    fn exhaustive(values: &str, actors: usize, minutes: usize) -> usize {
+        let valves = parse(values).unwrap();
+        let ids = valves.keys().map(String::as_str).collect::<Vec<_>>();
+        let index = |id: &str| ids.iter().position(|&other| other == id).unwrap();
+        let flows = ids.iter().map(|id| valves[*id].flow).collect::<Vec<_>>();
+        let next = ids
+            .iter()
+            .map(|id| {
+                valves[*id]
+                    .next
+                    .iter()
+                    .map(|n| index(n))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let start = vec![index("AA"); actors];
+        let mut states = HashMap::from([((start, 0u64), 0)]);
+        for minute in 1..=minutes {
+            let remaining = minutes - minute;
+            let mut next_states = HashMap::new();
+            for ((positions, opened), released) in states {
+                // each actor in turn either opens its valve or walks to a neighbour
+                let mut partial = vec![(vec![], opened, released)];
+                for &at in &positions {
+                    let mut extended = vec![];
+                    for (moved, opened, released) in partial {
+                        if flows[at] > 0 && opened & (1 << at) == 0 {
+                            let mut moved = moved.clone();
+                            moved.push(at);
+                            let released = released + flows[at] * remaining;
+                            extended.push((moved, opened | (1 << at), released));
+                        }
+                        for &to in &next[at] {
+                            let mut moved = moved.clone();
+                            moved.push(to);
+                            extended.push((moved, opened, released));
+                        }
+                    }
+                    partial = extended;
+                }
+                for (moved, opened, released) in partial {
+                    let best = next_states.entry((moved, opened)).or_insert(0);
+                    *best = released.max(*best);
+                }
+            }
+            states = next_states;
+        }
+        states.into_values().max().unwrap()
+    }
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 1651);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(1651));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 1707);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(1707));
+    }
+
+    #[test]
+    fn test_star_one_against_exhaustive() {
+        testing::differential(100, generate_small, star_one, |values| {
+            Ok(exhaustive(values, 1, 30))
+        });
+    }
+
+    #[test]
+    fn test_star_two_against_exhaustive() {
+        testing::differential(100, generate_small, star_two, |values| {
+            Ok(exhaustive(values, 2, 26))
+        });
     }
 }
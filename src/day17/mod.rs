@@ -1,3 +1,7 @@
+use crate::grid::Bounds;
+use crate::parse::{self, ParseError};
+use crate::render::{Colour, Glyph, Painter, Recorder};
+use crate::solution::{Answer, Solution};
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 
@@ -61,21 +65,66 @@ impl Block {
     }
 }
 
-pub fn star_one() -> usize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
+/// Reads the jet pattern, a single line of `<` and `>`.
+fn parse(values: &str) -> Result<&[u8], ParseError> {
+    let mut lines = parse::lines(values);
+    let line = lines.expect("jet pattern")?;
+    if let Some(offset) = line.text.bytes().position(|b| b != b'<' && b != b'>') {
+        return Err(line.error_at(offset, "jets must be `<` or `>`"));
+    }
+    if line.text.is_empty() {
+        return Err(line.error_at(0, "expected jet pattern"));
+    }
+    if let Some(line) = lines.find(|line| !line.text.is_empty()) {
+        return Err(line.error_at(0, "the jet pattern must fit on a single line"));
+    }
+    Ok(line.text.as_bytes())
+}
+
+/// How many rows of the top of the tower are drawn.
+const DRAWN_ROWS: usize = 40;
+
+/// Draws the top of the tower between its walls, and the floor once it is in sight.
+fn draw(painter: &Painter, tableau: &BTreeSet<(usize, usize)>) -> String {
+    let (height, _) = *tableau.last().unwrap_or(&(0, 0));
+    let rows = (height + 1).min(DRAWN_ROWS) as isize;
+    let bounds = Bounds {
+        top: 0,
+        left: -1,
+        bottom: rows,
+        right: 8,
+    };
+    // rows of the frame go downwards from the top of the tower, the floor being row 0
+    painter.frame(bounds, |(row, column)| {
+        let row = height - row as usize;
+        match (row, column) {
+            (0, -1 | 7) => Glyph::coloured('+', Colour::Grey),
+            (0, _) => Glyph::coloured('-', Colour::Grey),
+            (_, -1 | 7) => Glyph::coloured('|', Colour::Grey),
+            _ if tableau.contains(&(row, column as usize)) => Glyph::coloured('#', Colour::Yellow),
+            _ => '.'.into(),
+        }
+    })
+}
+
+pub fn star_one(values: &str) -> Result<usize, ParseError> {
+    stack(values, &mut Recorder::off())
+}
+
+/// Drops 2022 rocks, drawing the tower after each of them.
+fn stack(values: &str, recorder: &mut Recorder) -> Result<usize, ParseError> {
+    let jets = parse(values)?;
 
     let mut tableau: BTreeSet<(usize, usize)> = BTreeSet::new();
+    recorder.step(0, |painter| draw(painter, &tableau));
 
     let mut tick = 0usize;
     for n_rock in 0usize..2022 {
         let mut left = 2usize;
-        let (mut bottom, _) = *tableau.iter().last().unwrap_or_else(|| &(0, 0));
+        let (mut bottom, _) = *tableau.iter().last().unwrap_or(&(0, 0));
         bottom += 4;
         loop {
-            let shift = values.as_bytes()[tick % (values.len() - 1)];
+            let shift = jets[tick % jets.len()];
             let block = Block::from(n_rock);
             let projection = block.project(left, bottom);
             match shift {
@@ -111,6 +160,7 @@ pub fn star_one() -> usize {
                     .any(|&(b, l)| tableau.contains(&(b - 1, l)));
             if will_lay {
                 tableau.extend(projection);
+                recorder.step(n_rock + 1, |painter| draw(painter, &tableau));
                 break;
             } else {
                 bottom -= 1;
@@ -118,19 +168,23 @@ pub fn star_one() -> usize {
         }
     }
 
+    recorder.last(2022, |painter| draw(painter, &tableau));
     let (bottom, _) = *tableau.iter().last().unwrap();
-    bottom
+    Ok(bottom)
 }
 
-pub fn star_two() -> usize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
+pub fn star_two(values: &str) -> Result<usize, ParseError> {
+    extrapolate(values, &mut Recorder::off())
+}
+
+/// Drops rocks until the tower repeats itself, drawing it after each of them.
+fn extrapolate(values: &str, recorder: &mut Recorder) -> Result<usize, ParseError> {
+    let jets = parse(values)?;
 
     const N: usize = 1_000_000_000_000 - 1;
 
     let mut tableau: BTreeSet<(usize, usize)> = BTreeSet::new();
+    recorder.step(0, |painter| draw(painter, &tableau));
 
     let mut prefix = None;
     let mut cached_height_by_rock = BTreeMap::<usize, usize>::new();
@@ -138,10 +192,10 @@ pub fn star_two() -> usize {
     let mut tick = 0usize;
     for n_rock in 0usize.. {
         let mut left = 2usize;
-        let (mut bottom, _) = *tableau.iter().last().unwrap_or_else(|| &(0, 0));
+        let (mut bottom, _) = *tableau.iter().last().unwrap_or(&(0, 0));
         bottom += 4;
         loop {
-            let shift = values.as_bytes()[tick % (values.len() - 1)];
+            let shift = jets[tick % jets.len()];
             let block = Block::from(n_rock);
             let projection = block.project(left, bottom);
             match shift {
@@ -177,21 +231,23 @@ pub fn star_two() -> usize {
                     .any(|&(b, l)| tableau.contains(&(b - 1, l)));
             if will_lay {
                 tableau.extend(projection);
+                recorder.step(n_rock + 1, |painter| draw(painter, &tableau));
 
                 cached_height_by_rock.insert(n_rock, tableau.last().unwrap().0);
 
-                if tick % (values.len() - 1) == 1 {
+                if tick % jets.len() == 1 {
                     if let Some((pre_n, pre_h)) = prefix {
                         let post_h = tableau.last().unwrap().0;
                         let post_n = n_rock;
                         let cycle_n = post_n - pre_n;
                         let cycle_h = post_h - pre_h;
 
-                        let early_n = cycle_n - pre_n;
-                        let offset_n = (N + early_n) % cycle_n;
+                        let offset_n = (N - pre_n) % cycle_n;
                         let offset_h = *cached_height_by_rock.get(&(pre_n + offset_n)).unwrap();
 
-                        return (N - pre_n) / cycle_n * cycle_h + offset_h;
+                        recorder.last(n_rock + 1, |painter| draw(painter, &tableau));
+
+                        return Ok((N - pre_n) / cycle_n * cycle_h + offset_h);
                     } else {
                         let h = tableau.last().unwrap().0;
                         prefix = Some((n_rock, h));
@@ -207,17 +263,54 @@ pub fn star_two() -> usize {
     unreachable!()
 }
 
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        17
+    }
+
+    fn title(&self) -> &'static str {
+        "Pyroclastic Flow"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn renders(&self) -> bool {
+        true
+    }
+
+    fn render(
+        &self,
+        part: u8,
+        input: &str,
+        recorder: &mut Recorder,
+    ) -> Option<Result<Answer, ParseError>> {
+        match part {
+            1 => Some(stack(input, recorder).map(Answer::from)),
+            2 => Some(extrapolate(input, recorder).map(Answer::from)),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 3068);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(3068));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 1514285714288);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(1514285714288));
     }
 }
@@ -1,22 +1,29 @@
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
+use std::collections::btree_map::Entry;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::collections::VecDeque;
 
-pub fn star_one() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
+type Cube = (isize, isize, isize);
 
-    let mut cubes = BTreeSet::<(isize, isize, isize)>::new();
-    for line in values.lines() {
-        let mut coords = line.split(',').map(|c| c.parse::<isize>().unwrap());
+/// Reads the cubes, one `x,y,z` per line.
+fn parse(values: &str) -> Result<BTreeSet<Cube>, ParseError> {
+    let mut cubes = BTreeSet::new();
+    for line in parse::lines(values) {
+        let (x, rest) = line.split_once(line.text, ",")?;
+        let (y, z) = line.split_once(rest, ",")?;
         cubes.insert((
-            coords.next().unwrap(),
-            coords.next().unwrap(),
-            coords.next().unwrap(),
+            line.parse(x, "x coordinate")?,
+            line.parse(y, "y coordinate")?,
+            line.parse(z, "z coordinate")?,
         ));
     }
+    Ok(cubes)
+}
+
+pub fn star_one(values: &str) -> Result<u64, ParseError> {
+    let cubes = parse(values)?;
 
     let mut faces = 0;
 
@@ -35,15 +42,10 @@ pub fn star_one() -> u64 {
         }
     }
 
-    faces
+    Ok(faces)
 }
 
-pub fn star_two() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
+pub fn star_two(values: &str) -> Result<u64, ParseError> {
     #[derive(Debug)]
     enum Cell {
         Exterior,
@@ -54,12 +56,8 @@ pub fn star_two() -> u64 {
 
     let mut bound_start = (isize::MAX, isize::MAX, isize::MAX);
     let mut bound_end = (isize::MIN, isize::MIN, isize::MIN);
-    let mut cubes = BTreeSet::<(isize, isize, isize)>::new();
-    for line in values.lines() {
-        let mut coords = line.split(',').map(|c| c.parse::<isize>().unwrap());
-        let x = coords.next().unwrap();
-        let y = coords.next().unwrap();
-        let z = coords.next().unwrap();
+    let cubes = parse(values)?;
+    for &(x, y, z) in &cubes {
         if x < bound_start.0 {
             bound_start.0 = x
         };
@@ -78,7 +76,6 @@ pub fn star_two() -> u64 {
         if z > bound_end.2 {
             bound_end.2 = z
         };
-        cubes.insert((x, y, z));
         map.insert((x, y, z), Cell::Solid);
     }
     bound_start.0 -= 1;
@@ -110,8 +107,8 @@ pub fn star_two() -> u64 {
                 && q.2 <= bound_end.2
         });
         for q in neighbours {
-            if !map.contains_key(&q) {
-                map.insert(q, Cell::Exterior);
+            if let Entry::Vacant(entry) = map.entry(q) {
+                entry.insert(Cell::Exterior);
                 queue.push_back(q);
             }
         }
@@ -136,7 +133,27 @@ pub fn star_two() -> u64 {
         }
     }
 
-    faces
+    Ok(faces)
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        18
+    }
+
+    fn title(&self) -> &'static str {
+        "Boiling Boulders"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
 }
 
 #[cfg(test)]
@@ -145,11 +162,11 @@ mod tests {
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 64);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(64));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 58);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(58));
     }
 }
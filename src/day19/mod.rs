@@ -1,4 +1,9 @@
+use crate::parse::{self, Line, ParseError};
+use crate::solution::{Answer, Solution};
+use rand::rngs::StdRng;
+use rand::Rng;
 use rayon::prelude::*;
+use std::ops::RangeInclusive;
 
 #[derive(Clone, Copy)]
 struct Amount {
@@ -47,38 +52,70 @@ struct State {
     robots: Amount,
 }
 
-fn parse_blueprint(line: &str) -> Blueprint {
-    let mut tokens = line.split_ascii_whitespace();
+/// Reads a blueprint such as `Blueprint 1: Each ore robot costs 4 ore. [...]`.
+fn parse_blueprint(line: &Line) -> Result<Blueprint, ParseError> {
+    let mut tokens = line.tokens();
+    tokens.keyword("Blueprint")?;
+    tokens.expect("blueprint id")?;
+
+    let mut robot = |kind: &str, other: Option<&str>| -> Result<(usize, usize), ParseError> {
+        tokens.keyword("Each")?;
+        tokens.keyword(kind)?;
+        tokens.keyword("robot")?;
+        tokens.keyword("costs")?;
+        let ore = tokens.parse("ore cost")?;
+        let other = match other {
+            Some(other) => {
+                tokens.keyword("ore")?;
+                tokens.keyword("and")?;
+                let cost = tokens.parse(&format!("{other} cost"))?;
+                tokens.keyword(&format!("{other}."))?;
+                cost
+            }
+            None => {
+                tokens.keyword("ore.")?;
+                0
+            }
+        };
+        Ok((ore, other))
+    };
+
+    let (ore, _) = robot("ore", None)?;
     let ore_robot_cost = Amount {
-        ore: tokens.nth(6).unwrap().parse().unwrap(),
+        ore,
         clay: 0,
         obsidian: 0,
         geode: 0,
     };
+    let (ore, _) = robot("clay", None)?;
     let clay_robot_cost = Amount {
-        ore: tokens.nth(5).unwrap().parse().unwrap(),
+        ore,
         clay: 0,
         obsidian: 0,
         geode: 0,
     };
+    let (ore, clay) = robot("obsidian", Some("clay"))?;
     let obsidian_robot_cost = Amount {
-        ore: tokens.nth(5).unwrap().parse().unwrap(),
-        clay: tokens.nth(2).unwrap().parse().unwrap(),
+        ore,
+        clay,
         obsidian: 0,
         geode: 0,
     };
+    let (ore, obsidian) = robot("geode", Some("obsidian"))?;
     let geode_robot_cost = Amount {
-        ore: tokens.nth(5).unwrap().parse().unwrap(),
+        ore,
         clay: 0,
-        obsidian: tokens.nth(2).unwrap().parse().unwrap(),
+        obsidian,
         geode: 0,
     };
-    Blueprint {
+    tokens.end()?;
+
+    Ok(Blueprint {
         ore: ore_robot_cost,
         clay: clay_robot_cost,
         obsidian: obsidian_robot_cost,
         geode: geode_robot_cost,
-    }
+    })
 }
 
 fn optimise(blueprint: &Blueprint, max_time: usize) -> usize {
@@ -121,7 +158,7 @@ fn optimise(blueprint: &Blueprint, max_time: usize) -> usize {
 
     let mut max_geodes = 0;
     simulate_recursively(
-        &blueprint,
+        blueprint,
         &max_robots,
         &State {
             time: 0,
@@ -164,49 +201,50 @@ fn simulate_recursively(
         *max_geodes = state.resources.geode;
     }
     // if we could build geode robots every turn, would we catch up with the the cached max?
-    if {
+    // a robot built this turn already cracks a geode the next one
+    let out_of_reach = {
         let mut g = state.resources.geode;
         for t in 0.. {
             if state.time + t == max_time {
                 break;
             }
-            g = g + state.resources.geode + state.robots.geode + t;
+            g += state.robots.geode + t + 1;
         }
         g < *max_geodes
-    } {
+    };
+    if out_of_reach {
         return;
     }
 
-    if state.robots.ore < max_robots.ore {
-        if blueprint.ore.ore + state.robots.ore <= state.resources.ore {
-            state.resources = state.resources - blueprint.ore;
-            state.robots.ore += 1;
-            simulate_recursively(blueprint, max_robots, &state, max_time, max_geodes);
-            state.robots.ore -= 1;
-            state.resources = state.resources + blueprint.ore;
-        }
+    if state.robots.ore < max_robots.ore
+        && blueprint.ore.ore + state.robots.ore <= state.resources.ore
+    {
+        state.resources = state.resources - blueprint.ore;
+        state.robots.ore += 1;
+        simulate_recursively(blueprint, max_robots, &state, max_time, max_geodes);
+        state.robots.ore -= 1;
+        state.resources = state.resources + blueprint.ore;
     }
 
-    if state.robots.clay < max_robots.clay {
-        if blueprint.clay.ore + state.robots.ore <= state.resources.ore {
-            state.resources = state.resources - blueprint.clay;
-            state.robots.clay += 1;
-            simulate_recursively(blueprint, max_robots, &state, max_time, max_geodes);
-            state.robots.clay -= 1;
-            state.resources = state.resources + blueprint.clay;
-        }
+    if state.robots.clay < max_robots.clay
+        && blueprint.clay.ore + state.robots.ore <= state.resources.ore
+    {
+        state.resources = state.resources - blueprint.clay;
+        state.robots.clay += 1;
+        simulate_recursively(blueprint, max_robots, &state, max_time, max_geodes);
+        state.robots.clay -= 1;
+        state.resources = state.resources + blueprint.clay;
     }
 
-    if state.robots.obsidian < max_robots.obsidian {
-        if blueprint.obsidian.ore + state.robots.ore <= state.resources.ore
-            && blueprint.obsidian.clay + state.robots.clay <= state.resources.clay
-        {
-            state.resources = state.resources - blueprint.obsidian;
-            state.robots.obsidian += 1;
-            simulate_recursively(blueprint, max_robots, &state, max_time, max_geodes);
-            state.robots.obsidian -= 1;
-            state.resources = state.resources + blueprint.obsidian;
-        }
+    if state.robots.obsidian < max_robots.obsidian
+        && blueprint.obsidian.ore + state.robots.ore <= state.resources.ore
+        && blueprint.obsidian.clay + state.robots.clay <= state.resources.clay
+    {
+        state.resources = state.resources - blueprint.obsidian;
+        state.robots.obsidian += 1;
+        simulate_recursively(blueprint, max_robots, &state, max_time, max_geodes);
+        state.robots.obsidian -= 1;
+        state.resources = state.resources + blueprint.obsidian;
     }
 
     if blueprint.geode.ore + state.robots.ore <= state.resources.ore
@@ -222,47 +260,170 @@ fn simulate_recursively(
     simulate_recursively(blueprint, max_robots, &state, max_time, max_geodes);
 }
 
-pub fn star_one() -> usize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
+pub fn star_one(values: &str) -> Result<usize, ParseError> {
+    let blueprints = parse::lines(values)
+        .map(|line| parse_blueprint(&line))
+        .collect::<Result<Vec<_>, _>>()?;
 
-    let blueprints = values.lines().map(parse_blueprint).collect::<Vec<_>>();
-
-    blueprints
+    Ok(blueprints
         .par_iter()
         .enumerate()
         .map(|(index, blueprint)| optimise(blueprint, 24) * (index + 1))
-        .sum()
+        .sum())
 }
 
-pub fn star_two() -> usize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let blueprints = values.lines().map(parse_blueprint).collect::<Vec<_>>();
+pub fn star_two(values: &str) -> Result<usize, ParseError> {
+    let blueprints = parse::lines(values)
+        .map(|line| parse_blueprint(&line))
+        .collect::<Result<Vec<_>, _>>()?;
 
-    blueprints
+    Ok(blueprints
         .par_iter()
         .take(3)
         .map(|blueprint| optimise(blueprint, 32))
-        .product()
+        .product())
+}
+
+/// `size` blueprints, their costs drawn in order from `costs`: the ore cost of each robot,
+/// followed by the clay or obsidian one of the last two.
+fn blueprints(rng: &mut StdRng, size: usize, costs: [RangeInclusive<usize>; 6]) -> String {
+    (1..=size)
+        .map(|id| {
+            let [a, b, c, d, e, f] = costs.clone().map(|cost| rng.gen_range(cost));
+            format!(
+                "Blueprint {id}: Each ore robot costs {a} ore. Each clay robot costs {b} ore. \
+                 Each obsidian robot costs {c} ore and {d} clay. \
+                 Each geode robot costs {e} ore and {f} obsidian.\n"
+            )
+        })
+        .collect()
+}
+
+/// `size` blueprints, with costs in the ranges of the puzzle inputs.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    blueprints(rng, size, [2..=4, 2..=4, 2..=4, 5..=20, 2..=4, 5..=20])
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        19
+    }
+
+    fn title(&self) -> &'static str {
+        "Not Enough Minerals"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn slow(&self) -> bool {
+        true
+    }
+
+    fn generate(&self, rng: &mut StdRng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing;
+    use std::collections::HashSet;
+
+    /// How many minutes the blueprints are compared over, short enough for every state to be
+    /// tried.
+    const MINUTES: usize = 13;
+
+    /// A few blueprints, cheap enough for geodes to be cracked within `MINUTES`.
+    fn generate_cheap(rng: &mut StdRng) -> String {
+        let count = rng.gen_range(1..=3);
+        blueprints(rng, count, [1..=4, 1..=4, 1..=4, 2..=8, 1..=4, 2..=8])
+    }
+
+    fn amount(amount: Amount) -> [usize; 4] {
+        [amount.ore, amount.clay, amount.obsidian, amount.geode]
+    }
+
+    /// Tries every choice of every minute, waiting or building any affordable robot, and
+    /// returns the most geodes cracked after each minute.
+    fn open_ended(blueprint: &Blueprint, minutes: usize) -> Vec<usize> {
+        let costs = [
+            blueprint.ore,
+            blueprint.clay,
+            blueprint.obsidian,
+            blueprint.geode,
+        ]
+        .map(amount);
+        let mut states = HashSet::from([([0; 4], [1, 0, 0, 0])]);
+        let mut most_geodes = vec![];
+        for _ in 0..minutes {
+            let mut next_states = HashSet::new();
+            for (resources, robots) in states {
+                let produce = |mut resources: [usize; 4]| {
+                    (0..4).for_each(|i| resources[i] += robots[i]);
+                    resources
+                };
+                next_states.insert((produce(resources), robots));
+                for (kind, cost) in costs.iter().enumerate() {
+                    if (0..4).all(|i| resources[i] >= cost[i]) {
+                        let mut left = resources;
+                        (0..4).for_each(|i| left[i] -= cost[i]);
+                        let mut built = robots;
+                        built[kind] += 1;
+                        next_states.insert((produce(left), built));
+                    }
+                }
+            }
+            states = next_states;
+            most_geodes.push(
+                states
+                    .iter()
+                    .map(|(resources, _)| resources[3])
+                    .max()
+                    .unwrap(),
+            );
+        }
+        most_geodes
+    }
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 33);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(33));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 3472);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(3472));
+    }
+
+    #[test]
+    fn test_optimise_against_open_ended() {
+        let blueprints = |values: &str| {
+            parse::lines(values)
+                .map(|line| parse_blueprint(&line).unwrap())
+                .collect::<Vec<_>>()
+        };
+        testing::differential(
+            20,
+            generate_cheap,
+            |values| {
+                let blueprints = blueprints(values);
+                let optimise = |b| (1..=MINUTES).map(|t| optimise(b, t)).collect::<Vec<_>>();
+                blueprints.iter().map(optimise).collect::<Vec<_>>()
+            },
+            |values| {
+                let blueprints = blueprints(values);
+                let open_ended = |b| open_ended(b, MINUTES);
+                blueprints.iter().map(open_ended).collect()
+            },
+        );
     }
 }
@@ -1,124 +1,246 @@
-#[derive(Clone, Copy)]
-enum Sign {
+pub mod simulate;
+
+use crate::parse::{self, Line, ParseError};
+use crate::solution::{Answer, Solution};
+use std::io::BufRead;
+
+/// A move of the game of the puzzle, `Game::rock_paper_scissors`, standing for its position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sign {
     Rock,
     Paper,
     Scissor,
 }
 
-impl std::str::FromStr for Sign {
-    type Err = &'static str;
+impl Sign {
+    pub const ALL: [Sign; 3] = [Sign::Rock, Sign::Paper, Sign::Scissor];
+
+    /// The position of the move in `Game::rock_paper_scissors`.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// The outcome of a round, for the one responding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Loss,
+    Draw,
+    Win,
+}
+
+/// How the second column of a strategy guide is read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    /// The move to respond with.
+    Move,
+    /// The outcome the round should have.
+    Outcome,
+}
+
+/// A move of a game: its name, the symbols standing for it in the first and second columns
+/// of a strategy guide, and what responding with it scores.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Move {
+    pub name: String,
+    pub elf: String,
+    pub response: String,
+    pub score: u64,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "A" | "X" => Ok(Sign::Rock),
-            "B" | "Y" => Ok(Sign::Paper),
-            "C" | "Z" => Ok(Sign::Scissor),
-            _ => Err("Not recognised"),
+impl Move {
+    pub fn new(name: &str, elf: &str, response: &str, score: u64) -> Self {
+        Self {
+            name: name.to_string(),
+            elf: elf.to_string(),
+            response: response.to_string(),
+            score,
         }
     }
 }
 
-impl Sign {
-    fn score(elf: Sign, response: Sign) -> u64 {
-        match response {
-            Sign::Rock => {
-                1 + match elf {
-                    Sign::Rock => 3,
-                    Sign::Paper => 0,
-                    Sign::Scissor => 6,
+/// A cyclic game of an odd number of moves: each move beats the moves an odd number of steps
+/// before it in the cycle, and loses to the others. Moves are referred to by their position.
+pub struct Game {
+    moves: Vec<Move>,
+    /// The symbols standing for a loss, a draw and a win in the second column.
+    outcomes: [String; 3],
+    /// What a loss, a draw and a win score.
+    scores: [u64; 3],
+}
+
+impl Game {
+    /// A game of the given moves, in cycle order. Fails unless there is an odd number of them,
+    /// 3 at least, and the symbols of each column are distinct words.
+    pub fn new(moves: Vec<Move>, outcomes: [&str; 3], scores: [u64; 3]) -> Result<Self, String> {
+        if moves.len() < 3 || moves.len().is_multiple_of(2) {
+            let message = "expected an odd number of moves, 3 at least";
+            return Err(format!("{message}, found {}", moves.len()));
+        }
+        let columns = [
+            moves.iter().map(|m| m.elf.as_str()).collect::<Vec<_>>(),
+            moves.iter().map(|m| m.response.as_str()).collect(),
+            outcomes.to_vec(),
+        ];
+        for column in &columns {
+            for (i, symbol) in column.iter().enumerate() {
+                if symbol.is_empty() || symbol.contains(char::is_whitespace) {
+                    return Err(format!("invalid symbol `{symbol}`"));
                 }
-            }
-            Sign::Paper => {
-                2 + match elf {
-                    Sign::Rock => 6,
-                    Sign::Paper => 3,
-                    Sign::Scissor => 0,
-                }
-            }
-            Sign::Scissor => {
-                3 + match elf {
-                    Sign::Rock => 0,
-                    Sign::Paper => 6,
-                    Sign::Scissor => 3,
+                if column[..i].contains(symbol) {
+                    return Err(format!("symbol `{symbol}` given twice"));
                 }
             }
         }
+
+        Ok(Self {
+            moves,
+            outcomes: outcomes.map(str::to_string),
+            scores,
+        })
+    }
+
+    /// The game of the puzzle, rock, paper and scissors read as A, B and C, then X, Y and Z.
+    pub fn rock_paper_scissors() -> Self {
+        let moves = vec![
+            Move::new("rock", "A", "X", 1),
+            Move::new("paper", "B", "Y", 2),
+            Move::new("scissors", "C", "Z", 3),
+        ];
+        Self::new(moves, ["X", "Y", "Z"], [0, 3, 6]).expect("the puzzle rules should be valid")
+    }
+
+    /// Rock, paper, scissors, Spock and lizard, read as A to E, then V to Z. Outcomes are read
+    /// as X, Y and Z.
+    pub fn rock_paper_scissors_lizard_spock() -> Self {
+        let moves = vec![
+            Move::new("rock", "A", "V", 1),
+            Move::new("paper", "B", "W", 2),
+            Move::new("scissors", "C", "X", 3),
+            Move::new("Spock", "D", "Y", 4),
+            Move::new("lizard", "E", "Z", 5),
+        ];
+        Self::new(moves, ["X", "Y", "Z"], [0, 3, 6]).expect("the rules should be valid")
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The outcome of responding with `response` to `elf`.
+    pub fn outcome(&self, elf: usize, response: usize) -> Outcome {
+        let len = self.moves.len();
+        match (response + len - elf) % len {
+            0 => Outcome::Draw,
+            steps if steps % 2 == 1 => Outcome::Win,
+            _ => Outcome::Loss,
+        }
+    }
+
+    /// The score of responding with `response` to `elf`.
+    pub fn score(&self, elf: usize, response: usize) -> u64 {
+        self.moves[response].score + self.scores[self.outcome(elf, response) as usize]
+    }
+
+    /// The response to `elf` with the given outcome: the move right after it to win, and the
+    /// one right before it to lose.
+    pub fn respond(&self, elf: usize, outcome: Outcome) -> usize {
+        let len = self.moves.len();
+        match outcome {
+            Outcome::Loss => (elf + len - 1) % len,
+            Outcome::Draw => elf,
+            Outcome::Win => (elf + 1) % len,
+        }
+    }
+
+    /// Reads a round of a strategy guide: the move of the elf and the response to it.
+    pub fn round(&self, line: &Line, strategy: Strategy) -> Result<(usize, usize), ParseError> {
+        let mut tokens = line.tokens();
+        let elf = tokens.expect("elf move")?;
+        let elf = find(line, elf, self.moves.iter().map(|m| &m.elf), "elf move")?;
+        let token = tokens.expect("response")?;
+        let response = match strategy {
+            Strategy::Move => find(line, token, self.moves.iter().map(|m| &m.response), "move")?,
+            Strategy::Outcome => {
+                let outcome = find(line, token, &self.outcomes, "outcome")?;
+                let outcomes = [Outcome::Loss, Outcome::Draw, Outcome::Win];
+                self.respond(elf, outcomes[outcome])
+            }
+        };
+        tokens.end()?;
+        Ok((elf, response))
     }
 
-    /// response encodes:
-    /// - rock => actual response should lose
-    /// - paper => actual response should draw
-    /// - scissor => actual response should win
-    fn evaluate_strategy(elf: Sign, encoded_response: Sign) -> Sign {
-        match elf {
-            Sign::Rock => match encoded_response {
-                Sign::Rock => Sign::Scissor,
-                Sign::Paper => Sign::Rock,
-                Sign::Scissor => Sign::Paper,
-            },
-            Sign::Paper => match encoded_response {
-                Sign::Rock => Sign::Rock,
-                Sign::Paper => Sign::Paper,
-                Sign::Scissor => Sign::Scissor,
-            },
-            Sign::Scissor => match encoded_response {
-                Sign::Rock => Sign::Paper,
-                Sign::Paper => Sign::Scissor,
-                Sign::Scissor => Sign::Rock,
-            },
+    /// The total score of a strategy guide read line by line from `reader`.
+    pub fn play(&self, strategy: Strategy, reader: impl BufRead) -> Result<u64, ParseError> {
+        let mut score = 0;
+        let mut lines = parse::stream(reader);
+        while let Some(line) = lines.next_line()? {
+            let (elf, response) = self.round(&line, strategy)?;
+            score += self.score(elf, response);
         }
+
+        Ok(score)
     }
 }
 
-pub fn star_one() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut score = 0;
-    for line in values.lines() {
-        let mut tokens = line.split_whitespace();
-        let elf = tokens
-            .next()
-            .expect("Missing elf")
-            .parse::<Sign>()
-            .expect("Invalid elf");
-        let response = tokens
-            .next()
-            .expect("Missing response")
-            .parse::<Sign>()
-            .expect("Invalid response");
-        score += Sign::score(elf, response);
-    }
-
-    score
+/// The position of `token`, a slice of `line`, amongst `symbols`.
+fn find<'a>(
+    line: &Line,
+    token: &str,
+    symbols: impl IntoIterator<Item = &'a String>,
+    what: &str,
+) -> Result<usize, ParseError> {
+    symbols
+        .into_iter()
+        .position(|symbol| symbol == token)
+        .ok_or_else(|| line.error(token, format!("expected {what}, found `{token}`")))
+}
+
+pub fn star_one(values: &str) -> Result<u64, ParseError> {
+    star_one_from(values.as_bytes())
+}
+
+pub fn star_two(values: &str) -> Result<u64, ParseError> {
+    star_two_from(values.as_bytes())
+}
+
+/// Same as `star_one`, reading the strategy guide line by line from `reader`.
+pub fn star_one_from(reader: impl BufRead) -> Result<u64, ParseError> {
+    Game::rock_paper_scissors().play(Strategy::Move, reader)
 }
 
-pub fn star_two() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut score = 0;
-    for line in values.lines() {
-        let mut tokens = line.split_whitespace();
-        let elf = tokens
-            .next()
-            .expect("Missing elf")
-            .parse::<Sign>()
-            .expect("Invalid elf");
-        let encoded_response = tokens
-            .next()
-            .expect("Missing response")
-            .parse::<Sign>()
-            .expect("Invalid response");
-        let actual_response = Sign::evaluate_strategy(elf, encoded_response);
-        score += Sign::score(elf, actual_response);
-    }
-
-    score
+/// Same as `star_two`, reading the strategy guide line by line from `reader`.
+pub fn star_two_from(reader: impl BufRead) -> Result<u64, ParseError> {
+    Game::rock_paper_scissors().play(Strategy::Outcome, reader)
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        2
+    }
+
+    fn title(&self) -> &'static str {
+        "Rock Paper Scissors"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn stream(&self, part: u8, reader: &mut dyn BufRead) -> Option<Result<Answer, ParseError>> {
+        match part {
+            1 => Some(star_one_from(reader).map(Answer::from)),
+            2 => Some(star_two_from(reader).map(Answer::from)),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -127,11 +249,85 @@ mod tests {
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 15);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(15));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 12);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(12));
+    }
+
+    #[test]
+    fn test_rock_paper_scissors() {
+        let game = Game::rock_paper_scissors();
+        let beaten = |sign: Sign| {
+            let beaten = Sign::ALL
+                .into_iter()
+                .filter(|other| game.outcome(other.index(), sign.index()) == Outcome::Win);
+            beaten.collect::<Vec<_>>()
+        };
+        assert_eq!(beaten(Sign::Rock), [Sign::Scissor]);
+        assert_eq!(beaten(Sign::Paper), [Sign::Rock]);
+        assert_eq!(beaten(Sign::Scissor), [Sign::Paper]);
+        let response = game.respond(Sign::Rock.index(), Outcome::Loss);
+        assert_eq!(response, Sign::Scissor.index());
+        assert_eq!(game.score(Sign::Rock.index(), response), 3);
+    }
+
+    #[test]
+    fn test_rock_paper_scissors_lizard_spock() {
+        let game = Game::rock_paper_scissors_lizard_spock();
+        let beaten = |name: &str| {
+            let response = game.moves().iter().position(|m| m.name == name).unwrap();
+            let beaten = (0..5).filter(|&elf| game.outcome(elf, response) == Outcome::Win);
+            beaten
+                .map(|elf| game.moves()[elf].name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(beaten("rock"), ["scissors", "lizard"]);
+        assert_eq!(beaten("paper"), ["rock", "Spock"]);
+        assert_eq!(beaten("scissors"), ["paper", "lizard"]);
+        assert_eq!(beaten("Spock"), ["rock", "scissors"]);
+        assert_eq!(beaten("lizard"), ["paper", "Spock"]);
+
+        let values = "A Y\nD Z\nE E\n";
+        let err = game.play(Strategy::Move, values.as_bytes()).unwrap_err();
+        assert_eq!(err, ParseError::new(3, 3, "expected move, found `E`"));
+        // lose to Spock with scissors, then beat rock with paper
+        let values = "D X\nA Z\n";
+        assert_eq!(game.play(Strategy::Outcome, values.as_bytes()), Ok(3 + 8));
+    }
+
+    #[test]
+    fn test_game() {
+        let moves = |count: usize| {
+            let symbols = (0..count).map(|i| ((b'a' + i as u8) as char).to_string());
+            symbols
+                .map(|s| Move::new(&s, &s, &s, 0))
+                .collect::<Vec<_>>()
+        };
+        let outcomes = ["l", "d", "w"];
+        let err = "expected an odd number of moves, 3 at least, found 4";
+        assert_eq!(
+            Game::new(moves(4), outcomes, [0; 3]).err().as_deref(),
+            Some(err)
+        );
+        let mut twice = moves(3);
+        twice[2].response = "a".to_string();
+        let err = Game::new(twice, outcomes, [0; 3]).err();
+        assert_eq!(err.as_deref(), Some("symbol `a` given twice"));
+        let err = Game::new(moves(3), ["l", "d d", "w"], [0; 3]).err();
+        assert_eq!(err.as_deref(), Some("invalid symbol `d d`"));
+
+        // every move beats as many moves as it loses to, and responding for an outcome gets it
+        let game = Game::new(moves(7), outcomes, [0, 3, 6]).unwrap();
+        for elf in 0..7 {
+            let outcomes = (0..7).map(|response| game.outcome(elf, response));
+            let wins = outcomes.filter(|&outcome| outcome == Outcome::Win).count();
+            assert_eq!(wins, 3);
+            for outcome in [Outcome::Loss, Outcome::Draw, Outcome::Win] {
+                assert_eq!(game.outcome(elf, game.respond(elf, outcome)), outcome);
+            }
+        }
     }
 }
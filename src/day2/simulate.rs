@@ -0,0 +1,277 @@
+use super::{Game, Outcome, Strategy};
+use crate::parse::{self, ParseError};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+
+/// How an opponent chooses its moves: the probability of each move of the game, given the
+/// responses to its moves so far.
+pub trait Opponent {
+    fn distribution(&self, game: &Game, responses: &[usize]) -> Vec<f64>;
+}
+
+/// Plays the moves of a sequence in turn, starting over at its end.
+pub struct Fixed {
+    moves: Vec<usize>,
+}
+
+impl Fixed {
+    /// Fails unless there is a move at least, each one of the game.
+    pub fn new(game: &Game, moves: Vec<usize>) -> Result<Self, String> {
+        if moves.is_empty() {
+            return Err("expected a move at least".to_string());
+        }
+        check_move(game, &moves)?;
+        Ok(Self { moves })
+    }
+}
+
+impl Opponent for Fixed {
+    fn distribution(&self, game: &Game, responses: &[usize]) -> Vec<f64> {
+        certainly(game, self.moves[responses.len() % self.moves.len()])
+    }
+}
+
+/// Plays every move with a given frequency, whatever happened before.
+pub struct Random {
+    frequencies: Vec<f64>,
+}
+
+impl Random {
+    /// Fails unless there is a frequency per move of the game, all finite, none negative,
+    /// and one at least other than 0. They need not add up to 1.
+    pub fn new(game: &Game, frequencies: Vec<f64>) -> Result<Self, String> {
+        if frequencies.len() != game.moves().len() {
+            return Err(format!(
+                "expected {} frequencies, one per move, not {}",
+                game.moves().len(),
+                frequencies.len()
+            ));
+        }
+        if let Some(f) = frequencies.iter().find(|f| !f.is_finite() || **f < 0.0) {
+            return Err(format!("invalid frequency {f}"));
+        }
+        let total = frequencies.iter().sum::<f64>();
+        if total == 0.0 {
+            return Err("expected a frequency other than 0".to_string());
+        }
+        let frequencies = frequencies.iter().map(|f| f / total).collect();
+        Ok(Self { frequencies })
+    }
+}
+
+impl Opponent for Random {
+    fn distribution(&self, _: &Game, _: &[usize]) -> Vec<f64> {
+        self.frequencies.clone()
+    }
+}
+
+/// Plays the move beating the response played most so far, the earliest in the cycle on
+/// ties, or `opening` on the first round.
+pub struct Adaptive {
+    opening: usize,
+}
+
+impl Adaptive {
+    /// Fails unless the opening is a move of the game.
+    pub fn new(game: &Game, opening: usize) -> Result<Self, String> {
+        check_move(game, &[opening])?;
+        Ok(Self { opening })
+    }
+}
+
+impl Opponent for Adaptive {
+    fn distribution(&self, game: &Game, responses: &[usize]) -> Vec<f64> {
+        let mut counts = vec![0; game.moves().len()];
+        for &response in responses {
+            counts[response] += 1;
+        }
+        // the first of the most played, as `max_by_key` keeps the last
+        let most = (0..counts.len()).rev().max_by_key(|&m| counts[m]);
+        match most {
+            Some(most) if !responses.is_empty() => {
+                certainly(game, game.respond(most, Outcome::Win))
+            }
+            _ => certainly(game, self.opening),
+        }
+    }
+}
+
+/// Fails on the first index which is not one of a move of the game.
+fn check_move(game: &Game, indices: &[usize]) -> Result<(), String> {
+    match indices.iter().find(|&&index| index >= game.moves().len()) {
+        Some(index) => Err(format!(
+            "no move {index}, the game has {}",
+            game.moves().len()
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The distribution of an opponent sure to play `index`.
+fn certainly(game: &Game, index: usize) -> Vec<f64> {
+    let mut distribution = vec![0.0; game.moves().len()];
+    distribution[index] = 1.0;
+    distribution
+}
+
+/// The expected score of responding with `response` to a move drawn from `distribution`.
+fn expected(game: &Game, distribution: &[f64], response: usize) -> f64 {
+    let scores = distribution.iter().enumerate();
+    scores
+        .map(|(elf, p)| p * game.score(elf, response) as f64)
+        .sum()
+}
+
+/// The responses to `rounds` moves of `opponent`, each maximising the expected score of its
+/// round given the responses before it, the earliest in the cycle on ties. Against an
+/// adaptive opponent, a lower score now may pay later, which this does not look for.
+pub fn best_responses(game: &Game, opponent: &dyn Opponent, rounds: usize) -> Vec<usize> {
+    let mut responses = Vec::with_capacity(rounds);
+    for _ in 0..rounds {
+        let distribution = opponent.distribution(game, &responses);
+        let scores = (0..game.moves().len()).map(|r| expected(game, &distribution, r));
+        // the first of the best, as `max_by` keeps the last
+        let best = scores
+            .enumerate()
+            .rev()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(response, _)| response);
+        responses.push(best.expect("a game has moves"));
+    }
+    responses
+}
+
+/// The responses of a strategy guide, its second column read as moves.
+pub fn responses(game: &Game, values: &str) -> Result<Vec<usize>, ParseError> {
+    parse::lines(values)
+        .map(|line| Ok(game.round(&line, Strategy::Move)?.1))
+        .collect()
+}
+
+/// The total score of a game over many simulated games.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Report {
+    pub mean: f64,
+    /// The sample variance, 0 for a single game.
+    pub variance: f64,
+}
+
+/// Plays `responses` against `opponent` `trials` times, drawing its moves with `rng`. None
+/// without any trial, as there is no score to report.
+pub fn simulate(
+    game: &Game,
+    opponent: &dyn Opponent,
+    responses: &[usize],
+    trials: usize,
+    rng: &mut StdRng,
+) -> Option<Report> {
+    if trials == 0 {
+        return None;
+    }
+    // the responses are set in advance, so are the distributions of the moves against them
+    let moves = (0..responses.len())
+        .map(|round| {
+            let distribution = opponent.distribution(game, &responses[..round]);
+            WeightedIndex::new(distribution).expect("a distribution should have a move")
+        })
+        .collect::<Vec<_>>();
+    let scores = (0..trials)
+        .map(|_| {
+            let rounds = moves.iter().zip(responses);
+            rounds
+                .map(|(moves, &response)| game.score(moves.sample(rng), response))
+                .sum::<u64>() as f64
+        })
+        .collect::<Vec<_>>();
+
+    let mean = scores.iter().sum::<f64>() / trials as f64;
+    let deviations = scores.iter().map(|score| (score - mean).powi(2));
+    let variance = deviations.sum::<f64>() / trials.saturating_sub(1).max(1) as f64;
+    Some(Report { mean, variance })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day2::Sign;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_fixed() {
+        let game = Game::rock_paper_scissors();
+        let guide = responses(&game, include_str!("mock.txt")).unwrap();
+        let elves = Sign::ALL.map(Sign::index).to_vec();
+        assert!(Fixed::new(&game, vec![]).is_err());
+        assert!(Fixed::new(&game, vec![0, 3]).is_err());
+        let opponent = Fixed::new(&game, elves).unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+        let expected = Report {
+            mean: 15.0,
+            variance: 0.0,
+        };
+        assert_eq!(
+            simulate(&game, &opponent, &guide, 10, &mut rng),
+            Some(expected)
+        );
+
+        let best = best_responses(&game, &opponent, 4);
+        let signs = [Sign::Paper, Sign::Scissor, Sign::Rock, Sign::Paper];
+        assert_eq!(best, signs.map(Sign::index));
+        let expected = Report {
+            mean: 8.0 + 9.0 + 7.0 + 8.0,
+            variance: 0.0,
+        };
+        assert_eq!(
+            simulate(&game, &opponent, &best, 10, &mut rng),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn test_random() {
+        let game = Game::rock_paper_scissors();
+        assert!(Random::new(&game, vec![0.0, 0.0, 0.0]).is_err());
+        assert!(Random::new(&game, vec![1.0, -1.0, 1.0]).is_err());
+        assert!(Random::new(&game, vec![1.0, 1.0]).is_err());
+
+        // every response is as likely to win, so the highest scoring move is best
+        let opponent = Random::new(&game, vec![1.0, 1.0, 1.0]).unwrap();
+        let best = best_responses(&game, &opponent, 100);
+        assert_eq!(best, [Sign::Scissor.index(); 100]);
+        // each round scores 3, 6 or 9 as likely: 6 on average, with a variance of 6
+        let mut rng = StdRng::seed_from_u64(0);
+        let report = simulate(&game, &opponent, &best, 2000, &mut rng).unwrap();
+        assert!((report.mean - 600.0).abs() < 2.0, "{report:?}");
+        assert!((report.variance - 600.0).abs() < 60.0, "{report:?}");
+        assert_eq!(simulate(&game, &opponent, &best, 0, &mut rng), None);
+
+        // mostly rock: paper pays more than scissors
+        let opponent = Random::new(&game, vec![3.0, 1.0, 1.0]).unwrap();
+        assert_eq!(best_responses(&game, &opponent, 1), [Sign::Paper.index()]);
+    }
+
+    #[test]
+    fn test_adaptive() {
+        let game = Game::rock_paper_scissors();
+        assert!(Adaptive::new(&game, 3).is_err());
+        let opponent = Adaptive::new(&game, Sign::Rock.index()).unwrap();
+        let best = best_responses(&game, &opponent, 5);
+        let signs = [
+            Sign::Paper,
+            Sign::Rock,
+            Sign::Scissor,
+            Sign::Scissor,
+            Sign::Paper,
+        ];
+        assert_eq!(best, signs.map(Sign::index));
+        let expected = Report {
+            mean: 41.0,
+            variance: 0.0,
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(
+            simulate(&game, &opponent, &best, 1, &mut rng),
+            Some(expected)
+        );
+    }
+}
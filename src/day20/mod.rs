@@ -1,3 +1,5 @@
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
 use std::collections::VecDeque;
 
 fn mix(data: &[isize], times: usize) -> VecDeque<usize> {
@@ -29,16 +31,24 @@ fn mix(data: &[isize], times: usize) -> VecDeque<usize> {
     positions
 }
 
-pub fn star_one() -> isize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
+/// Reads the encrypted file, one number per line, which must contain a 0 to start from.
+fn parse(values: &str) -> Result<Vec<isize>, ParseError> {
+    let mut numbers = vec![];
+    for line in parse::lines(values) {
+        numbers.push(line.parse::<isize>(line.text, "number")?);
+    }
+    if !numbers.contains(&0) {
+        return Err(ParseError::new(
+            numbers.len() + 1,
+            1,
+            "the file contains no 0",
+        ));
+    }
+    Ok(numbers)
+}
 
-    let values = values
-        .lines()
-        .map(|line| line.parse::<isize>().unwrap())
-        .collect::<Vec<_>>();
+pub fn star_one(values: &str) -> Result<isize, ParseError> {
+    let values = parse(values)?;
 
     let positions = mix(&values, 1);
 
@@ -48,23 +58,17 @@ pub fn star_one() -> isize {
         .position(|&i| i == original_zero_index)
         .unwrap();
 
-    (1..=3)
+    Ok((1..=3)
         .map(|i| {
             let j = (zero_index + i * 1000) % positions.len();
             values[positions[j]]
         })
-        .sum()
+        .sum())
 }
 
-pub fn star_two() -> isize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let values = values
-        .lines()
-        .map(|line| line.parse::<isize>().unwrap())
+pub fn star_two(values: &str) -> Result<isize, ParseError> {
+    let values = parse(values)?
+        .into_iter()
         .map(|v| v * 811589153)
         .collect::<Vec<_>>();
 
@@ -76,25 +80,93 @@ pub fn star_two() -> isize {
         .position(|&i| i == original_zero_index)
         .unwrap();
 
-    (1..=3)
+    Ok((1..=3)
         .map(|i| {
             let j = (zero_index + i * 1000) % positions.len();
             values[positions[j]]
         })
-        .sum()
+        .sum())
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        20
+    }
+
+    fn title(&self) -> &'static str {
+        "Grove Positioning System"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing;
+    use rand::rngs::StdRng;
+    use rand::Rng;
+
+    /// A short file of small numbers, duplicates included, with a single 0.
+    fn generate(rng: &mut StdRng) -> String {
+        let mut numbers = (0..rng.gen_range(1..30))
+            .map(|_| rng.gen_range(-50..=50))
+            .filter(|&n| n != 0)
+            .collect::<Vec<_>>();
+        numbers.insert(rng.gen_range(0..=numbers.len()), 0);
+        numbers.iter().map(|n| format!("{n}\n")).collect()
+    }
+
+    /// Mixes the numbers by moving each one out of a plain list and back in at its new index.
+    fn naive(values: &str, key: isize, times: usize) -> isize {
+        let numbers = parse(values)
+            .unwrap()
+            .into_iter()
+            .map(|n| n * key)
+            .enumerate()
+            .collect::<Vec<_>>();
+        let mut mixed = numbers.clone();
+        for _ in 0..times {
+            for number in &numbers {
+                let from = mixed.iter().position(|n| n == number).unwrap();
+                mixed.remove(from);
+                let to = (from as isize + number.1).rem_euclid(mixed.len().max(1) as isize);
+                mixed.insert(to as usize, *number);
+            }
+        }
+        let zero = mixed.iter().position(|n| n.1 == 0).unwrap();
+        (1..=3)
+            .map(|i| mixed[(zero + i * 1000) % mixed.len()].1)
+            .sum()
+    }
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 3);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(3));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 1623178306);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(1623178306));
+    }
+
+    #[test]
+    fn test_star_one_against_naive() {
+        testing::differential(200, generate, star_one, |values| Ok(naive(values, 1, 1)));
+    }
+
+    #[test]
+    fn test_star_two_against_naive() {
+        testing::differential(200, generate, star_two, |values| {
+            Ok(naive(values, 811589153, 10))
+        });
     }
 }
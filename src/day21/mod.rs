@@ -1,20 +1,45 @@
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::VecDeque;
 
-#[derive(Debug)]
-struct Polynomial(Vec<i64>);
+/// A polynomial with integer coefficients, from the constant one up; never empty.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Polynomial(Vec<i64>);
 
 impl Polynomial {
-    fn deg(&self) -> usize {
+    /// The polynomial with the given coefficients, from the constant one up, without the
+    /// trailing zeros.
+    pub fn new(mut coefficients: Vec<i64>) -> Self {
+        while coefficients.len() > 1 && coefficients.last() == Some(&0) {
+            coefficients.pop();
+        }
+        if coefficients.is_empty() {
+            coefficients.push(0);
+        }
+        Self(coefficients)
+    }
+
+    /// The coefficients, from the constant one up.
+    pub fn coefficients(&self) -> &[i64] {
+        &self.0
+    }
+
+    pub fn deg(&self) -> usize {
         self.0.len() - 1
     }
 
-    fn gcd(&self) -> i64 {
+    /// The greatest common divisor of the coefficients, 0 for the null polynomial.
+    pub fn gcd(&self) -> i64 {
         self.0.iter().fold(0u64, |g, &x| {
             if x == 0 {
                 g
             } else {
-                gcd::binary_u64(g, x.abs() as u64)
+                gcd::binary_u64(g, x.unsigned_abs())
             }
         }) as i64
     }
@@ -22,7 +47,7 @@ impl Polynomial {
 
 impl From<i64> for Polynomial {
     fn from(value: i64) -> Self {
-        Self(vec![value])
+        Self::new(vec![value])
     }
 }
 
@@ -33,18 +58,12 @@ impl std::ops::Add<&Polynomial> for &Polynomial {
         let l = usize::max(self.deg(), rhs.deg());
         let lhs = self.0.iter().chain(std::iter::repeat(&0));
         let rhs = rhs.0.iter().chain(std::iter::repeat(&0));
-        let mut sum = Vec::with_capacity(l + 1);
-        sum.resize(l + 1, 0);
+        let mut sum = vec![0; l + 1];
         let entries = sum.iter_mut();
         for ((&x, &y), z) in lhs.zip(rhs).zip(entries) {
             *z = x + y;
         }
-        sum = sum.into_iter().rev().skip_while(|&x| x == 0).collect();
-        sum.reverse();
-        if sum.is_empty() {
-            sum.push(0)
-        }
-        Polynomial(sum)
+        Polynomial::new(sum)
     }
 }
 
@@ -55,18 +74,12 @@ impl std::ops::Sub<&Polynomial> for &Polynomial {
         let l = usize::max(self.deg(), rhs.deg());
         let lhs = self.0.iter().chain(std::iter::repeat(&0));
         let rhs = rhs.0.iter().chain(std::iter::repeat(&0));
-        let mut diff = Vec::with_capacity(l + 1);
-        diff.resize(l + 1, 0);
+        let mut diff = vec![0; l + 1];
         let entries = diff.iter_mut();
         for ((&x, &y), z) in lhs.zip(rhs).zip(entries) {
             *z = x - y;
         }
-        diff = diff.into_iter().rev().skip_while(|&x| x == 0).collect();
-        diff.reverse();
-        if diff.is_empty() {
-            diff.push(0)
-        }
-        Polynomial(diff)
+        Polynomial::new(diff)
     }
 }
 
@@ -75,22 +88,36 @@ impl std::ops::Mul<&Polynomial> for &Polynomial {
 
     fn mul(self, rhs: &Polynomial) -> Self::Output {
         let l = self.deg() + rhs.deg();
-        let mut mul = Vec::with_capacity(l + 1);
-        mul.resize(l + 1, 0);
+        let mut mul = vec![0; l + 1];
         for (d1, a1) in self.0.iter().enumerate() {
             for (d2, a2) in rhs.0.iter().enumerate() {
                 *mul.get_mut(d1 + d2).unwrap() += a1 * a2;
             }
         }
-        Polynomial(mul)
+        Polynomial::new(mul)
     }
 }
 
-#[derive(Debug)]
-struct Rational(Polynomial, Polynomial);
+/// A rational function, the quotient of two polynomials. Common polynomial factors are not
+/// cancelled out, so equal functions may be written differently.
+#[derive(Clone, Debug)]
+pub struct Rational(Polynomial, Polynomial);
 
 impl Rational {
-    fn simplify(self) -> Self {
+    pub fn new(numerator: Polynomial, denominator: Polynomial) -> Self {
+        Self(numerator, denominator).simplify()
+    }
+
+    pub fn numerator(&self) -> &Polynomial {
+        &self.0
+    }
+
+    pub fn denominator(&self) -> &Polynomial {
+        &self.1
+    }
+
+    /// Divides both polynomials by the greatest common divisor of their coefficients.
+    pub fn simplify(self) -> Self {
         let mut num = self.0;
         let mut den = self.1;
         let g = num.gcd();
@@ -166,34 +193,62 @@ enum Op<'a> {
     Div(&'a str, &'a str),
 }
 
-pub fn star_one() -> i64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut monkeys = BTreeMap::<&str, Op>::new();
-    for line in values.lines() {
-        let (name, line) = line.split_once(": ").unwrap();
-        let op = match line.parse::<i64>() {
+/// Reads the job of every monkey by name, checking that every operand is a known monkey.
+fn parse(values: &str) -> Result<BTreeMap<&str, Op<'_>>, ParseError> {
+    let mut monkeys = BTreeMap::new();
+    let mut operands = vec![];
+    for line in parse::lines(values) {
+        let (name, job) = line.split_once(line.text, ": ")?;
+        let op = match job.parse::<i64>() {
             Ok(literal) => Op::Literal(literal),
             Err(_) => {
-                let mut tokens = line.split_whitespace();
-                let op1 = tokens.next().unwrap();
-                let sym = tokens.next().unwrap();
-                let op2 = tokens.next().unwrap();
+                let mut tokens = line.tokens();
+                tokens.expect("name")?;
+                let op1 = tokens.expect("operand")?;
+                let sym = tokens.expect("operator")?;
+                let op2 = tokens.expect("operand")?;
+                tokens.end()?;
+                operands.extend([(line, op1), (line, op2)]);
                 match sym {
                     "+" => Op::Add(op1, op2),
                     "-" => Op::Sub(op1, op2),
                     "*" => Op::Mul(op1, op2),
                     "/" => Op::Div(op1, op2),
-                    _ => unreachable!(),
+                    _ => return Err(line.error(sym, format!("unrecognised operator `{sym}`"))),
                 }
             }
         };
         monkeys.insert(name, op);
     }
 
+    for (line, operand) in operands {
+        if !monkeys.contains_key(operand) {
+            return Err(line.error(operand, format!("there is no monkey {operand}")));
+        }
+    }
+    if !monkeys.contains_key("root") {
+        return Err(ParseError::new(1, 1, "there is no monkey root"));
+    }
+
+    Ok(monkeys)
+}
+
+/// Locates an error at the operator in the job of monkey `m`, or at its number.
+fn job_error(values: &str, m: &str, message: impl Into<String>) -> ParseError {
+    let line = parse::lines(values)
+        .find(|line| {
+            line.text
+                .split_once(": ")
+                .is_some_and(|(name, _)| name == m)
+        })
+        .expect("the monkey should have a job");
+    let (_, job) = line.text.split_once(": ").unwrap();
+    line.error(job.split(' ').nth(1).unwrap_or(job), message)
+}
+
+pub fn star_one(values: &str) -> Result<i64, ParseError> {
+    let mut monkeys = parse(values)?;
+
     let mut queue = VecDeque::new();
     queue.push_back("root");
     while let Some(m) = queue.pop_front() {
@@ -209,6 +264,13 @@ pub fn star_one() -> i64 {
                     Op::Add(_, _) => *op = Op::Literal(a + b),
                     Op::Sub(_, _) => *op = Op::Literal(a - b),
                     Op::Mul(_, _) => *op = Op::Literal(a * b),
+                    Op::Div(_, _) if b == 0 => {
+                        return Err(job_error(values, m, "cannot divide by 0"));
+                    }
+                    Op::Div(_, _) if a % b != 0 => {
+                        let message = format!("{a} is not divisible by {b}");
+                        return Err(job_error(values, m, message));
+                    }
                     Op::Div(_, _) => *op = Op::Literal(a / b),
                     _ => {
                         unreachable!()
@@ -222,52 +284,26 @@ pub fn star_one() -> i64 {
         queue.push_front(m1);
     }
 
-    let Op::Literal(v) = monkeys.get("root").unwrap() else { panic!() };
-    *v
+    let Op::Literal(v) = monkeys.get("root").unwrap() else {
+        panic!()
+    };
+    Ok(*v)
 }
 
-pub fn star_two() -> i64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut monkeys = BTreeMap::<&str, Op>::new();
-    for line in values.lines() {
-        let (name, line) = line.split_once(": ").unwrap();
-        let op = match line.parse::<i64>() {
-            Ok(literal) => Op::Literal(literal),
-            Err(_) => {
-                let mut tokens = line.split_whitespace();
-                let op1 = tokens.next().unwrap();
-                let sym = tokens.next().unwrap();
-                let op2 = tokens.next().unwrap();
-                match sym {
-                    "+" => Op::Add(op1, op2),
-                    "-" => Op::Sub(op1, op2),
-                    "*" => Op::Mul(op1, op2),
-                    "/" => Op::Div(op1, op2),
-                    _ => unreachable!(),
-                }
-            }
-        };
-        monkeys.insert(name, op);
-    }
+pub fn star_two(values: &str) -> Result<i64, ParseError> {
+    let monkeys = parse(values)?;
 
     let mut expressions = BTreeMap::<&str, Rational>::new();
     expressions.insert(
         "humn",
-        Rational(Polynomial(vec![0, 1]), Polynomial(vec![1])),
+        Rational::new(Polynomial::new(vec![0, 1]), Polynomial::from(1)),
     );
 
-    let mut queue = VecDeque::new();
-    match monkeys.get("root").unwrap() {
-        Op::Add(m1, m2) | Op::Sub(m1, m2) | Op::Mul(m1, m2) | Op::Div(m1, m2) => {
-            queue.push_back(*m1);
-            queue.push_back(*m2);
-        }
-        _ => unreachable!(),
+    let (m1, m2) = match monkeys.get("root").unwrap() {
+        Op::Add(m1, m2) | Op::Sub(m1, m2) | Op::Mul(m1, m2) | Op::Div(m1, m2) => (*m1, *m2),
+        Op::Literal(_) => return Err(job_error(values, "root", "root has nothing to compare")),
     };
+    let mut queue = VecDeque::from([m1, m2]);
 
     while let Some(m) = queue.pop_front() {
         if expressions.contains_key(m) {
@@ -305,10 +341,6 @@ pub fn star_two() -> i64 {
         queue.push_front(m1);
     }
 
-    let (m1, m2) = match monkeys.get("root").unwrap() {
-        Op::Add(m1, m2) | Op::Sub(m1, m2) | Op::Mul(m1, m2) | Op::Div(m1, m2) => (m1, m2),
-        _ => unreachable!(),
-    };
     let m1 = expressions.get(m1).unwrap();
     let m2 = expressions.get(m2).unwrap();
 
@@ -316,20 +348,177 @@ pub fn star_two() -> i64 {
     let q = &m2.0 * &m1.1;
     let f = &p - &q;
 
-    (-f.0[0]) / (f.0[1])
+    // root balances once the numerator of the difference of its operands cancels out
+    match f.coefficients() {
+        &[b, a] if a != 0 && b % a == 0 => Ok(-b / a),
+        _ => {
+            let message = "root cannot be balanced by a single integer humn";
+            Err(job_error(values, "root", message))
+        }
+    }
+}
+
+/// Random jobs for about `size` monkeys. root adds up a chain of operations on humn, without
+/// divisions so that both parts stay whole, and a tree of operations on numbers which is
+/// adjusted to balance root for a hidden humn, different from the one shouted.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    let mut taken = BTreeSet::from(["root".to_string(), "humn".to_string()]);
+    let mut name = |rng: &mut StdRng| loop {
+        let name = (0..4).map(|_| rng.gen_range('a'..='z')).collect::<String>();
+        if taken.insert(name.clone()) {
+            break name;
+        }
+    };
+    let mut jobs = vec![];
+
+    // a forest of operations on numbers, one tree per link of the chain and one to balance root
+    let links = (size / 5).max(1);
+    let mut trees = vec![];
+    for _ in 0..(size.saturating_sub(3) / 2).max(links + 1) {
+        let (m, value) = (name(rng), rng.gen_range(1..=20i64));
+        jobs.push(format!("{m}: {value}"));
+        trees.push((m, value));
+    }
+    while trees.len() > links + 1 {
+        let (m2, b) = trees.swap_remove(rng.gen_range(0..trees.len()));
+        let (m1, a) = trees.swap_remove(rng.gen_range(0..trees.len()));
+        let (sign, value) = match rng.gen_range(0..4) {
+            0 if b != 0 && a % b == 0 => ('/', a / b),
+            1 if a.checked_mul(b).is_some_and(|x| x.abs() <= 1_000_000) => ('*', a * b),
+            2 => ('-', a - b),
+            _ => ('+', a + b),
+        };
+        let m = name(rng);
+        jobs.push(format!("{m}: {m1} {sign} {m2}"));
+        trees.push((m, value));
+    }
+
+    // the chain, evaluated for both the hidden and the shouted humn
+    let hidden = rng.gen_range(1..=10_000i64);
+    let shouted = loop {
+        let shouted = rng.gen_range(1..=10_000i64);
+        if shouted != hidden {
+            break shouted;
+        }
+    };
+    jobs.push(format!("humn: {shouted}"));
+    let (mut chain, mut values) = ("humn".to_string(), [hidden, shouted]);
+    let (balance, target) = trees.pop().unwrap();
+    for (m, value) in trees {
+        let small = values.iter().all(|x| {
+            x.checked_mul(value)
+                .is_some_and(|x| x.abs() <= 1_000_000_000_000)
+        });
+        let (job, next) = match rng.gen_range(0..3) {
+            0 if value != 0 && small => (format!("{chain} * {m}"), values.map(|x| x * value)),
+            1 => (format!("{m} - {chain}"), values.map(|x| value - x)),
+            2 => (format!("{chain} - {m}"), values.map(|x| x - value)),
+            _ => (format!("{m} + {chain}"), values.map(|x| value + x)),
+        };
+        chain = name(rng);
+        jobs.push(format!("{chain}: {job}"));
+        values = next;
+    }
+
+    let (adjustment, balanced) = (name(rng), name(rng));
+    let (sign, difference) = match values[0] - target {
+        difference if difference >= 0 => ('+', difference),
+        difference => ('-', -difference),
+    };
+    jobs.push(format!("{adjustment}: {difference}"));
+    jobs.push(format!("{balanced}: {balance} {sign} {adjustment}"));
+    jobs.push(format!("root: {chain} + {balanced}"));
+
+    jobs.shuffle(rng);
+    jobs.into_iter().map(|job| job + "\n").collect()
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        21
+    }
+
+    fn title(&self) -> &'static str {
+        "Monkey Math"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn generate(&self, rng: &mut StdRng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 152);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(152));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 301);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(301));
+    }
+
+    #[test]
+    fn test_polynomial_normalised() {
+        let x = Polynomial::new(vec![0, 1, 0]);
+        assert_eq!(x.coefficients(), [0, 1]);
+        assert_eq!(&x * &Polynomial::from(0), Polynomial::from(0));
+        assert_eq!(&x - &x, Polynomial::new(vec![]));
+        assert_eq!((&x * &Polynomial::from(0)).deg(), 0);
+    }
+
+    #[test]
+    fn test_star_two_unbalanced() {
+        let values = "root: aaaa + bbbb\naaaa: 1\nbbbb: 2\nhumn: 5\n";
+        let message = "root cannot be balanced by a single integer humn";
+        assert_eq!(star_two(values), Err(ParseError::new(1, 12, message)));
+    }
+
+    #[test]
+    fn test_star_one_division() {
+        let values = "root: aaaa / bbbb\naaaa: 7\nbbbb: 0\n";
+        let error = ParseError::new(1, 12, "cannot divide by 0");
+        assert_eq!(star_one(values), Err(error));
+        let values = "root: aaaa / bbbb\naaaa: 7\nbbbb: 2\n";
+        let error = ParseError::new(1, 12, "7 is not divisible by 2");
+        assert_eq!(star_one(values), Err(error));
+    }
+
+    #[test]
+    fn test_generate() {
+        testing::check(
+            50,
+            |rng| generate(rng, 60),
+            |values| {
+                let humn = star_two(values).map_err(|err| err.to_string())?;
+                // shouting the answer balances root, which then finds no difference
+                let balanced = values
+                    .lines()
+                    .map(|line| match line.split_once(": ") {
+                        Some(("humn", _)) => format!("humn: {humn}\n"),
+                        Some(("root", job)) => format!("root: {}\n", job.replace('+', "-")),
+                        _ => format!("{line}\n"),
+                    })
+                    .collect::<String>();
+                match star_one(&balanced) {
+                    Ok(0) => star_one(values).map(|_| ()).map_err(|err| err.to_string()),
+                    difference => Err(format!("root is off by {difference:?} for humn {humn}")),
+                }
+            },
+        );
     }
 }
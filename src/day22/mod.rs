@@ -1,8 +1,13 @@
+use crate::grid::{self, Dense, Grid, Point};
+use crate::parse::{self, ParseError};
+use crate::render::{Colour, Glyph, Painter, Recorder};
+use crate::solution::{Answer, Solution};
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 
+/// An integer vector of the 3D space the cube is folded in.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
-struct V3([isize; 3]);
+pub struct V3(pub [isize; 3]);
 
 impl std::ops::Deref for V3 {
     type Target = [isize; 3];
@@ -20,8 +25,9 @@ impl std::ops::Mul<V3> for V3 {
     }
 }
 
+/// An integer 3x3 matrix, such as a rotation of the cube, stored as its columns.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
-struct M3([V3; 3]);
+pub struct M3(pub [V3; 3]);
 
 impl std::ops::Deref for M3 {
     type Target = [V3; 3];
@@ -32,7 +38,7 @@ impl std::ops::Deref for M3 {
 }
 
 impl M3 {
-    fn transposed(self) -> Self {
+    pub fn transposed(self) -> Self {
         M3([
             V3([self[0][0], self[1][0], self[2][0]]),
             V3([self[0][1], self[1][1], self[2][1]]),
@@ -40,7 +46,8 @@ impl M3 {
         ])
     }
 
-    fn complement(self, row: usize, column: usize) -> M2 {
+    /// The matrix left once the given row and column are removed.
+    pub fn complement(self, row: usize, column: usize) -> M2 {
         let M3(columns) = self;
         let mut new_columns = columns
             .into_iter()
@@ -77,8 +84,9 @@ impl std::ops::Mul<M3> for M3 {
     }
 }
 
+/// An integer vector of the plane of a face of the cube.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
-struct V2([isize; 2]);
+pub struct V2(pub [isize; 2]);
 
 impl std::ops::Deref for V2 {
     type Target = [isize; 2];
@@ -96,8 +104,9 @@ impl std::ops::Mul<V2> for V2 {
     }
 }
 
+/// An integer 2x2 matrix, stored as its columns.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
-struct M2([V2; 2]);
+pub struct M2(pub [V2; 2]);
 
 impl std::ops::Deref for M2 {
     type Target = [V2; 2];
@@ -108,7 +117,7 @@ impl std::ops::Deref for M2 {
 }
 
 impl M2 {
-    fn transposed(self) -> Self {
+    pub fn transposed(self) -> Self {
         M2([V2([self[0][0], self[1][0]]), V2([self[0][1], self[1][1]])])
     }
 }
@@ -137,6 +146,7 @@ enum Cell {
     Wall,
 }
 
+#[derive(Clone, Copy)]
 enum Direction {
     Left,
     Right,
@@ -144,76 +154,105 @@ enum Direction {
     Down,
 }
 
-pub fn star_one() -> isize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut map = BTreeMap::new();
-    let lines = values.lines();
-    let mut lines = lines.rev();
-    let code = lines.next().unwrap();
-    let lines = lines.rev();
-    let mut start = (false, 0, 0);
-    let mut max_row = 0;
-    let mut max_column = 0;
-    for (row, line) in lines.enumerate() {
-        for (column, c) in line.chars().enumerate() {
-            match c {
-                '.' => {
-                    if !start.0 {
-                        start = (true, row as isize, column as isize);
-                    }
-                    map.insert((row as isize, column as isize), Cell::Floor);
-                    if row as isize > max_row {
-                        max_row = row as isize;
-                    }
-                    if column as isize > max_column {
-                        max_column = column as isize;
-                    }
-                }
-                '#' => {
-                    map.insert((row as isize, column as isize), Cell::Wall);
-                    if row as isize > max_row {
-                        max_row = row as isize;
-                    }
-                    if column as isize > max_column {
-                        max_column = column as isize;
-                    }
-                }
-                _ => {}
-            }
+impl Direction {
+    fn arrow(self) -> char {
+        match self {
+            Direction::Left => '<',
+            Direction::Right => '>',
+            Direction::Up => '^',
+            Direction::Down => 'v',
         }
     }
+}
 
-    let mut position = (start.1, start.2);
-    let mut direction = Direction::Right;
+/// The board and the path to follow on it.
+struct Notes<'a> {
+    map: Dense<Cell>,
+    start: Point,
+    max_row: isize,
+    max_column: isize,
+    /// How many steps to take, then where to turn: the last instruction has no turn.
+    path: Vec<(usize, Option<&'a str>)>,
+}
 
-    let mut code_index = 0;
+/// Reads the board, a blank line, then the path.
+fn parse(values: &str) -> Result<Notes<'_>, ParseError> {
+    let mut lines = parse::lines(values);
+    let board = lines.by_ref().take_while(|line| !line.text.is_empty());
+    let map: Dense<Cell> = grid::from_lines(board, |_, c| match c {
+        '.' => Ok(Some(Cell::Floor)),
+        '#' => Ok(Some(Cell::Wall)),
+        ' ' => Ok(None),
+        _ => Err("tiles must be `.`, `#` or ` `".to_string()),
+    })?;
+    let (start, _) = map
+        .iter()
+        .find(|(_, cell)| matches!(cell, Cell::Floor))
+        .ok_or_else(|| ParseError::new(1, 1, "the board has no open tile"))?;
+    let bounds = map.bounds().unwrap();
+
+    let line = lines.expect("path")?;
+    let mut path = vec![];
+    let mut rest = line.text;
     loop {
-        let mut rotation = None;
-        let steps;
-
-        let mut token_end = code_index + 1;
-        while token_end < code.len() {
-            let rot = &code[token_end..=token_end];
-            if rot == "R" || rot == "L" {
-                rotation = Some(rot);
-                break;
-            }
-            token_end += 1;
+        let token_end = rest.find(['L', 'R']).unwrap_or(rest.len());
+        let steps = line.parse::<usize>(&rest[..token_end], "number of steps")?;
+        if token_end == rest.len() {
+            path.push((steps, None));
+            break;
         }
-        if token_end == code.len() {
-            steps = code[code_index..=token_end - 1].parse::<usize>().unwrap();
-        } else {
-            steps = code[code_index..token_end].parse::<usize>().unwrap();
+        path.push((steps, Some(&rest[token_end..=token_end])));
+        rest = &rest[token_end + 1..];
+    }
+
+    Ok(Notes {
+        max_row: bounds.bottom - 1,
+        max_column: bounds.right - 1,
+        map,
+        start,
+        path,
+    })
+}
+
+/// Draws the board along with the tiles walked on, the current one standing out.
+fn draw(painter: &Painter, map: &Dense<Cell>, trace: &Dense<Direction>, position: Point) -> String {
+    painter.frame(map.area(), |point| {
+        match (map.get(point), trace.get(point)) {
+            (_, Some(direction)) if point == position => {
+                Glyph::coloured(direction.arrow(), Colour::Red)
+            }
+            (_, Some(direction)) => Glyph::coloured(direction.arrow(), Colour::Cyan),
+            (Some(Cell::Wall), _) => Glyph::coloured('#', Colour::Grey),
+            (Some(Cell::Floor), _) => '.'.into(),
+            (None, _) => ' '.into(),
         }
-        code_index = token_end + 1;
+    })
+}
+
+pub fn star_one(values: &str) -> Result<isize, ParseError> {
+    walk_flat(values, &mut Recorder::off())
+}
 
+/// Follows the path on the flat board, drawing it after each instruction.
+fn walk_flat(values: &str, recorder: &mut Recorder) -> Result<isize, ParseError> {
+    let Notes {
+        map,
+        start,
+        max_row,
+        max_column,
+        path,
+    } = parse(values)?;
+
+    let mut position = start;
+    let mut direction = Direction::Right;
+    let mut trace = Dense::new(map.area());
+    trace.insert(position, direction);
+    recorder.step(0, |painter| draw(painter, &map, &trace, position));
+
+    for (instruction, &(steps, rotation)) in path.iter().enumerate() {
         'walk: for _ in 0..steps {
             match direction {
-                Direction::Left => match map.get(&(position.0, position.1 - 1)) {
+                Direction::Left => match map.get((position.0, position.1 - 1)) {
                     Some(Cell::Floor) => {
                         position = (position.0, position.1 - 1);
                     }
@@ -222,7 +261,7 @@ pub fn star_one() -> isize {
                     }
                     None => {
                         'wrap: for c in (isize::MIN..=max_column).rev() {
-                            match map.get(&(position.0, c)) {
+                            match map.get((position.0, c)) {
                                 Some(Cell::Floor) => {
                                     position = (position.0, c);
                                     break 'wrap;
@@ -235,7 +274,7 @@ pub fn star_one() -> isize {
                         }
                     }
                 },
-                Direction::Right => match map.get(&(position.0, position.1 + 1)) {
+                Direction::Right => match map.get((position.0, position.1 + 1)) {
                     Some(Cell::Floor) => {
                         position = (position.0, position.1 + 1);
                     }
@@ -244,7 +283,7 @@ pub fn star_one() -> isize {
                     }
                     None => {
                         'wrap: for c in 0.. {
-                            match map.get(&(position.0, c)) {
+                            match map.get((position.0, c)) {
                                 Some(Cell::Floor) => {
                                     position = (position.0, c);
                                     break 'wrap;
@@ -257,7 +296,7 @@ pub fn star_one() -> isize {
                         }
                     }
                 },
-                Direction::Up => match map.get(&(position.0 - 1, position.1)) {
+                Direction::Up => match map.get((position.0 - 1, position.1)) {
                     Some(Cell::Floor) => {
                         position = (position.0 - 1, position.1);
                     }
@@ -266,7 +305,7 @@ pub fn star_one() -> isize {
                     }
                     None => {
                         'wrap: for r in (isize::MIN..=max_row).rev() {
-                            match map.get(&(r, position.1)) {
+                            match map.get((r, position.1)) {
                                 Some(Cell::Floor) => {
                                     position = (r, position.1);
                                     break 'wrap;
@@ -279,7 +318,7 @@ pub fn star_one() -> isize {
                         }
                     }
                 },
-                Direction::Down => match map.get(&(position.0 + 1, position.1)) {
+                Direction::Down => match map.get((position.0 + 1, position.1)) {
                     Some(Cell::Floor) => {
                         position = (position.0 + 1, position.1);
                     }
@@ -288,7 +327,7 @@ pub fn star_one() -> isize {
                     }
                     None => {
                         'wrap: for r in 0.. {
-                            match map.get(&(r, position.1)) {
+                            match map.get((r, position.1)) {
                                 Some(Cell::Floor) => {
                                     position = (r, position.1);
                                     break 'wrap;
@@ -302,6 +341,7 @@ pub fn star_one() -> isize {
                     }
                 },
             }
+            trace.insert(position, direction);
         }
 
         if let Some(rotation) = rotation {
@@ -315,67 +355,46 @@ pub fn star_one() -> isize {
                 ("R", Direction::Up) => Direction::Right,
                 ("R", Direction::Down) => Direction::Left,
                 _ => unreachable!(),
-            }
-        } else {
-            break;
+            };
+            trace.insert(position, direction);
         }
+        recorder.step(instruction + 1, |painter| {
+            draw(painter, &map, &trace, position)
+        });
     }
 
-    1000 * (position.0 + 1)
+    recorder.last(path.len(), |painter| draw(painter, &map, &trace, position));
+    Ok(1000 * (position.0 + 1)
         + 4 * (position.1 + 1)
         + match direction {
             Direction::Left => 2,
             Direction::Right => 0,
             Direction::Up => 3,
             Direction::Down => 1,
-        }
+        })
 }
 
-pub fn star_two() -> isize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(test)]
-    let face_size = 4;
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-    #[cfg(not(test))]
-    let face_size = 50;
-
-    let mut map = BTreeMap::new();
-    let lines = values.lines();
-    let mut lines = lines.rev();
-    let code = lines.next().unwrap();
-    let lines = lines.rev();
-    let mut start = (false, 0, 0);
-    let mut max_row = 0;
-    let mut max_column = 0;
-    for (row, line) in lines.enumerate() {
-        for (column, c) in line.chars().enumerate() {
-            match c {
-                '.' => {
-                    if !start.0 {
-                        start = (true, row as isize, column as isize);
-                    }
-                    map.insert((row as isize, column as isize), Cell::Floor);
-                    if row as isize > max_row {
-                        max_row = row as isize;
-                    }
-                    if column as isize > max_column {
-                        max_column = column as isize;
-                    }
-                }
-                '#' => {
-                    map.insert((row as isize, column as isize), Cell::Wall);
-                    if row as isize > max_row {
-                        max_row = row as isize;
-                    }
-                    if column as isize > max_column {
-                        max_column = column as isize;
-                    }
-                }
-                _ => {}
-            }
-        }
+pub fn star_two(values: &str) -> Result<isize, ParseError> {
+    walk_cube(values, &mut Recorder::off())
+}
+
+/// Follows the path on the folded cube, drawing the board after each instruction.
+fn walk_cube(values: &str, recorder: &mut Recorder) -> Result<isize, ParseError> {
+    let Notes {
+        map,
+        start,
+        max_row,
+        max_column,
+        path,
+    } = parse(values)?;
+    let not_a_net = |(row, column): Point| {
+        let message = "the board does not fold into a cube";
+        ParseError::new(row as usize + 1, column as usize + 1, message)
+    };
+    // the net is made of six square faces, so their size follows from the cell count
+    let face_size = (1..).find(|f| 6 * f * f >= map.len() as isize).unwrap();
+    if 6 * face_size * face_size != map.len() as isize {
+        return Err(not_a_net((0, 0)));
     }
     let faces_across = (max_column + 1) / face_size;
     let faces_down = (max_row + 1) / face_size;
@@ -384,13 +403,29 @@ pub fn star_two() -> isize {
     for r in 0..faces_down {
         for c in 0..faces_across {
             let coords = (face_size * r, face_size * c);
-            if map.get(&coords).is_some() {
+            if map.contains(coords) {
                 corners.insert(coords);
             }
         }
     }
+    // with as many tiles as six whole faces, the board is made of them only
+    for &(row, column) in &corners {
+        for r in row..row + face_size {
+            for c in column..column + face_size {
+                if !map.contains((r, c)) {
+                    return Err(not_a_net((r, c)));
+                }
+            }
+        }
+    }
+    if let Some((point, _)) = map.iter().find(|&((row, column), _)| {
+        let corner = (row / face_size * face_size, column / face_size * face_size);
+        !corners.contains(&corner)
+    }) {
+        return Err(not_a_net(point));
+    }
     let mut cube = BTreeMap::<(isize, isize), M3>::new();
-    let first_face = corners.iter().next().unwrap().clone();
+    let first_face = *corners.iter().next().unwrap();
     cube.insert(
         first_face,
         M3([V3([0, -1, 0]), V3([1, 0, 0]), V3([0, 0, 1])]),
@@ -429,37 +464,29 @@ pub fn star_two() -> isize {
                 break;
             }
         }
-        let x = x.unwrap();
+        let Some(x) = x else {
+            // the faces left are not connected to those folded so far
+            let apart = corners.iter().find(|c| !cube.contains_key(c)).unwrap();
+            return Err(not_a_net(*apart));
+        };
         cube.insert(x.0, x.1);
     }
+    // faces folding onto the same side of the cube overlap
+    let sides = cube.values().map(|m| m[2]).collect::<Vec<_>>();
+    if let Some(i) = (1..sides.len()).find(|&i| sides[..i].contains(&sides[i])) {
+        return Err(not_a_net(*cube.keys().nth(i).unwrap()));
+    }
 
-    let mut position = (start.1, start.2);
+    let mut position = start;
     let mut direction = Direction::Right;
+    let mut trace = Dense::new(map.area());
+    trace.insert(position, direction);
+    recorder.step(0, |painter| draw(painter, &map, &trace, position));
 
-    let mut code_index = 0;
-    loop {
-        let mut rotation = None;
-        let steps;
-
-        let mut token_end = code_index + 1;
-        while token_end < code.len() {
-            let rot = &code[token_end..=token_end];
-            if rot == "R" || rot == "L" {
-                rotation = Some(rot);
-                break;
-            }
-            token_end += 1;
-        }
-        if token_end == code.len() {
-            steps = code[code_index..=token_end - 1].parse::<usize>().unwrap();
-        } else {
-            steps = code[code_index..token_end].parse::<usize>().unwrap();
-        }
-        code_index = token_end + 1;
-
+    for (instruction, &(steps, rotation)) in path.iter().enumerate() {
         'walk: for _ in 0..steps {
             match direction {
-                Direction::Left => match map.get(&(position.0, position.1 - 1)) {
+                Direction::Left => match map.get((position.0, position.1 - 1)) {
                     Some(Cell::Floor) => {
                         position = (position.0, position.1 - 1);
                     }
@@ -491,7 +518,7 @@ pub fn star_two() -> isize {
                             corner_there.0 + d_there.0 / 2,
                             corner_there.1 + d_there.1 / 2,
                         );
-                        match map.get(&position_there) {
+                        match map.get(position_there) {
                             Some(Cell::Floor) => {
                                 position = position_there;
                                 direction = match new_direction.0 {
@@ -509,7 +536,7 @@ pub fn star_two() -> isize {
                         }
                     }
                 },
-                Direction::Right => match map.get(&(position.0, position.1 + 1)) {
+                Direction::Right => match map.get((position.0, position.1 + 1)) {
                     Some(Cell::Floor) => {
                         position = (position.0, position.1 + 1);
                     }
@@ -541,7 +568,7 @@ pub fn star_two() -> isize {
                             corner_there.0 + d_there.0 / 2,
                             corner_there.1 + d_there.1 / 2,
                         );
-                        match map.get(&position_there) {
+                        match map.get(position_there) {
                             Some(Cell::Floor) => {
                                 position = position_there;
                                 direction = match new_direction.0 {
@@ -559,7 +586,7 @@ pub fn star_two() -> isize {
                         }
                     }
                 },
-                Direction::Up => match map.get(&(position.0 - 1, position.1)) {
+                Direction::Up => match map.get((position.0 - 1, position.1)) {
                     Some(Cell::Floor) => {
                         position = (position.0 - 1, position.1);
                     }
@@ -591,7 +618,7 @@ pub fn star_two() -> isize {
                             corner_there.0 + d_there.0 / 2,
                             corner_there.1 + d_there.1 / 2,
                         );
-                        match map.get(&position_there) {
+                        match map.get(position_there) {
                             Some(Cell::Floor) => {
                                 position = position_there;
                                 direction = match new_direction.0 {
@@ -609,7 +636,7 @@ pub fn star_two() -> isize {
                         }
                     }
                 },
-                Direction::Down => match map.get(&(position.0 + 1, position.1)) {
+                Direction::Down => match map.get((position.0 + 1, position.1)) {
                     Some(Cell::Floor) => {
                         position = (position.0 + 1, position.1);
                     }
@@ -641,7 +668,7 @@ pub fn star_two() -> isize {
                             corner_there.0 + d_there.0 / 2,
                             corner_there.1 + d_there.1 / 2,
                         );
-                        match map.get(&position_there) {
+                        match map.get(position_there) {
                             Some(Cell::Floor) => {
                                 position = position_there;
                                 direction = match new_direction.0 {
@@ -660,6 +687,7 @@ pub fn star_two() -> isize {
                     }
                 },
             }
+            trace.insert(position, direction);
         }
 
         if let Some(rotation) = rotation {
@@ -673,20 +701,60 @@ pub fn star_two() -> isize {
                 ("R", Direction::Up) => Direction::Right,
                 ("R", Direction::Down) => Direction::Left,
                 _ => unreachable!(),
-            }
-        } else {
-            break;
+            };
+            trace.insert(position, direction);
         }
+        recorder.step(instruction + 1, |painter| {
+            draw(painter, &map, &trace, position)
+        });
     }
 
-    1000 * (position.0 + 1)
+    recorder.last(path.len(), |painter| draw(painter, &map, &trace, position));
+    Ok(1000 * (position.0 + 1)
         + 4 * (position.1 + 1)
         + match direction {
             Direction::Left => 2,
             Direction::Right => 0,
             Direction::Up => 3,
             Direction::Down => 1,
+        })
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        22
+    }
+
+    fn title(&self) -> &'static str {
+        "Monkey Map"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn renders(&self) -> bool {
+        true
+    }
+
+    fn render(
+        &self,
+        part: u8,
+        input: &str,
+        recorder: &mut Recorder,
+    ) -> Option<Result<Answer, ParseError>> {
+        match part {
+            1 => Some(walk_flat(input, recorder).map(Answer::from)),
+            2 => Some(walk_cube(input, recorder).map(Answer::from)),
+            _ => None,
         }
+    }
 }
 
 #[cfg(test)]
@@ -695,11 +763,30 @@ mod tests {
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 6032);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(6032));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 5031);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(5031));
+    }
+
+    #[test]
+    fn test_not_a_net() {
+        let error = |line, column| {
+            let message = "the board does not fold into a cube";
+            Err(ParseError::new(line, column, message))
+        };
+        assert_eq!(star_two("..\n\n10"), error(1, 1));
+        // six faces in a row, the fifth folding onto the first
+        assert_eq!(star_two("......\n\n1"), error(1, 5));
+        // two pieces
+        assert_eq!(star_two(".. ...\n.\n\n1"), error(1, 4));
+        // a face missing a tile, another with a single one
+        let values = "........\n........\n.....\n...\n\n1";
+        assert_eq!(star_two(values), error(4, 4));
+        // five faces, and tiles beside them
+        let values = "........\n........\n.. .\n.. . . .\n\n1";
+        assert_eq!(star_two(values), error(3, 4));
     }
 }
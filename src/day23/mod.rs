@@ -1,5 +1,8 @@
+use crate::grid::{self, Grid, Point, Sparse};
+use crate::parse::{self, ParseError};
+use crate::render::{Colour, Glyph, Painter, Recorder};
+use crate::solution::{Answer, Solution};
 use std::collections::BTreeMap;
-use std::collections::BTreeSet;
 
 #[derive(Clone, Copy)]
 enum Direction {
@@ -10,7 +13,7 @@ enum Direction {
 }
 
 impl Direction {
-    fn offset(&self, (r, c): (isize, isize)) -> [(isize, isize); 3] {
+    fn offset(&self, (r, c): Point) -> [Point; 3] {
         match self {
             Direction::North => [(r - 1, c - 1), (r - 1, c), (r - 1, c + 1)],
             Direction::South => [(r + 1, c - 1), (r + 1, c), (r + 1, c + 1)],
@@ -29,41 +32,49 @@ impl Direction {
     }
 }
 
-pub fn star_one() -> isize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut map = BTreeSet::<(isize, isize)>::new();
-    for (row, line) in values.lines().enumerate() {
-        for (column, c) in line.chars().enumerate() {
-            if c == '#' {
-                map.insert((row as isize, column as isize));
-            }
-        }
+/// Reads the position of every elf, drawn as `#` among `.`, checking that there is one.
+fn parse(values: &str) -> Result<Sparse<()>, ParseError> {
+    let map: Sparse<()> = grid::parse(values, |_, c| match c {
+        '#' => Ok(Some(())),
+        '.' => Ok(None),
+        _ => Err("tiles must be `#` or `.`".to_string()),
+    })?;
+    if map.is_empty() {
+        let rows = parse::lines(values).count();
+        return Err(ParseError::new(rows + 1, 1, "the grove has no elf"));
     }
+    Ok(map)
+}
+
+/// Draws the elves within the rectangle containing them.
+fn draw(painter: &Painter, map: &Sparse<()>) -> String {
+    painter.frame(map.bounds().unwrap(), |elf| match map.get(elf) {
+        Some(()) => Glyph::coloured('#', Colour::Green),
+        None => '.'.into(),
+    })
+}
+
+pub fn star_one(values: &str) -> Result<isize, ParseError> {
+    spread(values, &mut Recorder::off())
+}
+
+/// Moves the elves for ten rounds, drawing them after each round.
+fn spread(values: &str, recorder: &mut Recorder) -> Result<isize, ParseError> {
+    let mut map = parse(values)?;
+    recorder.step(0, |painter| draw(painter, &map));
 
     let mut first_direction = Direction::North;
-    for _ in 0..10 {
-        let mut proposals = BTreeMap::<(isize, isize), Vec<(isize, isize)>>::new();
-        for &elf in &map {
-            if !map.contains(&(elf.0 - 1, elf.1 - 1))
-                && !map.contains(&(elf.0 - 1, elf.1))
-                && !map.contains(&(elf.0 - 1, elf.1 + 1))
-                && !map.contains(&(elf.0, elf.1 - 1))
-                && !map.contains(&(elf.0, elf.1 + 1))
-                && !map.contains(&(elf.0 + 1, elf.1 - 1))
-                && !map.contains(&(elf.0 + 1, elf.1))
-                && !map.contains(&(elf.0 + 1, elf.1 + 1))
-            {
+    for round in 1..=10 {
+        let mut proposals = BTreeMap::<Point, Vec<Point>>::new();
+        for (elf, _) in map.iter() {
+            if map.neighbours8(elf).next().is_none() {
                 continue;
             }
 
             let mut proposed = false;
             for _ in 0..4 {
                 let neighbours = first_direction.offset(elf);
-                if !proposed && !neighbours.iter().any(|nei| map.contains(nei)) {
+                if !proposed && !neighbours.iter().any(|&nei| map.contains(nei)) {
                     proposals.entry(neighbours[1]).or_default().push(elf);
                     proposed = true;
                 }
@@ -73,65 +84,40 @@ pub fn star_one() -> isize {
         first_direction = first_direction.shift();
 
         for (target, candidates) in proposals {
-            if let &[elf] = &candidates.as_slice() {
+            if let &[elf] = candidates.as_slice() {
                 map.remove(elf);
-                map.insert(target);
+                map.insert(target, ());
             }
         }
+        recorder.step(round, |painter| draw(painter, &map));
     }
 
-    let mut top_left = (isize::MAX, isize::MAX);
-    let mut bottom_right = (isize::MIN, isize::MIN);
-
-    for elf in &map {
-        top_left = (isize::min(top_left.0, elf.0), isize::min(top_left.1, elf.1));
-        bottom_right = (
-            isize::max(bottom_right.0, elf.0),
-            isize::max(bottom_right.1, elf.1),
-        );
-    }
-
-    let height = bottom_right.0 - top_left.0 + 1;
-    let width = bottom_right.1 - top_left.1 + 1;
+    recorder.last(10, |painter| draw(painter, &map));
+    let bounds = map.bounds().unwrap();
+    Ok((bounds.area() - map.len()) as isize)
+}
 
-    width * height - map.len() as isize
+pub fn star_two(values: &str) -> Result<isize, ParseError> {
+    settle(values, &mut Recorder::off())
 }
 
-pub fn star_two() -> isize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut map = BTreeSet::<(isize, isize)>::new();
-    for (row, line) in values.lines().enumerate() {
-        for (column, c) in line.chars().enumerate() {
-            if c == '#' {
-                map.insert((row as isize, column as isize));
-            }
-        }
-    }
+/// Moves the elves until none of them moves, drawing them after each round.
+fn settle(values: &str, recorder: &mut Recorder) -> Result<isize, ParseError> {
+    let mut map = parse(values)?;
+    recorder.step(0, |painter| draw(painter, &map));
 
     let mut first_direction = Direction::North;
     for first_unmoved_round in 1.. {
-        let mut proposals = BTreeMap::<(isize, isize), Vec<(isize, isize)>>::new();
-        for &elf in &map {
-            if !map.contains(&(elf.0 - 1, elf.1 - 1))
-                && !map.contains(&(elf.0 - 1, elf.1))
-                && !map.contains(&(elf.0 - 1, elf.1 + 1))
-                && !map.contains(&(elf.0, elf.1 - 1))
-                && !map.contains(&(elf.0, elf.1 + 1))
-                && !map.contains(&(elf.0 + 1, elf.1 - 1))
-                && !map.contains(&(elf.0 + 1, elf.1))
-                && !map.contains(&(elf.0 + 1, elf.1 + 1))
-            {
+        let mut proposals = BTreeMap::<Point, Vec<Point>>::new();
+        for (elf, _) in map.iter() {
+            if map.neighbours8(elf).next().is_none() {
                 continue;
             }
 
             let mut proposed = false;
             for _ in 0..4 {
                 let neighbours = first_direction.offset(elf);
-                if !proposed && !neighbours.iter().any(|nei| map.contains(nei)) {
+                if !proposed && !neighbours.iter().any(|&nei| map.contains(nei)) {
                     proposals.entry(neighbours[1]).or_default().push(elf);
                     proposed = true;
                 }
@@ -142,30 +128,69 @@ pub fn star_two() -> isize {
 
         let mut moved = false;
         for (target, candidates) in proposals {
-            if let &[elf] = &candidates.as_slice() {
+            if let &[elf] = candidates.as_slice() {
                 map.remove(elf);
-                map.insert(target);
+                map.insert(target, ());
                 moved = true;
             }
         }
         if !moved {
-            return first_unmoved_round;
+            recorder.last(first_unmoved_round as usize, |painter| draw(painter, &map));
+            return Ok(first_unmoved_round);
         }
+        recorder.step(first_unmoved_round as usize, |painter| draw(painter, &map));
     }
     unreachable!()
 }
 
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        23
+    }
+
+    fn title(&self) -> &'static str {
+        "Unstable Diffusion"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn renders(&self) -> bool {
+        true
+    }
+
+    fn render(
+        &self,
+        part: u8,
+        input: &str,
+        recorder: &mut Recorder,
+    ) -> Option<Result<Answer, ParseError>> {
+        match part {
+            1 => Some(spread(input, recorder).map(Answer::from)),
+            2 => Some(settle(input, recorder).map(Answer::from)),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 110);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(110));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 20);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(20));
     }
 }
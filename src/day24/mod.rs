@@ -1,7 +1,15 @@
+use crate::grid::{self, Dense, Grid};
+use crate::parse::{self, ParseError};
+use crate::render::{Colour, Glyph, Painter, Recorder};
+use crate::solution::{Answer, Solution};
+use rand::rngs::StdRng;
+use rand::Rng;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::collections::BinaryHeap;
+use std::collections::HashMap;
 use std::collections::HashSet;
+use std::hash::Hash;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 enum Cell {
@@ -11,12 +19,26 @@ enum Cell {
 }
 
 struct Atlas {
-    by_time: BTreeMap<usize, BTreeMap<(isize, isize), Cell>>,
+    by_time: BTreeMap<usize, Dense<Cell>>,
     max_row: isize,
     max_column: isize,
+    /// After how many minutes the blizzards are back where they started.
+    cycle: usize,
 }
 impl Atlas {
-    fn get(&mut self, time: usize) -> &BTreeMap<(isize, isize), Cell> {
+    fn new(map: Dense<Cell>) -> Self {
+        let (max_row, max_column) = map.iter().last().unwrap().0;
+        let (height, width) = (max_row as usize - 1, max_column as usize - 1);
+        Self {
+            by_time: BTreeMap::from([(0, map)]),
+            max_row,
+            max_column,
+            cycle: height * width / gcd::binary_usize(height, width),
+        }
+    }
+
+    fn get(&mut self, time: usize) -> &Dense<Cell> {
+        let time = time % self.cycle;
         let (&last_time, last_map) = self.by_time.last_key_value().unwrap();
         if last_time < time {
             let mut new_map = last_map
@@ -27,10 +49,10 @@ impl Atlas {
                         Cell::Blizzards(_) => Cell::Ground,
                         Cell::Ground => Cell::Ground,
                     };
-                    (*position, new_cell)
+                    (position, new_cell)
                 })
-                .collect::<BTreeMap<_, _>>();
-            for (coords, cell) in last_map {
+                .collect::<Dense<_>>();
+            for (coords, cell) in last_map.iter() {
                 if let Cell::Blizzards(blizzards) = cell {
                     for blizzard in blizzards {
                         let mut new_coords = (coords.0 + blizzard.0, coords.1 + blizzard.1);
@@ -46,7 +68,7 @@ impl Atlas {
                         if new_coords.1 == self.max_column {
                             new_coords.1 = 1;
                         }
-                        match new_map.get_mut(&new_coords) {
+                        match new_map.get_mut(new_coords) {
                             None => unreachable!(),
                             Some(new_cell) => match new_cell {
                                 Cell::Wall => unreachable!(),
@@ -69,46 +91,101 @@ impl Atlas {
     }
 }
 
-pub fn star_one() -> usize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut map = BTreeMap::<(isize, isize), Cell>::new();
-    for (r, line) in values.lines().enumerate() {
-        for (c, x) in line.chars().enumerate() {
-            map.insert(
-                (r as isize, c as isize),
-                match x {
-                    '#' => Cell::Wall,
-                    '.' => Cell::Ground,
-                    '>' => Cell::Blizzards(vec![(0, 1)]),
-                    '<' => Cell::Blizzards(vec![(0, -1)]),
-                    '^' => Cell::Blizzards(vec![(-1, 0)]),
-                    'v' => Cell::Blizzards(vec![(1, 0)]),
-                    _ => unreachable!(),
-                },
-            );
+/// Draws the valley at the given minute, each blizzard as its direction or, where several
+/// share a tile, as their count, along with the expedition.
+fn draw(painter: &Painter, map: &Dense<Cell>, expedition: (isize, isize)) -> String {
+    painter.frame(map.area(), |point| match map.get(point) {
+        _ if point == expedition => Glyph::coloured('E', Colour::Yellow),
+        Some(Cell::Wall) => Glyph::coloured('#', Colour::Grey),
+        Some(Cell::Blizzards(blizzards)) => {
+            let c = match blizzards.as_slice() {
+                [(0, 1)] => '>',
+                [(0, -1)] => '<',
+                [(-1, 0)] => '^',
+                [(1, 0)] => 'v',
+                _ => char::from_digit(blizzards.len().min(9) as u32, 10).unwrap(),
+            };
+            Glyph::coloured(c, Colour::Cyan)
         }
+        Some(Cell::Ground) | None => '.'.into(),
+    })
+}
+
+/// Walks back from `last` to the starting state, returning the position of the expedition at
+/// each minute.
+fn trace<S: Copy + Eq + Hash>(
+    parents: &HashMap<S, S>,
+    last: S,
+    position: impl Fn(&S) -> (isize, isize),
+) -> Vec<(isize, isize)> {
+    let mut path = vec![position(&last)];
+    let mut state = last;
+    while let Some(parent) = parents.get(&state) {
+        path.push(position(parent));
+        state = *parent;
+    }
+    path.reverse();
+    path
+}
+
+/// Draws the valley and the expedition at each minute of the crossing, once its path is known.
+fn replay(atlas: &mut Atlas, path: &[(isize, isize)], recorder: &mut Recorder) {
+    let minutes = path.len() - 1;
+    for (time, &expedition) in path.iter().enumerate().take(minutes) {
+        recorder.step(time, |painter| draw(painter, atlas.get(time), expedition));
+    }
+    recorder.last(minutes, |painter| {
+        draw(painter, atlas.get(minutes), path[minutes])
+    });
+}
+
+/// Reads the valley, which must have an opening to enter and another to leave.
+fn parse(values: &str) -> Result<Dense<Cell>, ParseError> {
+    let map: Dense<Cell> = grid::parse(values, |_, x| {
+        Ok(Some(match x {
+            '#' => Cell::Wall,
+            '.' => Cell::Ground,
+            '>' => Cell::Blizzards(vec![(0, 1)]),
+            '<' => Cell::Blizzards(vec![(0, -1)]),
+            '^' => Cell::Blizzards(vec![(-1, 0)]),
+            'v' => Cell::Blizzards(vec![(1, 0)]),
+            _ => return Err("tiles must be `#`, `.`, `>`, `<`, `^` or `v`".to_string()),
+        }))
+    })?;
+    let openings = map
+        .iter()
+        .filter(|(_, cell)| matches!(cell, Cell::Ground))
+        .count();
+    if openings < 2 {
+        let rows = parse::lines(values).count();
+        return Err(ParseError::new(
+            rows + 1,
+            1,
+            "the valley has no entrance and exit",
+        ));
     }
+    Ok(map)
+}
+
+pub fn star_one(values: &str) -> Result<usize, ParseError> {
+    cross(values, &mut Recorder::off())
+}
+
+/// Crosses the valley once, then draws the expedition and the blizzards over the course of
+/// the crossing.
+fn cross(values: &str, recorder: &mut Recorder) -> Result<usize, ParseError> {
+    let map = parse(values)?;
 
-    let (&start, _) = map
+    let (start, _) = map
         .iter()
         .find(|(_, cell)| matches!(cell, Cell::Ground))
         .unwrap();
-    let (&end, _) = map
+    let (end, _) = map
         .iter()
         .rev()
         .find(|(_, cell)| matches!(cell, Cell::Ground))
         .unwrap();
-    let (&(max_row, max_column), _) = map.iter().last().unwrap();
-
-    let mut atlas = Atlas {
-        by_time: BTreeMap::<usize, BTreeMap<(isize, isize), Cell>>::from([(0, map)]),
-        max_row,
-        max_column,
-    };
+    let mut atlas = Atlas::new(map);
 
     #[derive(Clone, Debug, PartialEq, Eq)]
     struct State {
@@ -119,17 +196,23 @@ pub fn star_one() -> usize {
 
     impl std::cmp::PartialOrd for State {
         fn partial_cmp(&self, rhs: &State) -> Option<std::cmp::Ordering> {
-            Some(self.distance.cmp(&rhs.distance).reverse())
+            Some(self.cmp(rhs))
         }
     }
 
+    // the soonest the crossing could end first, which never overestimates it: once the end is
+    // reached, every state left could only get there later
     impl std::cmp::Ord for State {
         fn cmp(&self, rhs: &State) -> std::cmp::Ordering {
-            unsafe { self.partial_cmp(rhs).unwrap_unchecked() }
+            (self.time + self.distance)
+                .cmp(&(rhs.time + rhs.distance))
+                .reverse()
         }
     }
 
     let mut visited_states = BTreeSet::<((isize, isize), usize)>::new();
+    // the state each one was first reached from, to retrace the crossing
+    let mut parents = HashMap::<((isize, isize), usize), ((isize, isize), usize)>::new();
 
     let mut min_steps = usize::MAX;
     let mut queue = BinaryHeap::<State>::new();
@@ -143,13 +226,17 @@ pub fn star_one() -> usize {
         if time >= min_steps {
             continue;
         }
-        if !visited_states.insert((position, time)) {
+        // the same tile at the same point of the blizzard cycle, only later
+        if !visited_states.insert((position, time % atlas.cycle)) {
             continue;
         }
         let new_map = atlas.get(time + 1);
         for mov in [(1isize, 0isize), (0, 1), (0, -1), (0, 0), (-1, 0)] {
             let new_position = (position.0 + mov.0, position.1 + mov.1);
-            if matches!(new_map.get(&new_position), Some(Cell::Ground)) {
+            if matches!(new_map.get(new_position), Some(Cell::Ground)) {
+                parents
+                    .entry((new_position, time + 1))
+                    .or_insert((position, time));
                 let new_distance =
                     isize::abs_diff(new_position.0, end.0) + isize::abs_diff(new_position.1, end.1);
                 if new_distance == 0 {
@@ -166,49 +253,39 @@ pub fn star_one() -> usize {
             }
         }
     }
-    min_steps
+    if min_steps == usize::MAX {
+        let (row, column) = end;
+        let message = "the exit cannot be reached";
+        return Err(ParseError::new(
+            row as usize + 1,
+            column as usize + 1,
+            message,
+        ));
+    }
+    let path = trace(&parents, (end, min_steps), |&(position, _)| position);
+    replay(&mut atlas, &path, recorder);
+    Ok(min_steps)
 }
 
-pub fn star_two() -> usize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut map = BTreeMap::<(isize, isize), Cell>::new();
-    for (r, line) in values.lines().enumerate() {
-        for (c, x) in line.chars().enumerate() {
-            map.insert(
-                (r as isize, c as isize),
-                match x {
-                    '#' => Cell::Wall,
-                    '.' => Cell::Ground,
-                    '>' => Cell::Blizzards(vec![(0, 1)]),
-                    '<' => Cell::Blizzards(vec![(0, -1)]),
-                    '^' => Cell::Blizzards(vec![(-1, 0)]),
-                    'v' => Cell::Blizzards(vec![(1, 0)]),
-                    _ => unreachable!(),
-                },
-            );
-        }
-    }
+pub fn star_two(values: &str) -> Result<usize, ParseError> {
+    cross_thrice(values, &mut Recorder::off())
+}
 
-    let (&start, _) = map
+/// Crosses the valley, goes back for the snacks and crosses it again, then draws the
+/// expedition and the blizzards over the course of the trips.
+fn cross_thrice(values: &str, recorder: &mut Recorder) -> Result<usize, ParseError> {
+    let map = parse(values)?;
+
+    let (start, _) = map
         .iter()
         .find(|(_, cell)| matches!(cell, Cell::Ground))
         .unwrap();
-    let (&end, _) = map
+    let (end, _) = map
         .iter()
         .rev()
         .find(|(_, cell)| matches!(cell, Cell::Ground))
         .unwrap();
-    let (&(max_row, max_column), _) = map.iter().last().unwrap();
-
-    let mut atlas = Atlas {
-        by_time: BTreeMap::<usize, BTreeMap<(isize, isize), Cell>>::from([(0, map)]),
-        max_row,
-        max_column,
-    };
+    let mut atlas = Atlas::new(map);
 
     #[derive(PartialEq, Eq, Clone, Debug, Copy, Hash)]
     enum Target {
@@ -230,97 +307,330 @@ pub fn star_two() -> usize {
         position: (isize, isize),
         time: usize,
         target: Target,
+        /// The soonest the last trip could end, the targets left being a straight walk away.
+        estimate: usize,
     }
 
     impl std::cmp::PartialOrd for State {
         fn partial_cmp(&self, rhs: &State) -> Option<std::cmp::Ordering> {
-            Some(match (&self.target, &rhs.target) {
-                (Target::A(_), Target::B(_)) => std::cmp::Ordering::Less,
-                (Target::A(_), Target::C(_)) => std::cmp::Ordering::Less,
-                (Target::B(_), Target::A(_)) => std::cmp::Ordering::Greater,
-                (Target::B(_), Target::C(_)) => std::cmp::Ordering::Less,
-                (Target::C(_), Target::A(_)) => std::cmp::Ordering::Greater,
-                (Target::C(_), Target::B(_)) => std::cmp::Ordering::Greater,
-                (Target::A(t1), Target::A(t2))
-                | (Target::B(t1), Target::B(t2))
-                | (Target::C(t1), Target::C(t2)) => {
-                    let d1 = isize::abs_diff(self.position.0, t1.0)
-                        + isize::abs_diff(self.position.1, t1.1);
-                    let d2 = isize::abs_diff(rhs.position.0, t2.0)
-                        + isize::abs_diff(rhs.position.1, t2.1);
-                    d1.cmp(&d2).reverse()
-                }
-            })
+            Some(self.cmp(rhs))
         }
     }
 
     impl std::cmp::Ord for State {
         fn cmp(&self, rhs: &State) -> std::cmp::Ordering {
-            unsafe { self.partial_cmp(rhs).unwrap_unchecked() }
+            self.estimate.cmp(&rhs.estimate).reverse()
         }
     }
 
-    let mut visited_states = HashSet::<State>::new();
+    let distance = |a: (isize, isize), b: (isize, isize)| a.0.abs_diff(b.0) + a.1.abs_diff(b.1);
+    let state = |position, time, target: Target| {
+        let trips_left = match target {
+            Target::A(_) => 2,
+            Target::B(_) => 1,
+            Target::C(_) => 0,
+        };
+        let estimate =
+            time + distance(position, target.coords()) + trips_left * distance(start, end);
+        State {
+            position,
+            time,
+            target,
+            estimate,
+        }
+    };
+
+    // the tile, the point of the blizzard cycle and the target of the states seen
+    let mut visited_states = HashSet::<((isize, isize), usize, Target)>::new();
+    // how many trips were made at most, to tell which one cannot be
+    let mut trips = 0;
+    // the state each one was first reached from, to retrace the trips; the last one reached
+    // is told apart by aiming past the end
+    let mut parents = HashMap::<State, State>::new();
+    let arrival = |time| state(end, time, Target::C(start));
 
     let mut min_steps = usize::MAX;
     let mut queue = BinaryHeap::<State>::new();
-    queue.push(State {
-        position: start,
-        time: 0,
-        target: Target::A(end),
-    });
-    while let Some(state) = queue.pop() {
-        if !visited_states.insert(state) {
-            continue;
-        }
+    queue.push(state(start, 0, Target::A(end)));
+    while let Some(current) = queue.pop() {
         let State {
             position,
             time,
             target,
-        } = state;
+            ..
+        } = current;
+        if !visited_states.insert((position, time % atlas.cycle, target)) {
+            continue;
+        }
         if time >= min_steps {
             continue;
         }
         let new_map = atlas.get(time + 1);
         for mov in [(1isize, 0isize), (0, 1), (0, -1), (0, 0), (-1, 0)] {
             let new_position = (position.0 + mov.0, position.1 + mov.1);
-            if matches!(new_map.get(&new_position), Some(Cell::Ground)) {
+            if matches!(new_map.get(new_position), Some(Cell::Ground)) {
                 let mut new_target = target;
                 if new_position == target.coords() {
                     new_target = match target {
-                        Target::A(_) => Target::B(start),
-                        Target::B(_) => Target::C(end),
+                        Target::A(_) => {
+                            trips = trips.max(1);
+                            Target::B(start)
+                        }
+                        Target::B(_) => {
+                            trips = trips.max(2);
+                            Target::C(end)
+                        }
                         Target::C(_) => {
                             if time + 1 < min_steps {
                                 min_steps = time + 1;
+                                parents.insert(arrival(time + 1), current);
                             }
                             continue;
                         }
                     }
                 }
-                let new_state = State {
-                    position: new_position,
-                    time: time + 1,
-                    target: new_target,
-                };
+                let new_state = state(new_position, time + 1, new_target);
+                parents.entry(new_state).or_insert(current);
                 queue.push(new_state);
             }
         }
     }
-    min_steps
+    if min_steps == usize::MAX {
+        let ((row, column), message) = match trips {
+            0 => (end, "the exit cannot be reached"),
+            1 => (start, "the entrance cannot be reached back"),
+            _ => (end, "the exit cannot be reached again"),
+        };
+        return Err(ParseError::new(
+            row as usize + 1,
+            column as usize + 1,
+            message,
+        ));
+    }
+    let path = trace(&parents, arrival(min_steps), |state| state.position);
+    replay(&mut atlas, &path, recorder);
+    Ok(min_steps)
+}
+
+/// Whether the expedition can cross the blizzards of the valley, drawn as `rows`, from the
+/// tile `from` to the tile `to` if it leaves at minute `time`, and if so when it arrives.
+/// Both tiles are openings, where the expedition may wait as long as it likes; once a whole
+/// cycle of the blizzards brings no new tile within reach, none ever will.
+fn crossing(
+    rows: &[Vec<u8>],
+    from: (usize, usize),
+    to: (usize, usize),
+    time: usize,
+) -> Option<usize> {
+    let (height, width) = (rows.len() - 2, rows[0].len() - 2);
+    let mut blizzards = vec![];
+    for (r, row) in rows.iter().enumerate() {
+        for (c, &tile) in row.iter().enumerate() {
+            match tile {
+                b'>' => blizzards.push((r, c, 0, 1)),
+                b'<' => blizzards.push((r, c, 0, width - 1)),
+                b'v' => blizzards.push((r, c, 1, 0)),
+                b'^' => blizzards.push((r, c, height - 1, 0)),
+                _ => {}
+            }
+        }
+    }
+    let cycle = width * height / gcd::binary_usize(width, height);
+    let index = |(r, c): (usize, usize)| r * (width + 2) + c;
+
+    // the tiles within reach at each minute, flattened row by row
+    let mut reached = vec![false; (height + 2) * (width + 2)];
+    reached[index(from)] = true;
+    let mut history = vec![];
+    for t in time.. {
+        if reached[index(to)] {
+            return Some(t);
+        }
+        if history.len() >= cycle && history[history.len() - cycle] == reached {
+            return None;
+        }
+
+        let mut free = rows
+            .iter()
+            .flat_map(|row| row.iter().map(|&tile| tile != b'#'))
+            .collect::<Vec<_>>();
+        for &(r, c, dr, dc) in &blizzards {
+            let (dr, dc) = (dr * (t + 1) % height, dc * (t + 1) % width);
+            free[index((1 + (r - 1 + dr) % height, 1 + (c - 1 + dc) % width))] = false;
+        }
+        let mut next = vec![false; reached.len()];
+        for r in 0..height + 2 {
+            for c in 0..width + 2 {
+                let near = [
+                    (r, c),
+                    (r + 1, c),
+                    (r, c + 1),
+                    (r.wrapping_sub(1), c),
+                    (r, c.wrapping_sub(1)),
+                ];
+                next[index((r, c))] = free[index((r, c))]
+                    && near
+                        .into_iter()
+                        .any(|(r, c)| r < height + 2 && c < width + 2 && reached[index((r, c))]);
+            }
+        }
+        history.push(std::mem::replace(&mut reached, next));
+    }
+    unreachable!()
+}
+
+/// A valley `size` tiles wide and about a fifth as high, with blizzards everywhere but the
+/// columns of the openings, where only horizontal ones blow. Valleys which cannot be crossed
+/// there, back and there again are drawn again with fewer blizzards.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    let (width, height) = (size.max(3), (size / 5).max(2));
+    let (entrance, exit) = ((0, 1), (height + 1, width));
+    let mut density = 0.85;
+    loop {
+        let mut rows = vec![vec![b'#'; width + 2]; height + 2];
+        rows[entrance.0][entrance.1] = b'.';
+        rows[exit.0][exit.1] = b'.';
+        for row in rows.iter_mut().skip(1).take(height) {
+            for (c, tile) in row.iter_mut().enumerate().skip(1).take(width) {
+                let tiles = match c == entrance.1 || c == exit.1 {
+                    true => &b"<>"[..],
+                    false => &b"<>^v"[..],
+                };
+                *tile = match rng.gen_bool(density) {
+                    true => tiles[rng.gen_range(0..tiles.len())],
+                    false => b'.',
+                };
+            }
+        }
+
+        let there = crossing(&rows, entrance, exit, 0);
+        let back = there.and_then(|t| crossing(&rows, exit, entrance, t));
+        if back
+            .and_then(|t| crossing(&rows, entrance, exit, t))
+            .is_some()
+        {
+            let rows = rows
+                .into_iter()
+                .map(|row| String::from_utf8(row).unwrap() + "\n");
+            return rows.collect();
+        }
+        density *= 0.9;
+    }
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        24
+    }
+
+    fn title(&self) -> &'static str {
+        "Blizzard Basin"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn renders(&self) -> bool {
+        true
+    }
+
+    fn render(
+        &self,
+        part: u8,
+        input: &str,
+        recorder: &mut Recorder,
+    ) -> Option<Result<Answer, ParseError>> {
+        match part {
+            1 => Some(cross(input, recorder).map(Answer::from)),
+            2 => Some(cross_thrice(input, recorder).map(Answer::from)),
+            _ => None,
+        }
+    }
+
+    fn generate(&self, rng: &mut StdRng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 18);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(18));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 54);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(54));
+    }
+
+    #[test]
+    fn test_render() {
+        let mut frames = vec![];
+        let mut sink = |_, frame: String| frames.push(frame);
+        let mut recorder = Recorder::new(Painter { ansi: false }, Some(1), &mut sink);
+        assert_eq!(
+            cross_thrice(include_str!("mock.txt"), &mut recorder),
+            Ok(54)
+        );
+
+        let expeditions = frames
+            .iter()
+            .map(|frame| {
+                let mut tiles = frame.lines().enumerate().flat_map(|(row, line)| {
+                    line.match_indices('E')
+                        .map(move |(column, _)| (row, column))
+                });
+                let expedition = tiles.next().expect("the expedition should be drawn");
+                assert_eq!(tiles.next(), None);
+                expedition
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(expeditions.len(), 55);
+        assert_eq!(expeditions.first(), Some(&(0, 1)));
+        assert_eq!(expeditions.last(), Some(&(5, 6)));
+        for pair in expeditions.windows(2) {
+            assert!(pair[0].0.abs_diff(pair[1].0) + pair[0].1.abs_diff(pair[1].1) <= 1);
+        }
+    }
+
+    #[test]
+    fn test_uncrossable() {
+        let valley = "#.###\n#>>>#\n###.#";
+        let err = ParseError::new(3, 4, "the exit cannot be reached");
+        assert_eq!(star_one(valley), Err(err.clone()));
+        assert_eq!(star_two(valley), Err(err));
+    }
+
+    #[test]
+    fn test_generate() {
+        testing::check(
+            10,
+            |rng| generate(rng, 20),
+            |values| {
+                let rows = values
+                    .lines()
+                    .map(|row| row.as_bytes().to_vec())
+                    .collect::<Vec<_>>();
+                let (entrance, exit) = ((0, 1), (rows.len() - 1, rows[0].len() - 2));
+                let there = crossing(&rows, entrance, exit, 0);
+                let back = there.and_then(|t| crossing(&rows, exit, entrance, t));
+                let again = back.and_then(|t| crossing(&rows, entrance, exit, t));
+                match (star_one(values), star_two(values)) {
+                    (Ok(one), Ok(two)) if Some(one) == there && Some(two) == again => Ok(()),
+                    answers => Err(format!("expected {there:?} and {again:?}, got {answers:?}")),
+                }
+            },
+        );
     }
 }
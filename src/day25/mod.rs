@@ -1,7 +1,12 @@
+use crate::parse::{self, Line, ParseError};
+use crate::solution::{Answer, Solution};
 use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
 
-#[derive(Clone, Copy)]
-enum Digit {
+/// A digit of a SNAFU number, worth from -2 to 2.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Digit {
     Equal,
     Dash,
     Zero,
@@ -10,7 +15,19 @@ enum Digit {
 }
 
 impl Digit {
-    fn decimal(&self) -> i64 {
+    /// Reads a digit written as `=`, `-`, `0`, `1` or `2`.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '=' => Some(Digit::Equal),
+            '-' => Some(Digit::Dash),
+            '0' => Some(Digit::Zero),
+            '1' => Some(Digit::One),
+            '2' => Some(Digit::Two),
+            _ => None,
+        }
+    }
+
+    pub fn decimal(&self) -> i64 {
         match self {
             Digit::Equal => -2,
             Digit::Dash => -1,
@@ -20,7 +37,7 @@ impl Digit {
         }
     }
 
-    fn as_str(&self) -> &str {
+    pub fn as_str(&self) -> &str {
         match self {
             Digit::Equal => "=",
             Digit::Dash => "-",
@@ -31,14 +48,20 @@ impl Digit {
     }
 }
 
-#[derive(Clone)]
-struct Snafu(Vec<Digit>);
+/// A number written in balanced base five, its least significant digit first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snafu(Vec<Digit>);
 
 impl Snafu {
-    fn decimal(&self) -> i64 {
+    /// The digits of the number, least significant first.
+    pub fn digits(&self) -> &[Digit] {
+        &self.0
+    }
+
+    pub fn decimal(&self) -> i64 {
         let mut dec = 0;
         let mut fives = 1;
-        for d in self.0.iter().rev() {
+        for d in self.0.iter() {
             dec += fives * d.decimal();
             fives *= 5;
         }
@@ -85,33 +108,66 @@ impl From<i64> for Snafu {
     }
 }
 
-pub fn star_one() -> String {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
+impl fmt::Display for Snafu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0
+            .iter()
+            .rev()
+            .try_for_each(|d| f.write_str(d.as_str()))
+    }
+}
+
+impl FromStr for Snafu {
+    type Err = ParseError;
 
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_snafu(&Line {
+            number: 1,
+            text: s.trim_end(),
+        })
+    }
+}
+
+/// Reads a SNAFU number such as `1=-0-2`, written most significant digit first.
+fn parse_snafu(line: &Line) -> Result<Snafu, ParseError> {
+    let mut digits = line
+        .text
+        .char_indices()
+        .map(|(offset, c)| {
+            Digit::from_char(c).ok_or_else(|| {
+                line.error_at(offset, "SNAFU digits must be `=`, `-`, `0`, `1` or `2`")
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if digits.is_empty() {
+        return Err(line.error_at(0, "expected a SNAFU number"));
+    }
+    digits.reverse();
+    Ok(Snafu(digits))
+}
+
+pub fn star_one(values: &str) -> Result<String, ParseError> {
     let mut sum = 0;
-    for line in values.lines() {
-        let mut digits = Vec::<Digit>::new();
-        for c in line.chars() {
-            digits.push(match c {
-                '=' => Digit::Equal,
-                '-' => Digit::Dash,
-                '0' => Digit::Zero,
-                '1' => Digit::One,
-                '2' => Digit::Two,
-                _ => unreachable!(),
-            });
-        }
-        sum += Snafu(digits).decimal();
-    }
-    let s = Snafu::from(sum);
-    s.0.iter()
-        .rev()
-        .map(Digit::as_str)
-        .collect::<Vec<_>>()
-        .join("")
+    for line in parse::lines(values) {
+        sum += parse_snafu(&line)?.decimal();
+    }
+    Ok(Snafu::from(sum).to_string())
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        25
+    }
+
+    fn title(&self) -> &'static str {
+        "Full of Hot Air"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
 }
 
 #[cfg(test)]
@@ -120,6 +176,34 @@ mod tests {
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), "2=-1=0");
+        assert_eq!(star_one(include_str!("mock.txt")), Ok("2=-1=0".to_string()));
+    }
+
+    #[test]
+    fn test_snafu() {
+        for (snafu, decimal) in [
+            ("0", 0),
+            ("1=", 3),
+            ("2=-01", 976),
+            ("1121-1110-1=0", 314159265),
+        ] {
+            let number = snafu.parse::<Snafu>().unwrap();
+            assert_eq!(number.decimal(), decimal);
+            assert_eq!(Snafu::from(decimal), number);
+            assert_eq!(number.to_string(), snafu);
+        }
+        assert_eq!(
+            "12a".parse::<Snafu>(),
+            Err(ParseError::new(
+                1,
+                3,
+                "SNAFU digits must be `=`, `-`, `0`, `1` or `2`"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_single_part() {
+        assert!(Solver.part(2, include_str!("mock.txt")).is_none());
     }
 }
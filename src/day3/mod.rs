@@ -1,76 +1,311 @@
+use crate::parse::{self, Line, ParseError, Stream};
+use crate::solution::{Answer, Solution};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::BTreeMap;
 use std::collections::BTreeSet;
+use std::io::BufRead;
 
-pub fn star_one() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut sum = 0;
-    let mut set = BTreeSet::new();
-
-    for line in values.lines() {
-        set.clear();
-        let len = line.len();
-        let (dept_one, dept_two) = line.split_at(len / 2);
-        for b in dept_one.as_bytes() {
-            set.insert(if *b > 96 { *b - 96 } else { *b - 38 });
-        }
-        for b in dept_two.as_bytes() {
-            let v = if *b > 96 { *b - 96 } else { *b - 38 };
-            if set.contains(&v) {
-                sum += v as u64;
-                break;
+/// The priority of every item type a rucksack may hold.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Priorities {
+    items: BTreeMap<char, u64>,
+}
+
+impl Priorities {
+    /// A table of the given item types and priorities. Fails if an item is given twice.
+    pub fn new(items: impl IntoIterator<Item = (char, u64)>) -> Result<Self, String> {
+        let mut table = BTreeMap::new();
+        for (item, priority) in items {
+            if table.insert(item, priority).is_some() {
+                return Err(format!("item `{item}` given twice"));
             }
         }
+        Ok(Self { items: table })
+    }
+
+    /// The items of `alphabet`, prioritised 1, 2, 3... in order.
+    pub fn alphabet(alphabet: &str) -> Result<Self, String> {
+        Self::new(alphabet.chars().zip(1..))
     }
 
-    sum
+    /// The table of the puzzle: a-z are 1-26, A-Z are 27-52.
+    pub fn letters() -> Self {
+        let letters = ('a'..='z').chain('A'..='Z').collect::<String>();
+        Self::alphabet(&letters).expect("letters should be distinct")
+    }
+
+    pub fn get(&self, item: char) -> Option<u64> {
+        self.items.get(&item).copied()
+    }
 }
 
-pub fn star_two() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut sum = 0;
-    let mut set_one = BTreeSet::new();
-    let mut set_two = BTreeSet::new();
-
-    let mut lines = values.lines();
-    while let Some(line) = lines.next() {
-        set_one.clear();
-        set_two.clear();
-        for b in line.as_bytes() {
-            set_one.insert(if *b > 96 { *b - 96 } else { *b - 38 });
+/// Finds the items rucksacks have in common: those in every compartment of a rucksack, and
+/// those in every rucksack of a group.
+pub struct Analyser {
+    priorities: Priorities,
+    compartments: usize,
+    group: usize,
+}
+
+impl Analyser {
+    /// Fails unless rucksacks have a compartment at least, and groups a rucksack.
+    pub fn new(priorities: Priorities, compartments: usize, group: usize) -> Result<Self, String> {
+        match (compartments, group) {
+            (0, _) => Err("expected a compartment at least".to_string()),
+            (_, 0) => Err("expected a rucksack per group at least".to_string()),
+            _ => Ok(Self {
+                priorities,
+                compartments,
+                group,
+            }),
         }
-        for b in lines.next().unwrap().as_bytes() {
-            set_two.insert(if *b > 96 { *b - 96 } else { *b - 38 });
+    }
+
+    /// The analyser of the puzzle: letters, two compartments and groups of three.
+    pub fn puzzle() -> Self {
+        Self::new(Priorities::letters(), 2, 3).expect("the puzzle settings should be valid")
+    }
+
+    /// The items of a rucksack, failing on those missing from the table.
+    fn items(&self, line: &Line) -> Result<Vec<char>, ParseError> {
+        let items =
+            line.text
+                .char_indices()
+                .map(|(offset, item)| match self.priorities.get(item) {
+                    Some(_) => Ok(item),
+                    None => Err(line.error_at(offset, format!("unknown item `{item}`"))),
+                });
+        items.collect()
+    }
+
+    /// Sorts items by priority, then by themselves.
+    fn ranked(&self, items: BTreeSet<char>) -> Vec<char> {
+        let mut items = items.into_iter().collect::<Vec<_>>();
+        items.sort_by_key(|&item| self.priorities.get(item));
+        items
+    }
+
+    /// Every item in all the compartments of a rucksack, by priority.
+    pub fn shared(&self, line: &Line) -> Result<Vec<char>, ParseError> {
+        let items = self.items(line)?;
+        if items.len() % self.compartments != 0 {
+            let len = line.text.len();
+            return Err(line.error_at(len, "compartments should have the same size"));
+        }
+        let size = items.len() / self.compartments;
+        let mut compartments = items
+            .chunks(size.max(1))
+            .map(|compartment| compartment.iter().copied().collect::<BTreeSet<_>>());
+        let first = compartments.next().unwrap_or_default();
+        let shared = compartments.fold(first, |shared, compartment| &shared & &compartment);
+        Ok(self.ranked(shared))
+    }
+
+    /// Every item in all the rucksacks of the next group read from `lines`, by priority, or
+    /// None at the end of the input.
+    pub fn badges<R: BufRead>(
+        &self,
+        lines: &mut Stream<R>,
+    ) -> Result<Option<Vec<char>>, ParseError> {
+        let Some(line) = lines.next_line()? else {
+            return Ok(None);
+        };
+        let mut badges = self.items(&line)?.into_iter().collect::<BTreeSet<_>>();
+        for rucksack in 2..=self.group {
+            let line = lines.expect(&format!("rucksack {rucksack} of the group"))?;
+            let items = self.items(&line)?.into_iter().collect::<BTreeSet<_>>();
+            badges = &badges & &items;
+        }
+        Ok(Some(self.ranked(badges)))
+    }
+
+    fn priority(&self, items: &[char]) -> u64 {
+        let priorities = items.iter().map(|&item| self.priorities.get(item));
+        priorities.map(Option::unwrap_or_default).sum()
+    }
+
+    /// The total priority of the items shared by the compartments of every rucksack read
+    /// line by line from `reader`.
+    pub fn shared_priority(&self, reader: impl BufRead) -> Result<u64, ParseError> {
+        let mut sum = 0;
+        let mut lines = parse::stream(reader);
+        while let Some(line) = lines.next_line()? {
+            sum += self.priority(&self.shared(&line)?);
+        }
+        Ok(sum)
+    }
+
+    /// The total priority of the badges of every group read line by line from `reader`.
+    pub fn badge_priority(&self, reader: impl BufRead) -> Result<u64, ParseError> {
+        let mut sum = 0;
+        let mut lines = parse::stream(reader);
+        while let Some(badges) = self.badges(&mut lines)? {
+            sum += self.priority(&badges);
         }
-        for b in lines.next().unwrap().as_bytes() {
-            let v = if *b > 96 { *b - 96 } else { *b - 38 };
-            if set_one.contains(&v) && set_two.contains(&v) {
-                sum += v as u64;
-                break;
+        Ok(sum)
+    }
+}
+
+pub fn star_one(values: &str) -> Result<u64, ParseError> {
+    star_one_from(values.as_bytes())
+}
+
+pub fn star_two(values: &str) -> Result<u64, ParseError> {
+    star_two_from(values.as_bytes())
+}
+
+/// Same as `star_one`, reading the rucksacks line by line from `reader`.
+pub fn star_one_from(reader: impl BufRead) -> Result<u64, ParseError> {
+    Analyser::puzzle().shared_priority(reader)
+}
+
+/// Same as `star_two`, reading the rucksacks line by line from `reader`.
+pub fn star_two_from(reader: impl BufRead) -> Result<u64, ParseError> {
+    Analyser::puzzle().badge_priority(reader)
+}
+
+/// Random rucksacks, `size` of them rounded up to whole groups of three, each with a single
+/// item type in both compartments and each group with a single badge.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    let mut values = String::new();
+    let mut items = (b'a'..=b'z').chain(b'A'..=b'Z').collect::<Vec<_>>();
+    for _ in 0..size.div_ceil(3) {
+        items.shuffle(rng);
+        // the badge, then the items only the first, second or third elf of the group carries
+        let (&badge, owned) = items.split_first().unwrap();
+        for owned in owned.chunks(owned.len() / 3) {
+            let (&shared, owned) = owned.split_first().unwrap();
+            let len = rng.gen_range(2..=24);
+            let mut compartments = [vec![shared], vec![shared]];
+            compartments[rng.gen_range(0..2)].push(badge);
+            let halves = owned.split_at(owned.len() / 2);
+            for (compartment, owned) in compartments.iter_mut().zip([halves.0, halves.1]) {
+                while compartment.len() < len {
+                    compartment.push(*owned.choose(rng).unwrap());
+                }
+                compartment.shuffle(rng);
+                values.push_str(std::str::from_utf8(compartment).unwrap());
             }
+            values.push('\n');
+        }
+    }
+    values
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        3
+    }
+
+    fn title(&self) -> &'static str {
+        "Rucksack Reorganization"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn stream(&self, part: u8, reader: &mut dyn BufRead) -> Option<Result<Answer, ParseError>> {
+        match part {
+            1 => Some(star_one_from(reader).map(Answer::from)),
+            2 => Some(star_two_from(reader).map(Answer::from)),
+            _ => None,
         }
     }
 
-    sum
+    fn generate(&self, rng: &mut StdRng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 157);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(157));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 70);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(70));
+    }
+
+    #[test]
+    fn test_shared() {
+        let analyser = Analyser::puzzle();
+        let shared = |text| analyser.shared(&parse::lines(text).next().unwrap());
+        assert_eq!(shared("vJrwpWtwJgWrhcsFMMfFFhFp"), Ok(vec!['p']));
+        assert_eq!(shared("abAcAa"), Ok(vec!['a', 'A']));
+        let err = ParseError::new(1, 4, "unknown item `1`");
+        assert_eq!(shared("abc1"), Err(err));
+        let err = ParseError::new(1, 4, "compartments should have the same size");
+        assert_eq!(shared("abc"), Err(err));
+
+        let analyser = Analyser::new(Priorities::letters(), 3, 2).unwrap();
+        let values = "abcbcacab\nxay\nabcdefghi\n";
+        let lines = parse::lines(values).collect::<Vec<_>>();
+        assert_eq!(analyser.shared(&lines[0]), Ok(vec!['a', 'b', 'c']));
+        assert_eq!(analyser.shared(&lines[2]), Ok(vec![]));
+        assert_eq!(analyser.shared_priority(values.as_bytes()), Ok(6));
+        let err = ParseError::new(4, 1, "expected rucksack 2 of the group, found end of input");
+        assert_eq!(analyser.badge_priority(values.as_bytes()), Err(err));
+        assert!(Analyser::new(Priorities::letters(), 0, 3).is_err());
+    }
+
+    #[test]
+    fn test_priorities() {
+        assert_eq!(Priorities::letters().get('A'), Some(27));
+        let err = Priorities::alphabet("αβα").err();
+        assert_eq!(err.as_deref(), Some("item `α` given twice"));
+
+        // a table of Greek letters, highest first
+        let priorities = Priorities::new("αβγδ".chars().zip([40, 30, 20, 10])).unwrap();
+        let analyser = Analyser::new(priorities, 2, 2).unwrap();
+        let values = "αγδβ\nδβαδ\n";
+        let mut lines = parse::stream(values.as_bytes());
+        assert_eq!(analyser.badges(&mut lines), Ok(Some(vec!['δ', 'β', 'α'])));
+        assert_eq!(analyser.badges(&mut lines), Ok(None));
+        assert_eq!(analyser.badge_priority(values.as_bytes()), Ok(80));
+        assert_eq!(analyser.shared_priority(values.as_bytes()), Ok(10));
+        let err = ParseError::new(1, 3, "unknown item `ε`");
+        assert_eq!(analyser.shared_priority("αβεγ".as_bytes()), Err(err));
+    }
+
+    #[test]
+    fn test_generate() {
+        testing::check(
+            20,
+            |rng| generate(rng, 30),
+            |values| {
+                let items = |text: &str| text.bytes().collect::<BTreeSet<_>>();
+                let lines = values.lines().collect::<Vec<_>>();
+                for line in &lines {
+                    let (left, right) = line.split_at(line.len() / 2);
+                    if items(left).intersection(&items(right)).count() != 1 {
+                        return Err(format!(
+                            "`{line}` has not a single item in both compartments"
+                        ));
+                    }
+                }
+                for group in lines.chunks(3) {
+                    let common = &(&items(group[0]) & &items(group[1])) & &items(group[2]);
+                    if common.len() != 1 {
+                        return Err(format!("{group:?} have not a single badge"));
+                    }
+                }
+                Ok(())
+            },
+        );
     }
 }
@@ -1,3 +1,7 @@
+use crate::parse::{self, Line, ParseError};
+use crate::solution::{Answer, Solution};
+use std::io::BufRead;
+
 struct Range {
     start: u64,
     end: u64,
@@ -8,76 +12,92 @@ impl Range {
         Self { start, end }
     }
 
+    fn contains(this: &Self, other: &Self) -> bool {
+        other.start >= this.start && other.end <= this.end
+    }
+
     fn overlaps(this: &Self, other: &Self) -> bool {
-        !(other.start > this.end) && !(other.end < this.start)
+        (other.start <= this.end) && (other.end >= this.start)
     }
 }
 
-pub fn star_one() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
+/// Reads a line such as `2-4,6-8` into its two ranges.
+fn parse_pair(line: &Line) -> Result<(Range, Range), ParseError> {
+    let parse_range = |text| -> Result<Range, ParseError> {
+        let (start, end) = line.split_once(text, "-")?;
+        Ok(Range::new(
+            line.parse(start, "section ID")?,
+            line.parse(end, "section ID")?,
+        ))
+    };
+    let (one, two) = line.split_once(line.text, ",")?;
+    Ok((parse_range(one)?, parse_range(two)?))
+}
+
+pub fn star_one(values: &str) -> Result<u64, ParseError> {
+    star_one_from(values.as_bytes())
+}
+
+pub fn star_two(values: &str) -> Result<u64, ParseError> {
+    star_two_from(values.as_bytes())
+}
 
+/// Same as `star_one`, reading the pairs line by line from `reader`.
+pub fn star_one_from(reader: impl BufRead) -> Result<u64, ParseError> {
     let mut sum = 0;
 
-    let bytes = values.as_bytes();
-    let len = bytes.len();
-
-    let mut i = 0;
-    let mut numbers = [0u64; 4];
-    while i < len {
-        unsafe {
-            let mut j = 0;
-            while j < 4 {
-                let b = *bytes.get_unchecked(i);
-                i += 1;
-                if b == 44 || b == 45 || b == 10 {
-                    j += 1;
-                } else {
-                    *numbers.get_unchecked_mut(j) *= 10;
-                    *numbers.get_unchecked_mut(j) += b as u64 - 48;
-                }
-            }
-            let a = numbers.get_unchecked(0);
-            let b = numbers.get_unchecked(1);
-            let c = numbers.get_unchecked(2);
-            let d = numbers.get_unchecked(3);
-            if (c >= a) && (d <= b) {
-                sum += 1;
-            } else {
-                if (a >= c) && (b <= d) {
-                    sum += 1;
-                }
-            }
-            numbers = [0; 4];
+    let mut lines = parse::stream(reader);
+    while let Some(line) = lines.next_line()? {
+        let (range_one, range_two) = parse_pair(&line)?;
+        if Range::contains(&range_one, &range_two) || Range::contains(&range_two, &range_one) {
+            sum += 1;
         }
     }
 
-    sum
+    Ok(sum)
 }
 
-pub fn star_two() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
+/// Same as `star_two`, reading the pairs line by line from `reader`.
+pub fn star_two_from(reader: impl BufRead) -> Result<u64, ParseError> {
     let mut sum = 0;
 
-    for line in values.lines() {
-        let mut tokeniser = line.split(",").map(|each| {
-            let mut each = each.split("-").map(|n| n.parse::<u64>().unwrap());
-            Range::new(each.next().unwrap(), each.next().unwrap())
-        });
-        let range_one = tokeniser.next().unwrap();
-        let range_two = tokeniser.next().unwrap();
+    let mut lines = parse::stream(reader);
+    while let Some(line) = lines.next_line()? {
+        let (range_one, range_two) = parse_pair(&line)?;
         if Range::overlaps(&range_one, &range_two) {
             sum += 1;
         }
     }
 
-    sum
+    Ok(sum)
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        4
+    }
+
+    fn title(&self) -> &'static str {
+        "Camp Cleanup"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn stream(&self, part: u8, reader: &mut dyn BufRead) -> Option<Result<Answer, ParseError>> {
+        match part {
+            1 => Some(star_one_from(reader).map(Answer::from)),
+            2 => Some(star_two_from(reader).map(Answer::from)),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -86,11 +106,11 @@ mod tests {
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 2);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(2));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 4);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(4));
     }
 }
@@ -1,20 +1,30 @@
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
+use rand::rngs::StdRng;
+use rand::Rng;
 use std::collections::BTreeMap;
 
-pub fn star_one() -> String {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
+/// Crates by lane, bottom first.
+type Stacks = BTreeMap<usize, Vec<char>>;
 
-    let mut stacks = BTreeMap::<usize, Vec<char>>::new();
+/// A `move <amount> from <lane> to <lane>` instruction.
+struct Move {
+    amount: usize,
+    from: usize,
+    to: usize,
+}
+
+fn parse(values: &str) -> Result<(Stacks, Vec<Move>), ParseError> {
+    let mut stacks = Stacks::new();
 
-    let mut lines = values.lines();
+    let mut lines = parse::lines(values);
 
-    while let Some(line) = lines.next() {
-        if line.is_empty() {
+    for line in lines.by_ref() {
+        if line.text.is_empty() {
             break;
         }
         for (lane, chunk) in line
+            .text
             .chars()
             .collect::<Vec<char>>()
             .as_slice()
@@ -22,31 +32,42 @@ pub fn star_one() -> String {
             .enumerate()
         {
             if chunk[0] == '[' {
-                stacks
-                    .entry(lane + 1)
-                    .or_insert_with(|| Vec::new())
-                    .insert(0, chunk[1]);
+                let Some(&label) = chunk.get(1) else {
+                    return Err(line.error_at(4 * lane + 1, "expected a crate label"));
+                };
+                stacks.entry(lane + 1).or_default().insert(0, label);
             }
         }
     }
 
-    while let Some(line) = lines.next() {
-        let tokens = line.split_whitespace().collect::<Vec<&str>>();
-        let amount = tokens[1].parse::<usize>().unwrap();
-        let lane_from = tokens[3].parse::<usize>().unwrap();
-        let lane_to = tokens[5].parse::<usize>().unwrap();
+    let mut moves = vec![];
+    for line in lines {
+        let mut tokens = line.tokens();
+        tokens.keyword("move")?;
+        let amount = tokens.parse::<usize>("amount of crates")?;
+        tokens.keyword("from")?;
+        let from = tokens.parse::<usize>("lane")?;
+        tokens.keyword("to")?;
+        let to = tokens.parse::<usize>("lane")?;
+        tokens.end()?;
+        moves.push(Move { amount, from, to });
+    }
+
+    Ok((stacks, moves))
+}
+
+pub fn star_one(values: &str) -> Result<String, ParseError> {
+    let (mut stacks, moves) = parse(values)?;
 
+    for Move { amount, from, to } in moves {
         let mut shifted_crates = Vec::<char>::new();
         for _ in 0..amount {
-            let lane_from = stacks.entry(lane_from).or_insert_with(|| vec![]);
+            let lane_from = stacks.entry(from).or_default();
             if let Some(item) = lane_from.pop() {
                 shifted_crates.push(item);
             }
         }
-        stacks
-            .entry(lane_to)
-            .or_insert_with(|| vec![])
-            .extend(shifted_crates.into_iter());
+        stacks.entry(to).or_default().extend(shifted_crates);
     }
 
     let mut tops = String::new();
@@ -54,55 +75,23 @@ pub fn star_one() -> String {
         tops.push(lane.pop().unwrap_or(' '))
     }
 
-    tops
+    Ok(tops)
 }
 
-pub fn star_two() -> String {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut stacks = BTreeMap::<usize, Vec<char>>::new();
-
-    let mut lines = values.lines();
-
-    while let Some(line) = lines.next() {
-        if line.is_empty() {
-            break;
-        }
-        for (lane, chunk) in line
-            .chars()
-            .collect::<Vec<char>>()
-            .as_slice()
-            .chunks(4)
-            .enumerate()
-        {
-            if chunk[0] == '[' {
-                stacks
-                    .entry(lane + 1)
-                    .or_insert_with(|| Vec::new())
-                    .insert(0, chunk[1]);
-            }
-        }
-    }
-
-    while let Some(line) = lines.next() {
-        let tokens = line.split_whitespace().collect::<Vec<&str>>();
-        let amount = tokens[1].parse::<usize>().unwrap();
-        let lane_from = tokens[3].parse::<usize>().unwrap();
-        let lane_to = tokens[5].parse::<usize>().unwrap();
+pub fn star_two(values: &str) -> Result<String, ParseError> {
+    let (mut stacks, moves) = parse(values)?;
 
+    for Move { amount, from, to } in moves {
         let mut shifted_crates = Vec::<char>::new();
         for _ in 0..amount {
-            let lane_from = stacks.entry(lane_from).or_insert_with(|| vec![]);
+            let lane_from = stacks.entry(from).or_default();
             if let Some(item) = lane_from.pop() {
                 shifted_crates.push(item);
             }
         }
         stacks
-            .entry(lane_to)
-            .or_insert_with(|| vec![])
+            .entry(to)
+            .or_default()
             .extend(shifted_crates.into_iter().rev());
     }
 
@@ -111,20 +100,97 @@ pub fn star_two() -> String {
         tops.push(lane.pop().unwrap_or(' '))
     }
 
-    tops
+    Ok(tops)
+}
+
+/// Nine random stacks of crates, then `size` moves between them, each of crates which are
+/// there and leaving one behind, so that every stack has a crate on top in the end.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    let mut heights = [0; 9].map(|_| rng.gen_range(2..=8));
+    let mut values = String::new();
+    for row in (0..*heights.iter().max().unwrap()).rev() {
+        let crates = heights.map(|height| match height > row {
+            true => format!("[{}]", rng.gen_range('A'..='Z')),
+            false => "   ".to_string(),
+        });
+        values.push_str(crates.join(" ").trim_end());
+        values.push('\n');
+    }
+    values.push_str(" 1   2   3   4   5   6   7   8   9\n\n");
+
+    for _ in 0..size {
+        let from = loop {
+            let lane = rng.gen_range(0..9);
+            if heights[lane] > 1 {
+                break lane;
+            }
+        };
+        let to = (from + rng.gen_range(1..9)) % 9;
+        let amount = rng.gen_range(1..heights[from]);
+        heights[from] -= amount;
+        heights[to] += amount;
+        values.push_str(&format!("move {amount} from {} to {}\n", from + 1, to + 1));
+    }
+    values
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        5
+    }
+
+    fn title(&self) -> &'static str {
+        "Supply Stacks"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn generate(&self, rng: &mut StdRng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), "CMZ".to_string());
+        assert_eq!(star_one(include_str!("mock.txt")), Ok("CMZ".to_string()));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), "MCD".to_string());
+        assert_eq!(star_two(include_str!("mock.txt")), Ok("MCD".to_string()));
+    }
+
+    #[test]
+    fn test_generate() {
+        testing::check(
+            20,
+            |rng| generate(rng, 50),
+            |values| {
+                let (mut stacks, moves) = parse(values).map_err(|err| err.to_string())?;
+                for Move { amount, from, to } in moves {
+                    let lane = stacks.entry(from).or_default();
+                    if from == to || amount >= lane.len() {
+                        let len = lane.len();
+                        return Err(format!("cannot move {amount} of {len} from {from} to {to}"));
+                    }
+                    let moved = lane.split_off(lane.len() - amount);
+                    stacks.entry(to).or_default().extend(moved);
+                }
+                Ok(())
+            },
+        );
     }
 }
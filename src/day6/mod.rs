@@ -1,43 +1,95 @@
-use std::collections::BTreeSet;
-
-pub fn star_one() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let bytes = values.lines().next().unwrap().as_bytes();
-    let offset = bytes
-        .windows(4)
-        .enumerate()
-        .find(|(_, window)| {
-            let symbols = window.iter().collect::<BTreeSet<_>>();
-            symbols.len() == 4
-        })
-        .map(|(offset, _)| offset + 4)
-        .unwrap();
-
-    offset as u64
-}
-
-pub fn star_two() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let bytes = values.lines().next().unwrap().as_bytes();
-    let offset = bytes
-        .windows(14)
-        .enumerate()
-        .find(|(_, window)| {
-            let symbols = window.iter().collect::<BTreeSet<_>>();
-            symbols.len() == 14
-        })
-        .map(|(offset, _)| offset + 14)
-        .unwrap();
-
-    offset as u64
+use crate::parse::ParseError;
+use crate::solution::{Answer, Solution};
+use std::io::BufRead;
+
+/// Finds how many characters must be read before the last `size` are all different.
+fn marker(values: &str, size: usize) -> Result<u64, ParseError> {
+    marker_from(values.as_bytes(), size)
+}
+
+/// Same as `marker`, reading the datastream a character at a time from `reader`, so that it
+/// never has to be held whole.
+fn marker_from(reader: impl BufRead, size: usize) -> Result<u64, ParseError> {
+    // where each character was last seen, and where the run of different ones starts
+    let mut seen = [None; 256];
+    let mut start = 0;
+    let mut read = 0;
+    let mut ended = true;
+    for byte in reader.bytes() {
+        let byte =
+            byte.map_err(|err| ParseError::new(1, read + 1, format!("cannot read: {err}")))?;
+        ended = false;
+        if byte == b'\n' || byte == b'\r' {
+            break;
+        }
+        if let Some(last) = seen[byte as usize] {
+            start = start.max(last + 1);
+        }
+        seen[byte as usize] = Some(read);
+        read += 1;
+        if read - start == size {
+            return Ok(read as u64);
+        }
+    }
+    if ended {
+        return Err(ParseError::new(
+            1,
+            1,
+            "expected datastream, found end of input",
+        ));
+    }
+
+    Err(ParseError::new(
+        1,
+        read + 1,
+        format!("no marker of {size} characters"),
+    ))
+}
+
+pub fn star_one(values: &str) -> Result<u64, ParseError> {
+    marker(values, 4)
+}
+
+pub fn star_two(values: &str) -> Result<u64, ParseError> {
+    marker(values, 14)
+}
+
+/// Same as `star_one`, reading the datastream incrementally from `reader`.
+pub fn star_one_from(reader: impl BufRead) -> Result<u64, ParseError> {
+    marker_from(reader, 4)
+}
+
+/// Same as `star_two`, reading the datastream incrementally from `reader`.
+pub fn star_two_from(reader: impl BufRead) -> Result<u64, ParseError> {
+    marker_from(reader, 14)
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        6
+    }
+
+    fn title(&self) -> &'static str {
+        "Tuning Trouble"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn stream(&self, part: u8, reader: &mut dyn BufRead) -> Option<Result<Answer, ParseError>> {
+        match part {
+            1 => Some(star_one_from(reader).map(Answer::from)),
+            2 => Some(star_two_from(reader).map(Answer::from)),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -46,11 +98,31 @@ mod tests {
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 7);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(7));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 19);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(19));
+    }
+
+    #[test]
+    fn test_marker_from() {
+        let examples = [
+            ("bvwbjplbgvbhsrlpgdmjqwftvncz\n", 5, 23),
+            ("nppdvjthqldpwncqszvftbrmjlhg\n", 6, 23),
+            ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg\n", 10, 29),
+            ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw\n", 11, 26),
+        ];
+        for (values, one, two) in examples {
+            // a single character at a time
+            let reader = || std::io::BufReader::with_capacity(1, values.as_bytes());
+            assert_eq!(star_one_from(reader()), Ok(one));
+            assert_eq!(star_two_from(reader()), Ok(two));
+        }
+        let error = ParseError::new(1, 4, "no marker of 4 characters");
+        assert_eq!(star_one("abc\nd"), Err(error));
+        let error = ParseError::new(1, 1, "expected datastream, found end of input");
+        assert_eq!(star_one(""), Err(error));
     }
 }
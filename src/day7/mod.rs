@@ -1,183 +1,569 @@
+pub mod shell;
+
+use crate::json;
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
+use rand::rngs::StdRng;
+use rand::Rng;
 use std::collections::BTreeMap;
-use std::collections::VecDeque;
+use std::io;
+use std::path::Path;
+
+/// The index of a folder in its filesystem.
+pub type FolderId = usize;
 
-struct Folder {
+/// A folder, along with its files and subfolders by name.
+pub struct Folder {
+    name: String,
+    parent: Option<FolderId>,
     files: BTreeMap<String, u64>,
-    folders: BTreeMap<String, Self>,
+    folders: BTreeMap<String, FolderId>,
+    /// The total size of the files within, however deep, kept up to date by the filesystem.
+    size: u64,
 }
 
 impl Folder {
-    fn new() -> Self {
-        Self {
-            files: BTreeMap::new(),
-            folders: BTreeMap::new(),
-        }
+    /// The name of the folder in its parent, empty for the root.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The folder holding this one, `None` for the root.
+    pub fn parent(&self) -> Option<FolderId> {
+        self.parent
     }
 
-    fn touch(&mut self, name: String, size: u64) {
-        self.files.insert(name, size);
+    /// The size of every file directly within this folder, by name.
+    pub fn files(&self) -> &BTreeMap<String, u64> {
+        &self.files
     }
 
-    fn mkdir(&mut self, name: String) {
-        self.folders.insert(name, Self::new());
+    /// The folders directly within this folder, by name.
+    pub fn folders(&self) -> &BTreeMap<String, FolderId> {
+        &self.folders
     }
 
-    fn size(&self) -> u64 {
-        self.folders.values().map(Self::size).sum::<u64>() + self.files.values().sum::<u64>()
+    /// The total size of the files within this folder, however deep.
+    pub fn size(&self) -> u64 {
+        self.size
     }
 }
 
-struct Filesystem {
-    root: Folder,
+/// A filesystem, as explored by a terminal transcript or walked on disk: an arena of folders,
+/// the root first.
+/// Removed folders are left behind in the arena, out of reach.
+pub struct Filesystem {
+    folders: Vec<Folder>,
+}
+
+impl Default for Filesystem {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Filesystem {
-    fn new() -> Self {
+    pub const ROOT: FolderId = 0;
+
+    pub fn new() -> Self {
+        let root = Folder {
+            name: String::new(),
+            parent: None,
+            files: BTreeMap::new(),
+            folders: BTreeMap::new(),
+            size: 0,
+        };
         Self {
-            root: Folder::new(),
+            folders: vec![root],
         }
     }
 
-    fn parse(values: &str) -> Self {
-        let mut filesystem = Filesystem::new();
+    pub fn root(&self) -> &Folder {
+        &self.folders[Self::ROOT]
+    }
 
-        // stack of references to the parents of `cwd`: used to navigate up;
-        // uses pointers, as borrowing rules make it impossible to have
-        // separate &mut references to something AND its content
-        // (since they'd be pointing to overlapping memory);
-        // we tackle this by threading read/write access through
-        // only one pointer at the time (the last of the list)
-        struct Path(Vec<*mut Folder>);
-
-        impl Path {
-            fn go_up(&mut self) {
-                if self.0.len() > 1 {
-                    self.0.pop();
-                }
-            }
+    pub fn folder(&self, id: FolderId) -> &Folder {
+        &self.folders[id]
+    }
 
-            fn go_root(&mut self) {
-                self.0.truncate(1);
-            }
+    /// The folder of the given name directly within another.
+    pub fn child(&self, id: FolderId, name: &str) -> Option<FolderId> {
+        self.folders[id].folders.get(name).copied()
+    }
 
-            fn cwd(&mut self) -> &mut Folder {
-                unsafe {
-                    self.0
-                        .last_mut()
-                        .expect("path should not be empty")
-                        .as_mut()
-                        .expect("pointer should be dereferenceable")
-                }
-            }
+    /// The absolute path of a folder, such as `/a/e`.
+    pub fn path(&self, id: FolderId) -> String {
+        let mut names = vec![];
+        let mut folder = &self.folders[id];
+        while let Some(parent) = folder.parent {
+            names.push(folder.name.as_str());
+            folder = &self.folders[parent];
+        }
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
 
-            fn enter(&mut self, name: &str) {
-                let cwd = self.cwd();
-                let dir = cwd.folders.get_mut(name).expect("folder must exist") as *mut Folder;
-                self.0.push(dir);
-            }
+    /// Every folder within the root, however deep, the root included, parents before their
+    /// subfolders.
+    pub fn iter(&self) -> impl Iterator<Item = (FolderId, &Folder)> + '_ {
+        let mut stack = vec![Self::ROOT];
+        std::iter::from_fn(move || {
+            let id = stack.pop()?;
+            let folder = &self.folders[id];
+            stack.extend(folder.folders.values().rev());
+            Some((id, folder))
+        })
+    }
+
+    /// Adds an empty folder, unless there is one of the same name already.
+    pub fn mkdir(&mut self, parent: FolderId, name: &str) -> FolderId {
+        if let Some(id) = self.child(parent, name) {
+            return id;
+        }
+        let id = self.folders.len();
+        self.folders.push(Folder {
+            name: name.to_string(),
+            parent: Some(parent),
+            files: BTreeMap::new(),
+            folders: BTreeMap::new(),
+            size: 0,
+        });
+        self.folders[parent].folders.insert(name.to_string(), id);
+        id
+    }
+
+    /// Adds a file, replacing any file of the same name.
+    pub fn touch(&mut self, folder: FolderId, name: &str, size: u64) {
+        let old = self.folders[folder].files.insert(name.to_string(), size);
+        self.resize(folder, old.unwrap_or(0), size);
+    }
+
+    /// Removes the file or the folder of the given name, returning whether there was one.
+    pub fn remove(&mut self, folder: FolderId, name: &str) -> bool {
+        if let Some(size) = self.folders[folder].files.remove(name) {
+            self.resize(folder, size, 0);
+            return true;
+        }
+        let Some(id) = self.folders[folder].folders.remove(name) else {
+            return false;
+        };
+        self.resize(folder, self.folders[id].size, 0);
+        self.folders[id].parent = None;
+        true
+    }
+
+    /// Moves the file or the folder of the given name into another folder under a new name,
+    /// replacing any file or empty folder there. Folders must not be moved within themselves.
+    pub fn rename(&mut self, folder: FolderId, name: &str, to: FolderId, new_name: &str) {
+        if let Some(size) = self.folders[folder].files.get(name).copied() {
+            self.remove(folder, name);
+            self.touch(to, new_name, size);
+            return;
+        }
+        let Some(id) = self.folders[folder].folders.remove(name) else {
+            return;
+        };
+        debug_assert!(self.ancestors(to).all(|ancestor| ancestor != id));
+        let size = self.folders[id].size;
+        self.resize(folder, size, 0);
+        self.remove(to, new_name);
+        self.folders[to].folders.insert(new_name.to_string(), id);
+        self.folders[id].parent = Some(to);
+        self.folders[id].name = new_name.to_string();
+        self.resize(to, 0, size);
+    }
+
+    /// A folder, then its parent, up to the root.
+    fn ancestors(&self, id: FolderId) -> impl Iterator<Item = FolderId> + '_ {
+        std::iter::successors(Some(id), |&id| self.folders[id].parent)
+    }
+
+    /// Updates the sizes of a folder and its parents after one of its files went from `old`
+    /// to `new`.
+    fn resize(&mut self, folder: FolderId, old: u64, new: u64) {
+        let mut id = Some(folder);
+        while let Some(folder) = id.map(|id| &mut self.folders[id]) {
+            folder.size = folder.size - old + new;
+            id = folder.parent;
         }
+    }
 
-        let mut path = Path(vec![&mut filesystem.root as *mut Folder]);
-
-        for line in values.lines() {
-            let mut tokens = line.split_whitespace();
-            match tokens.next() {
-                Some("$") => match tokens.next() {
-                    Some("cd") => match tokens.next() {
-                        Some("/") => {
-                            path.go_root();
-                        }
-                        Some("..") => {
-                            path.go_up();
-                        }
-                        Some(name) => {
-                            path.enter(name);
-                        }
-                        _ => unreachable!("unrecognised cd operand"),
+    /// Rebuilds the filesystem from a transcript of `cd` and `ls` commands along with their
+    /// output.
+    pub fn parse(values: &str) -> Result<Self, ParseError> {
+        let mut filesystem = Filesystem::new();
+        let mut cwd = Self::ROOT;
+
+        for line in parse::lines(values) {
+            let mut tokens = line.tokens();
+            match tokens.expect("command or listing")? {
+                "$" => match tokens.expect("command")? {
+                    // names run to the end of the line, spaces included
+                    "cd" => match tokens.rest("folder")? {
+                        "/" => cwd = Self::ROOT,
+                        ".." => cwd = filesystem.folders[cwd].parent.unwrap_or(Self::ROOT),
+                        name => match filesystem.child(cwd, name) {
+                            Some(id) => cwd = id,
+                            None => {
+                                return Err(line.error(name, format!("no folder named `{name}`")))
+                            }
+                        },
                     },
-                    Some("ls") => {}
-                    _ => unreachable!("unrecognised command"),
+                    "ls" => {}
+                    command => {
+                        return Err(line.error(command, format!("unrecognised command `{command}`")))
+                    }
                 },
-                Some("dir") => {
-                    let name = tokens.next().expect("file must have a name");
-                    path.cwd().mkdir(name.to_string());
+                "dir" => {
+                    let name = tokens.rest("folder name")?;
+                    filesystem.mkdir(cwd, name);
                 }
-                Some(size) => {
-                    let name = tokens.next().expect("file must have a name");
-                    let size = size.parse::<u64>().expect("size must be a u64");
-                    path.cwd().touch(name.to_string(), size);
+                size => {
+                    let size = line.parse::<u64>(size, "file size")?;
+                    let name = tokens.rest("file name")?;
+                    filesystem.touch(cwd, name, size);
                 }
-                _ => unreachable!("unrecognised"),
             }
+            tokens.end()?;
         }
 
-        filesystem
+        Ok(filesystem)
     }
-}
 
-pub fn star_one() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let filesystem = Filesystem::parse(values);
+    /// Walks a directory on disk, files weighing their length. Symbolic links are not
+    /// followed, and entries within which cannot be read are left out, as are the ones a
+    /// transcript could not hold: names with a line break, or with whitespace at either end.
+    pub fn scan(path: &Path) -> io::Result<Self> {
+        let mut filesystem = Filesystem::new();
+        let mut stack = vec![(path.to_path_buf(), Self::ROOT)];
+        while let Some((path, folder)) = stack.pop() {
+            let entries = match std::fs::read_dir(&path) {
+                Ok(entries) => entries,
+                Err(err) if folder == Self::ROOT => return Err(err),
+                Err(_) => continue,
+            };
+            for entry in entries.flatten() {
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                let name = entry.file_name().to_string_lossy().into_owned();
+                // a transcript could not tell such names apart from the next line, and its
+                // lines lose the whitespace around them
+                if name.contains('\n') || name.trim() != name {
+                    continue;
+                }
+                if metadata.is_dir() {
+                    stack.push((entry.path(), filesystem.mkdir(folder, &name)));
+                } else if metadata.is_file() {
+                    filesystem.touch(folder, &name, metadata.len());
+                }
+            }
+        }
+        Ok(filesystem)
+    }
 
-    let mut s = 0;
-    let mut queue = VecDeque::from([&filesystem.root]);
-    while let Some(folder) = queue.pop_front() {
-        let size = folder.size();
-        if size <= 100_000 {
-            s += size;
+    /// A transcript exploring the whole filesystem, which `parse` reads back: every folder
+    /// is listed right after being entered, then its subfolders are explored in turn.
+    pub fn transcript(&self) -> String {
+        let mut values = String::new();
+        // the folders left to visit, or None to go back up once a folder is done
+        let mut stack = vec![Some(Self::ROOT)];
+        while let Some(entry) = stack.pop() {
+            let Some(id) = entry else {
+                values.push_str("$ cd ..\n");
+                continue;
+            };
+            let folder = &self.folders[id];
+            let name = if id == Self::ROOT { "/" } else { &folder.name };
+            values.push_str(&format!("$ cd {name}\n$ ls\n"));
+            for name in folder.folders.keys() {
+                values.push_str(&format!("dir {name}\n"));
+            }
+            for (name, size) in &folder.files {
+                values.push_str(&format!("{size} {name}\n"));
+            }
+            for &subfolder in folder.folders.values().rev() {
+                stack.extend([None, Some(subfolder)]);
+            }
         }
-        for child in folder.folders.values() {
-            queue.push_back(child);
+        // the transcript stops once the last folder is listed
+        while values.ends_with("$ cd ..\n") {
+            values.truncate(values.len() - "$ cd ..\n".len());
         }
+        values
+    }
+
+    /// The filesystem as nested JSON objects, each folder with its name, total size, files
+    /// and subfolders.
+    pub fn json(&self) -> String {
+        self.json_of(Self::ROOT)
+    }
+
+    fn json_of(&self, id: FolderId) -> String {
+        let folder = &self.folders[id];
+        let files = folder.files.iter().map(|(name, size)| {
+            json::object(&[("name", json::string(name)), ("size", size.to_string())])
+        });
+        let folders = folder.folders.values().map(|&id| self.json_of(id));
+        json::object(&[
+            (
+                "name",
+                json::string(if id == Self::ROOT { "/" } else { &folder.name }),
+            ),
+            ("size", folder.size.to_string()),
+            ("files", json::array(&files.collect::<Vec<_>>())),
+            ("folders", json::array(&folders.collect::<Vec<_>>())),
+        ])
+    }
+
+    /// The total size of the folders of at most `limit` each, counting nested ones again.
+    pub fn small_folders(&self, limit: u64) -> u64 {
+        let sizes = self.iter().map(|(_, folder)| folder.size);
+        sizes.filter(|&size| size <= limit).sum()
+    }
+
+    /// The size of the smallest folder to delete for `needed` to be free on a disk of
+    /// `capacity`, or None if deleting everything is not enough.
+    pub fn smallest_freeing(&self, capacity: u64, needed: u64) -> Option<u64> {
+        // with enough free space already, deleting the smallest folder will do
+        let free = capacity.saturating_sub(self.root().size);
+        let required = needed.saturating_sub(free);
+        let sizes = self.iter().map(|(_, folder)| folder.size);
+        sizes.filter(|&size| size >= required).min()
     }
-    s
 }
 
-pub fn star_two() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
+pub fn star_one(values: &str) -> Result<u64, ParseError> {
+    Ok(Filesystem::parse(values)?.small_folders(100_000))
+}
 
-    let filesystem = Filesystem::parse(values);
+pub fn star_two(values: &str) -> Result<u64, ParseError> {
+    let filesystem = Filesystem::parse(values)?;
+    Ok(filesystem
+        .smallest_freeing(70_000_000, 30_000_000)
+        .expect("the root folder should be big enough"))
+}
 
-    let used_space = filesystem.root.size();
-    let free_space = 70_000_000 - used_space;
-    let required_space = 30_000_000 - free_space;
+/// A random name of lowercase letters, with an extension if `extension`.
+fn random_name(rng: &mut StdRng, extension: bool) -> String {
+    let len = rng.gen_range(1..=8) + if extension { 4 } else { 0 };
+    let mut name = (0..len)
+        .map(|_| rng.gen_range('a'..='z'))
+        .collect::<String>();
+    if extension {
+        name.insert(len - 3, '.');
+    }
+    name
+}
 
-    let mut folder_by_size = BTreeMap::new();
+/// A transcript exploring a random filesystem of `size` files spread over about a quarter as
+/// many folders, weighing 41 to 69 million in total like the puzzle inputs.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    // the parent of every folder but the root, then the folder of every file
+    let folders = size / 4 + 1;
+    let parents = (1..folders)
+        .map(|i| rng.gen_range(0..i))
+        .collect::<Vec<_>>();
+    let locations = (0..size)
+        .map(|_| rng.gen_range(0..folders))
+        .collect::<Vec<_>>();
+    let weights = (0..size)
+        .map(|_| rng.gen_range(1..=1000u64).pow(3))
+        .collect::<Vec<_>>();
+    let total = rng.gen_range(41_000_000..=69_000_000);
+    let weight = weights.iter().sum::<u64>();
 
-    let mut queue = VecDeque::from([&filesystem.root]);
-    while let Some(folder) = queue.pop_front() {
-        let size = folder.size();
-        folder_by_size.insert(size, folder);
-        for child in folder.folders.values() {
-            queue.push_back(child);
+    // parents come before their subfolders, so they are created first
+    let mut filesystem = Filesystem::new();
+    let mut ids = vec![Filesystem::ROOT];
+    for &parent in &parents {
+        let name = unused_name(rng, &filesystem, ids[parent], false);
+        ids.push(filesystem.mkdir(ids[parent], &name));
+    }
+    for (&folder, &w) in locations.iter().zip(&weights) {
+        let extension = rng.gen_bool(0.5);
+        let name = unused_name(rng, &filesystem, ids[folder], extension);
+        filesystem.touch(ids[folder], &name, (total * w / weight).max(1));
+    }
+    filesystem.transcript()
+}
+
+/// A random name, like `random_name`, for nothing within the given folder yet.
+fn unused_name(
+    rng: &mut StdRng,
+    filesystem: &Filesystem,
+    folder: FolderId,
+    extension: bool,
+) -> String {
+    let folder = filesystem.folder(folder);
+    loop {
+        let name = random_name(rng, extension);
+        if !folder.files.contains_key(&name) && !folder.folders.contains_key(&name) {
+            return name;
         }
     }
+}
 
-    let (size, _) = folder_by_size
-        .range(required_space..)
-        .next()
-        .expect("there should be a big enough folder");
-    *size
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        7
+    }
+
+    fn title(&self) -> &'static str {
+        "No Space Left On Device"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn generate(&self, rng: &mut StdRng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 95437);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(95437));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 24933642);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(24933642));
+    }
+
+    #[test]
+    fn test_filesystem() {
+        let mut filesystem = Filesystem::parse(include_str!("mock.txt")).unwrap();
+        let folders = filesystem
+            .iter()
+            .map(|(id, folder)| (filesystem.path(id), folder.size()))
+            .collect::<Vec<_>>();
+        let expected = [
+            ("/", 48381165),
+            ("/a", 94853),
+            ("/a/e", 584),
+            ("/d", 24933642),
+        ];
+        assert_eq!(
+            folders,
+            expected.map(|(path, size)| (path.to_string(), size))
+        );
+
+        let a = filesystem.child(Filesystem::ROOT, "a").unwrap();
+        let e = filesystem.child(a, "e").unwrap();
+        assert_eq!(filesystem.folder(e).parent(), Some(a));
+        filesystem.rename(a, "e", Filesystem::ROOT, "moved");
+        assert_eq!(filesystem.path(e), "/moved");
+        assert_eq!(filesystem.folder(a).size(), 94853 - 584);
+        filesystem.touch(e, "i", 1000);
+        assert!(filesystem.remove(Filesystem::ROOT, "d"));
+        assert_eq!(filesystem.root().size(), 48381165 - 24933642 + 1000 - 584);
+    }
+
+    #[test]
+    fn test_transcript() {
+        let filesystem = Filesystem::parse(include_str!("mock.txt")).unwrap();
+        let values = filesystem.transcript();
+        let expected = "\
+$ cd /\n$ ls\ndir a\ndir d\n14848514 b.txt\n8504156 c.dat\n\
+$ cd a\n$ ls\ndir e\n29116 f\n2557 g\n62596 h.lst\n\
+$ cd e\n$ ls\n584 i\n$ cd ..\n$ cd ..\n\
+$ cd d\n$ ls\n5626152 d.ext\n8033020 d.log\n4060174 j\n7214296 k\n";
+        assert_eq!(values, expected);
+        assert_eq!(Filesystem::parse(&values).unwrap().transcript(), values);
+
+        let json = Filesystem::parse("$ cd /\n$ ls\ndir a b\n$ cd a b\n$ ls\n3 c\n")
+            .unwrap()
+            .json();
+        let expected = r#"{"name":"/","size":3,"files":[],"folders":[{"name":"a b","size":3,"files":[{"name":"c","size":3}],"folders":[]}]}"#;
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn test_scan() {
+        let root = std::env::temp_dir().join(format!("aoc2022-day7-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("a b/c")).unwrap();
+        std::fs::write(root.join("a b/c/d.txt"), [0; 120]).unwrap();
+        std::fs::write(root.join("a b/e"), [0; 30]).unwrap();
+        std::fs::write(root.join("f"), [0; 7]).unwrap();
+        let filesystem = Filesystem::scan(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let filesystem = filesystem.unwrap();
+        let folders = filesystem
+            .iter()
+            .map(|(id, folder)| (filesystem.path(id), folder.size()))
+            .collect::<Vec<_>>();
+        let expected = [("/", 157), ("/a b", 150), ("/a b/c", 120)];
+        assert_eq!(
+            folders,
+            expected.map(|(path, size)| (path.to_string(), size))
+        );
+        assert_eq!(filesystem.small_folders(150), 270);
+        assert_eq!(filesystem.smallest_freeing(200, 100), Some(120));
+        assert_eq!(filesystem.smallest_freeing(200, 400), None);
+        let values = filesystem.transcript();
+        assert_eq!(star_one(&values), Ok(427));
+        assert_eq!(star_two(&values), Ok(120));
+    }
+
+    #[test]
+    fn test_scan_whitespace() {
+        let root = std::env::temp_dir().join(format!("aoc2022-day7-ws-{}", std::process::id()));
+        for folder in ["in side", " lead", "trail ", " "] {
+            std::fs::create_dir_all(root.join(folder)).unwrap();
+            std::fs::write(root.join(folder).join("a"), [0; 5]).unwrap();
+            std::fs::write(root.join(format!("{folder}.txt")), [0; 3]).unwrap();
+        }
+        std::fs::write(root.join("\t"), [0; 2]).unwrap();
+        let filesystem = Filesystem::scan(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let filesystem = filesystem.unwrap();
+        let values = filesystem.transcript();
+        let expected = "\
+$ cd /\n$ ls\ndir in side\n3 in side.txt\n3 trail .txt\n\
+$ cd in side\n$ ls\n5 a\n";
+        assert_eq!(values, expected);
+        let parsed = Filesystem::parse(&values).unwrap();
+        assert_eq!(parsed.transcript(), values);
+        assert_eq!(parsed.root().size(), filesystem.root().size());
+    }
+
+    #[test]
+    fn test_generate() {
+        testing::check(
+            20,
+            |rng| generate(rng, 40),
+            |values| {
+                let filesystem = Filesystem::parse(values).map_err(|err| err.to_string())?;
+                let total = values
+                    .lines()
+                    .filter_map(|line| line.split(' ').next()?.parse::<u64>().ok())
+                    .sum::<u64>();
+                if filesystem.root().size() != total {
+                    return Err(format!(
+                        "the root holds {}, not {total}",
+                        filesystem.root().size()
+                    ));
+                }
+                if !(41_000_000..=69_000_000).contains(&total) {
+                    return Err(format!("the files weigh {total} in total"));
+                }
+                star_two(values).map(|_| ()).map_err(|err| err.to_string())
+            },
+        );
     }
 }
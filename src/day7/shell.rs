@@ -0,0 +1,795 @@
+use super::{Filesystem, FolderId};
+
+/// What a command printed, on its standard output and error.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Output {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Output {
+    /// Whether the command went without any error.
+    pub fn success(&self) -> bool {
+        self.stderr.is_empty()
+    }
+
+    fn print(&mut self, line: impl AsRef<str>) {
+        self.stdout.push_str(line.as_ref());
+        self.stdout.push('\n');
+    }
+
+    fn error(&mut self, line: impl AsRef<str>) {
+        self.stderr.push_str(line.as_ref());
+        self.stderr.push('\n');
+    }
+}
+
+/// An entry of a folder.
+enum Node {
+    Folder(FolderId),
+    File(u64),
+}
+
+/// A shell over a filesystem, understanding `cd`, `pwd`, `ls`, `mkdir`, `rm`, `mv`, `find`,
+/// `du` and `tree` along with the options of theirs worth having on a filesystem of bare
+/// sizes. Commands print like their Unix counterparts, folders weighing nothing themselves.
+pub struct Shell {
+    filesystem: Filesystem,
+    /// The names of the folders leading to the working folder from the root.
+    cwd: Vec<String>,
+}
+
+impl Shell {
+    pub fn new(filesystem: Filesystem) -> Self {
+        Self {
+            filesystem,
+            cwd: vec![],
+        }
+    }
+
+    pub fn filesystem(&self) -> &Filesystem {
+        &self.filesystem
+    }
+
+    /// The absolute path of the working folder.
+    pub fn cwd(&self) -> String {
+        display(&self.cwd)
+    }
+
+    /// Runs a command line: a command and its arguments, separated by whitespace, which
+    /// double quotes or a backslash keep within a word as in a Unix shell.
+    pub fn execute(&mut self, line: &str) -> Output {
+        let mut out = Output::default();
+        let Some(words) = words(line) else {
+            out.error("syntax error: unterminated quoted string");
+            return out;
+        };
+        let Some((command, args)) = words.split_first() else {
+            return out;
+        };
+        let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+        match command.as_str() {
+            "cd" => self.cd(&args, &mut out),
+            "pwd" => out.print(self.cwd()),
+            "ls" => self.ls(&args, &mut out),
+            "mkdir" => self.mkdir(&args, &mut out),
+            "rm" => self.rm(&args, &mut out),
+            "mv" => self.mv(&args, &mut out),
+            "find" => self.find(&args, &mut out),
+            "du" => self.du(&args, &mut out),
+            "tree" => self.tree(&args, &mut out),
+            _ => out.error(format!("{command}: command not found")),
+        }
+        out
+    }
+
+    fn cd(&mut self, args: &[&str], out: &mut Output) {
+        let [path] = args else {
+            match args {
+                [] => self.cwd.clear(),
+                _ => out.error("cd: too many arguments"),
+            }
+            return;
+        };
+        let resolved = self.resolve(path);
+        match self.node(&resolved) {
+            Some(Node::Folder(_)) => self.cwd = resolved,
+            Some(Node::File(_)) => out.error(format!("cd: {path}: Not a directory")),
+            None => out.error(format!("cd: {path}: No such file or directory")),
+        }
+    }
+
+    fn ls(&self, args: &[&str], out: &mut Output) {
+        let Some((_, paths)) = options("ls", args, "", out) else {
+            return;
+        };
+        let paths = if paths.is_empty() { vec!["."] } else { paths };
+        // files first, then the content of each folder, under its name if there are several
+        let mut folders = vec![];
+        for &path in &paths {
+            match self.node(&self.resolve(path)) {
+                Some(Node::Folder(folder)) => folders.push((path, folder)),
+                Some(Node::File(_)) => out.print(path),
+                None => out.error(format!(
+                    "ls: cannot access '{path}': No such file or directory"
+                )),
+            }
+        }
+        for (path, folder) in folders {
+            if paths.len() > 1 {
+                let gap = if out.stdout.is_empty() { "" } else { "\n" };
+                out.print(format!("{gap}{path}:"));
+            }
+            for (name, _) in entries(&self.filesystem, folder) {
+                out.print(name);
+            }
+        }
+    }
+
+    fn mkdir(&mut self, args: &[&str], out: &mut Output) {
+        let Some((flags, paths)) = options("mkdir", args, "p", out) else {
+            return;
+        };
+        if paths.is_empty() {
+            out.error("mkdir: missing operand");
+        }
+        let parents = flags.contains(&'p');
+        for path in paths {
+            let resolved = self.resolve(path);
+            let failure = |reason| format!("mkdir: cannot create directory '{path}': {reason}");
+            // with `-p`, every missing folder along the way, but never the root
+            let first = if parents { 1 } else { resolved.len() };
+            for depth in first..=resolved.len() {
+                let Some((name, parent)) = resolved[..depth].split_last() else {
+                    out.error(failure("File exists"));
+                    break;
+                };
+                let parent = match self.node(parent) {
+                    Some(Node::Folder(parent)) => parent,
+                    Some(Node::File(_)) => {
+                        out.error(failure("Not a directory"));
+                        break;
+                    }
+                    None => {
+                        out.error(failure("No such file or directory"));
+                        break;
+                    }
+                };
+                let folder = self.filesystem.folder(parent);
+                if folder.files().contains_key(name) {
+                    out.error(failure(if parents {
+                        "Not a directory"
+                    } else {
+                        "File exists"
+                    }));
+                    break;
+                }
+                if folder.folders().contains_key(name) {
+                    if !parents {
+                        out.error(failure("File exists"));
+                    }
+                    continue;
+                }
+                self.filesystem.mkdir(parent, name);
+            }
+        }
+    }
+
+    fn rm(&mut self, args: &[&str], out: &mut Output) {
+        let Some((flags, paths)) = options("rm", args, "rfR", out) else {
+            return;
+        };
+        let recursive = flags.contains(&'r') || flags.contains(&'R');
+        let force = flags.contains(&'f');
+        if paths.is_empty() && !force {
+            out.error("rm: missing operand");
+        }
+        for path in paths {
+            if matches!(
+                path.trim_end_matches('/').rsplit('/').next(),
+                Some("." | "..")
+            ) {
+                out.error(format!(
+                    "rm: refusing to remove '.' or '..' directory: skipping '{path}'"
+                ));
+                continue;
+            }
+            let resolved = self.resolve(path);
+            let is_folder = self
+                .node(&resolved)
+                .map(|node| matches!(node, Node::Folder(_)));
+            match is_folder {
+                _ if resolved.is_empty() && recursive => {
+                    out.error("rm: it is dangerous to operate recursively on '/'");
+                }
+                Some(true) if !recursive => {
+                    out.error(format!("rm: cannot remove '{path}': Is a directory"));
+                }
+                Some(_) => {
+                    let (name, parent) = resolved.split_last().unwrap();
+                    let parent = self.folder(parent).unwrap();
+                    self.filesystem.remove(parent, name);
+                }
+                None if !force => {
+                    out.error(format!(
+                        "rm: cannot remove '{path}': No such file or directory"
+                    ));
+                }
+                None => {}
+            }
+        }
+        // a removed working folder leaves its closest remaining parent as the working one
+        while !matches!(self.node(&self.cwd), Some(Node::Folder(_))) {
+            self.cwd.pop();
+        }
+    }
+
+    fn mv(&mut self, args: &[&str], out: &mut Output) {
+        let Some((_, paths)) = options("mv", args, "", out) else {
+            return;
+        };
+        let (destination, sources) = match &paths[..] {
+            [] => return out.error("mv: missing file operand"),
+            [source] => {
+                return out.error(format!(
+                    "mv: missing destination file operand after '{source}'"
+                ))
+            }
+            [sources @ .., destination] => (*destination, sources),
+        };
+        let target = self.resolve(destination);
+        let into = matches!(self.node(&target), Some(Node::Folder(_)));
+        if sources.len() > 1 && !into {
+            return out.error(format!("mv: target '{destination}' is not a directory"));
+        }
+
+        for &source in sources {
+            let from = self.resolve(source);
+            let Some(node) = self.node(&from) else {
+                out.error(format!(
+                    "mv: cannot stat '{source}': No such file or directory"
+                ));
+                continue;
+            };
+            let is_folder = matches!(node, Node::Folder(_));
+            let (to, shown) = match (into, from.last()) {
+                (true, Some(name)) => {
+                    let mut to = target.clone();
+                    to.push(name.clone());
+                    (to, format!("{}/{name}", destination.trim_end_matches('/')))
+                }
+                _ => (target.clone(), destination.to_string()),
+            };
+
+            if to == from {
+                out.error(format!("mv: '{source}' and '{shown}' are the same file"));
+                continue;
+            }
+            if to.starts_with(&from) {
+                out.error(format!(
+                    "mv: cannot move '{source}' to a subdirectory of itself, '{shown}'"
+                ));
+                continue;
+            }
+            let failure = |reason| format!("mv: cannot move '{source}' to '{shown}': {reason}");
+            match (self.node(&to[..to.len() - 1]), self.node(&to)) {
+                (Some(Node::File(_)), _) => {
+                    out.error(failure("Not a directory"));
+                    continue;
+                }
+                (None, _) => {
+                    out.error(failure("No such file or directory"));
+                    continue;
+                }
+                (_, Some(Node::Folder(_))) if !is_folder => {
+                    out.error(format!(
+                        "mv: cannot overwrite directory '{shown}' with non-directory"
+                    ));
+                    continue;
+                }
+                (_, Some(Node::File(_))) if is_folder => {
+                    out.error(format!(
+                        "mv: cannot overwrite non-directory '{shown}' with directory '{source}'"
+                    ));
+                    continue;
+                }
+                (_, Some(Node::Folder(folder)))
+                    if !entries(&self.filesystem, folder).is_empty() =>
+                {
+                    out.error(failure("Directory not empty"));
+                    continue;
+                }
+                _ => {}
+            }
+
+            let (name, parent) = from.split_last().unwrap();
+            let (new_name, new_parent) = to.split_last().unwrap();
+            let (parent, new_parent) = (self.folder(parent), self.folder(new_parent));
+            let (parent, new_parent) = (parent.unwrap(), new_parent.unwrap());
+            self.filesystem.rename(parent, name, new_parent, new_name);
+            // the working folder moves along
+            if self.cwd.starts_with(&from) {
+                self.cwd.splice(..from.len(), to);
+            }
+        }
+    }
+
+    fn find(&self, args: &[&str], out: &mut Output) {
+        let start = args.iter().position(|arg| arg.starts_with('-'));
+        let (paths, mut expression) = args.split_at(start.unwrap_or(args.len()));
+        let mut kind = None;
+        let mut size = None;
+        while let [predicate, rest @ ..] = expression {
+            let [value, rest @ ..] = rest else {
+                return out.error(format!("find: missing argument to '{predicate}'"));
+            };
+            match *predicate {
+                "-type" => match *value {
+                    "f" => kind = Some(false),
+                    "d" => kind = Some(true),
+                    _ => return out.error(format!("find: Unknown argument to -type: {value}")),
+                },
+                "-size" => match Size::parse(value) {
+                    Some(parsed) => size = Some(parsed),
+                    None => {
+                        return out.error(format!("find: invalid argument '{value}' to '-size'"))
+                    }
+                },
+                _ => return out.error(format!("find: unknown predicate '{predicate}'")),
+            }
+            expression = rest;
+        }
+
+        let paths = if paths.is_empty() { &["."][..] } else { paths };
+        for &path in paths {
+            let Some(node) = self.node(&self.resolve(path)) else {
+                out.error(format!("find: '{path}': No such file or directory"));
+                continue;
+            };
+            let mut visit = |shown: &str, node: &Node| {
+                let (is_folder, bytes) = match node {
+                    Node::Folder(_) => (true, 0),
+                    Node::File(bytes) => (false, *bytes),
+                };
+                if kind.is_none_or(|kind| kind == is_folder)
+                    && size.as_ref().is_none_or(|size| size.matches(bytes))
+                {
+                    out.print(shown);
+                }
+            };
+            walk(&self.filesystem, path.to_string(), &node, &mut visit);
+        }
+    }
+
+    fn du(&self, args: &[&str], out: &mut Output) {
+        let Some((flags, paths)) = options("du", args, "asb", out) else {
+            return;
+        };
+        let (all, summarize, bytes) = (
+            flags.contains(&'a'),
+            flags.contains(&'s'),
+            flags.contains(&'b'),
+        );
+        if all && summarize {
+            return out.error("du: cannot both summarize and show all entries");
+        }
+        // apparent sizes in bytes, or disk usage in blocks of 1024 bytes
+        let usage = |size: u64| if bytes { size } else { size.div_ceil(1024) };
+
+        fn total(
+            filesystem: &Filesystem,
+            shown: &str,
+            folder: FolderId,
+            usage: &dyn Fn(u64) -> u64,
+            print: &mut dyn FnMut(u64, &str, bool),
+        ) -> u64 {
+            let mut sum = 0;
+            for (name, node) in entries(filesystem, folder) {
+                let shown = format!("{}/{name}", shown.trim_end_matches('/'));
+                sum += match node {
+                    Node::Folder(folder) => total(filesystem, &shown, folder, usage, print),
+                    Node::File(size) => {
+                        print(usage(size), &shown, false);
+                        usage(size)
+                    }
+                };
+            }
+            print(sum, shown, true);
+            sum
+        }
+
+        let paths = if paths.is_empty() { vec!["."] } else { paths };
+        for path in paths {
+            match self.node(&self.resolve(path)) {
+                Some(Node::Folder(folder)) => {
+                    let mut print = |usage: u64, shown: &str, is_folder: bool| {
+                        if (is_folder && !summarize) || (!is_folder && all) {
+                            out.print(format!("{usage}\t{shown}"));
+                        }
+                    };
+                    let sum = total(&self.filesystem, path, folder, &usage, &mut print);
+                    if summarize {
+                        out.print(format!("{sum}\t{path}"));
+                    }
+                }
+                Some(Node::File(size)) => out.print(format!("{}\t{path}", usage(size))),
+                None => out.error(format!(
+                    "du: cannot access '{path}': No such file or directory"
+                )),
+            }
+        }
+    }
+
+    fn tree(&self, args: &[&str], out: &mut Output) {
+        let Some((_, paths)) = options("tree", args, "", out) else {
+            return;
+        };
+        let paths = if paths.is_empty() { vec!["."] } else { paths };
+        let (mut folders, mut files) = (0, 0);
+
+        /// Prints the entries within a folder, returning how many folders and files it holds.
+        fn branch(
+            filesystem: &Filesystem,
+            folder: FolderId,
+            indent: &str,
+            out: &mut Output,
+        ) -> (usize, usize) {
+            let entries = entries(filesystem, folder);
+            let (mut folders, mut files) = (0, 0);
+            for (i, (name, node)) in entries.iter().enumerate() {
+                let last = i + 1 == entries.len();
+                out.print(format!(
+                    "{indent}{}{name}",
+                    if last { "└── " } else { "├── " }
+                ));
+                match node {
+                    Node::Folder(folder) => {
+                        let indent = format!("{indent}{}", if last { "    " } else { "│   " });
+                        let (below, within) = branch(filesystem, *folder, &indent, out);
+                        (folders, files) = (folders + below + 1, files + within);
+                    }
+                    Node::File(_) => files += 1,
+                }
+            }
+            (folders, files)
+        }
+
+        for path in paths {
+            match self.node(&self.resolve(path)) {
+                Some(Node::Folder(folder)) => {
+                    out.print(path);
+                    let (below, within) = branch(&self.filesystem, folder, "", out);
+                    (folders, files) = (folders + below, files + within);
+                }
+                _ => out.print(format!("{path} [error opening dir]")),
+            }
+        }
+        let folders = match folders {
+            1 => "1 directory".to_string(),
+            n => format!("{n} directories"),
+        };
+        let files = match files {
+            1 => "1 file".to_string(),
+            n => format!("{n} files"),
+        };
+        out.print(format!("\n{folders}, {files}"));
+    }
+
+    /// The names leading to the given path from the root, `..` and `.` resolved.
+    fn resolve(&self, path: &str) -> Vec<String> {
+        let mut resolved = if path.starts_with('/') {
+            vec![]
+        } else {
+            self.cwd.clone()
+        };
+        for name in path.split('/') {
+            match name {
+                "" | "." => {}
+                ".." => {
+                    resolved.pop();
+                }
+                name => resolved.push(name.to_string()),
+            }
+        }
+        resolved
+    }
+
+    fn node(&self, path: &[String]) -> Option<Node> {
+        let Some((name, parent)) = path.split_last() else {
+            return Some(Node::Folder(Filesystem::ROOT));
+        };
+        let parent = self.filesystem.folder(self.folder(parent)?);
+        match (parent.folders().get(name), parent.files().get(name)) {
+            (Some(&folder), _) => Some(Node::Folder(folder)),
+            (_, Some(&size)) => Some(Node::File(size)),
+            _ => None,
+        }
+    }
+
+    fn folder(&self, path: &[String]) -> Option<FolderId> {
+        let mut folder = Filesystem::ROOT;
+        for name in path {
+            folder = self.filesystem.child(folder, name)?;
+        }
+        Some(folder)
+    }
+}
+
+/// A test on the size of files, as given to `find -size`: a number of units, sizes being
+/// rounded up to whole units, optionally preceded by `+` to look for more or `-` for less.
+struct Size {
+    ordering: std::cmp::Ordering,
+    count: u64,
+    unit: u64,
+}
+
+impl Size {
+    fn parse(value: &str) -> Option<Self> {
+        let (ordering, value) = match value.as_bytes().first()? {
+            b'+' => (std::cmp::Ordering::Greater, &value[1..]),
+            b'-' => (std::cmp::Ordering::Less, &value[1..]),
+            _ => (std::cmp::Ordering::Equal, value),
+        };
+        let (count, unit) = match value.char_indices().last()? {
+            (i, 'c') => (&value[..i], 1),
+            (i, 'w') => (&value[..i], 2),
+            (i, 'b') => (&value[..i], 512),
+            (i, 'k') => (&value[..i], 1 << 10),
+            (i, 'M') => (&value[..i], 1 << 20),
+            (i, 'G') => (&value[..i], 1 << 30),
+            _ => (value, 512),
+        };
+        let count = count.parse().ok()?;
+        Some(Self {
+            ordering,
+            count,
+            unit,
+        })
+    }
+
+    fn matches(&self, size: u64) -> bool {
+        size.div_ceil(self.unit).cmp(&self.count) == self.ordering
+    }
+}
+
+/// Splits a command line into words at whitespace, except within double quotes or after a
+/// backslash. Within quotes, a backslash only escapes a quote or another backslash. None if
+/// a quote is left open.
+fn words(line: &str) -> Option<Vec<String>> {
+    let mut words = vec![];
+    // the word being read, if any: quotes start one even if nothing is within them
+    let mut word: Option<String> = None;
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                word.get_or_insert_with(String::new);
+            }
+            '\\' if !quoted || matches!(chars.peek(), Some('"' | '\\')) => {
+                // a backslash ending the line stands for itself
+                let escaped = chars.next().unwrap_or('\\');
+                word.get_or_insert_with(String::new).push(escaped);
+            }
+            c if c.is_whitespace() && !quoted => words.extend(word.take()),
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    words.extend(word);
+    Some(words)
+}
+
+/// Parses the options of a command, flags bundled or not before its other arguments,
+/// reporting the first one not in `allowed`.
+fn options<'a>(
+    command: &str,
+    args: &[&'a str],
+    allowed: &str,
+    out: &mut Output,
+) -> Option<(Vec<char>, Vec<&'a str>)> {
+    let start = args
+        .iter()
+        .position(|arg| !arg.starts_with('-') || *arg == "-")
+        .unwrap_or(args.len());
+    let flags = args[..start]
+        .iter()
+        .flat_map(|arg| arg.chars().skip(1))
+        .collect::<Vec<_>>();
+    if let Some(flag) = flags.iter().find(|&&flag| !allowed.contains(flag)) {
+        out.error(format!("{command}: invalid option -- '{flag}'"));
+        return None;
+    }
+    Some((flags, args[start..].to_vec()))
+}
+
+/// The files and subfolders of a folder, sorted by name.
+fn entries(filesystem: &Filesystem, folder: FolderId) -> Vec<(&String, Node)> {
+    let folder = filesystem.folder(folder);
+    let files = folder
+        .files()
+        .iter()
+        .map(|(name, &size)| (name, Node::File(size)));
+    let folders = folder
+        .folders()
+        .iter()
+        .map(|(name, &id)| (name, Node::Folder(id)));
+    let mut entries = files.chain(folders).collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+/// Visits an entry then everything within it, depth first, along with their path from the
+/// one given.
+fn walk(filesystem: &Filesystem, shown: String, node: &Node, visit: &mut dyn FnMut(&str, &Node)) {
+    visit(&shown, node);
+    if let Node::Folder(folder) = node {
+        for (name, node) in entries(filesystem, *folder) {
+            let shown = format!("{}/{name}", shown.trim_end_matches('/'));
+            walk(filesystem, shown, &node, visit);
+        }
+    }
+}
+
+/// The absolute path leading to a folder.
+fn display(path: &[String]) -> String {
+    format!("/{}", path.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell() -> Shell {
+        Shell::new(Filesystem::parse(include_str!("mock.txt")).unwrap())
+    }
+
+    fn run(shell: &mut Shell, line: &str) -> String {
+        let output = shell.execute(line);
+        assert_eq!(output.stderr, "", "`{line}` failed");
+        output.stdout
+    }
+
+    #[test]
+    fn test_tree() {
+        let expected = "\
+.
+├── a
+│   ├── e
+│   │   └── i
+│   ├── f
+│   ├── g
+│   └── h.lst
+├── b.txt
+├── c.dat
+└── d
+    ├── d.ext
+    ├── d.log
+    ├── j
+    └── k
+
+3 directories, 10 files
+";
+        assert_eq!(run(&mut shell(), "tree"), expected);
+    }
+
+    #[test]
+    fn test_du() {
+        let mut shell = shell();
+        let expected = "584\t./a/e\n94853\t./a\n24933642\t./d\n48381165\t.\n";
+        assert_eq!(run(&mut shell, "du -b"), expected);
+        assert_eq!(run(&mut shell, "du -s /a/e"), "1\t/a/e\n");
+        assert_eq!(run(&mut shell, "du -ab a/e"), "584\ta/e/i\n584\ta/e\n");
+    }
+
+    #[test]
+    fn test_find() {
+        let mut shell = shell();
+        // folders weigh nothing themselves
+        let expected = ".\n./a\n./a/e\n./a/e/i\n./a/f\n./a/g\n./a/h.lst\n./d\n";
+        assert_eq!(run(&mut shell, "find -size -100k"), expected);
+        let expected = "./a/e/i\n./a/f\n./a/g\n./a/h.lst\n";
+        assert_eq!(run(&mut shell, "find . -type f -size -100k"), expected);
+        assert_eq!(run(&mut shell, "find / -type d"), "/\n/a\n/a/e\n/d\n");
+        assert_eq!(run(&mut shell, "find d -size +7000000c"), "d/d.log\nd/k\n");
+        assert_eq!(
+            run(&mut shell, "ls a/f /d"),
+            "a/f\n\n/d:\nd.ext\nd.log\nj\nk\n"
+        );
+    }
+
+    #[test]
+    fn test_edit() {
+        let mut shell = shell();
+        run(&mut shell, "mkdir -p x/y/z");
+        run(&mut shell, "cd x/y");
+        run(&mut shell, "mv /a/e z");
+        run(&mut shell, "mv /b.txt ../moved.txt");
+        run(&mut shell, "rm -r /d");
+        assert_eq!(run(&mut shell, "pwd"), "/x/y\n");
+        assert_eq!(
+            run(&mut shell, "find /"),
+            "\
+/\n/a\n/a/f\n/a/g\n/a/h.lst\n/c.dat\n/x\n/x/moved.txt\n/x/y\n/x/y/z\n/x/y/z/e\n/x/y/z/e/i\n"
+        );
+
+        run(&mut shell, "cd z/e");
+        run(&mut shell, "mv /x /a");
+        assert_eq!(run(&mut shell, "pwd"), "/a/x/y/z/e\n");
+        run(&mut shell, "rm -r /a");
+        assert_eq!(run(&mut shell, "pwd"), "/\n");
+    }
+
+    #[test]
+    fn test_quotes() {
+        let mut shell = shell();
+        run(
+            &mut shell,
+            r#"mkdir "My Documents" My\ Music "\"quoted\" \\ back""#,
+        );
+        assert_eq!(
+            run(&mut shell, "ls"),
+            "\"quoted\" \\ back\nMy Documents\nMy Music\na\nb.txt\nc.dat\nd\n"
+        );
+        run(&mut shell, r#"cd "My Documents""#);
+        assert_eq!(run(&mut shell, "pwd"), "/My Documents\n");
+        run(&mut shell, r"cd ../My\ Music");
+        assert_eq!(run(&mut shell, "pwd"), "/My Music\n");
+        run(&mut shell, r#"cd /My" "Documents"#);
+        assert_eq!(run(&mut shell, "pwd"), "/My Documents\n");
+
+        let output = shell.execute(r#"cd "My Documents"#);
+        assert_eq!(output.stderr, "syntax error: unterminated quoted string\n");
+        assert_eq!(
+            shell.execute("cd My Documents").stderr,
+            "cd: too many arguments\n"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut shell = shell();
+        let errors = [
+            ("mkdir a", "mkdir: cannot create directory 'a': File exists"),
+            (
+                "mkdir q/r",
+                "mkdir: cannot create directory 'q/r': No such file or directory",
+            ),
+            (
+                "mkdir -p b.txt/r",
+                "mkdir: cannot create directory 'b.txt/r': Not a directory",
+            ),
+            ("rm a", "rm: cannot remove 'a': Is a directory"),
+            ("rm q", "rm: cannot remove 'q': No such file or directory"),
+            (
+                "rm -r /",
+                "rm: it is dangerous to operate recursively on '/'",
+            ),
+            (
+                "mv a a/e",
+                "mv: cannot move 'a' to a subdirectory of itself, 'a/e/a'",
+            ),
+            (
+                "mv a b.txt",
+                "mv: cannot overwrite non-directory 'b.txt' with directory 'a'",
+            ),
+            ("mv q a", "mv: cannot stat 'q': No such file or directory"),
+            ("cd b.txt", "cd: b.txt: Not a directory"),
+            ("find -size 1x", "find: invalid argument '1x' to '-size'"),
+            ("du -x", "du: invalid option -- 'x'"),
+            ("mkdir /", "mkdir: cannot create directory '/': File exists"),
+            ("cat b.txt", "cat: command not found"),
+        ];
+        for (line, error) in errors {
+            let output = shell.execute(line);
+            assert_eq!(
+                (output.stdout, output.stderr),
+                (String::new(), format!("{error}\n"))
+            );
+        }
+        assert_eq!(run(&mut shell, "tree"), run(&mut self::shell(), "tree"));
+    }
+}
@@ -1,71 +1,26 @@
-use std::collections::BTreeMap;
-
-struct GridMap<T> {
-    data: BTreeMap<usize, BTreeMap<usize, T>>,
-    rows: usize,
-    columns: usize,
-}
-
-impl<T> GridMap<T> {
-    fn new() -> Self {
-        Self {
-            data: BTreeMap::new(),
-            rows: 0,
-            columns: 0,
-        }
-    }
-
-    fn get(&self, row: usize, column: usize) -> Option<&T> {
-        self.data.get(&row).and_then(|row| row.get(&column))
-    }
-
-    fn set(&mut self, row: usize, column: usize, value: T) {
-        self.data.entry(row).or_default().insert(column, value);
-        self.rows = self.rows.max(row + 1);
-        self.columns = self.columns.max(column + 1);
-    }
-
-    fn row(&self, row: usize) -> Option<impl DoubleEndedIterator<Item = (usize, Option<&T>)>> {
-        if row >= self.rows {
-            None
-        } else {
-            Some((0..self.columns).map(move |column| (column, self.get(row, column))))
-        }
-    }
-
-    fn column(
-        &self,
-        column: usize,
-    ) -> Option<impl DoubleEndedIterator<Item = (usize, Option<&T>)>> {
-        if column >= self.columns {
-            None
-        } else {
-            Some((0..self.rows).map(move |row| (row, self.get(row, column))))
-        }
-    }
+use crate::grid::{self, Dense, Grid, Sparse};
+use crate::parse::ParseError;
+use crate::solution::{Answer, Solution};
+
+/// Reads the height of every tree, one digit each.
+fn parse(values: &str) -> Result<Dense<u8>, ParseError> {
+    grid::parse(values, |_, c| match c.to_digit(10) {
+        Some(height) => Ok(Some(height as u8)),
+        None => Err("tree heights must be digits".to_string()),
+    })
 }
 
-pub fn star_one() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
-
-    let mut map = GridMap::<u8>::new();
-
-    for (row, line) in values.lines().enumerate() {
-        for (column, b) in line.bytes().enumerate() {
-            if b < 48 {
-                break;
-            }
-            map.set(row, column, b as u8 - 48);
-        }
-    }
+pub fn star_one(values: &str) -> Result<u64, ParseError> {
+    let map = parse(values)?;
 
-    let mut visible = GridMap::<bool>::new();
+    let Some(bounds) = map.bounds() else {
+        return Ok(0);
+    };
+    let mut visible = Sparse::<bool>::new();
 
-    for row in 0..map.rows {
-        if let Some(ltr) = map.row(row) {
+    for row in bounds.rows() {
+        {
+            let ltr = map.row(row);
             ltr.fold(None, |mut max_height, data| {
                 let column = data.0;
                 if let Some(&height) = data.1 {
@@ -73,19 +28,20 @@ pub fn star_one() -> u64 {
                         Some(max) => {
                             if height > max {
                                 max_height = Some(height);
-                                visible.set(row, column, true);
+                                visible.insert((row, column), true);
                             }
                         }
                         None => {
                             max_height = Some(height);
-                            visible.set(row, column, true);
+                            visible.insert((row, column), true);
                         }
                     }
                 }
                 max_height
             });
         }
-        if let Some(ltr) = map.row(row) {
+        {
+            let ltr = map.row(row);
             ltr.rev().fold(None, |mut max_height, data| {
                 let column = data.0;
                 if let Some(&height) = data.1 {
@@ -93,12 +49,12 @@ pub fn star_one() -> u64 {
                         Some(max) => {
                             if height > max {
                                 max_height = Some(height);
-                                visible.set(row, column, true);
+                                visible.insert((row, column), true);
                             }
                         }
                         None => {
                             max_height = Some(height);
-                            visible.set(row, column, true);
+                            visible.insert((row, column), true);
                         }
                     }
                 }
@@ -107,8 +63,9 @@ pub fn star_one() -> u64 {
         }
     }
 
-    for column in 0..map.columns {
-        if let Some(ttb) = map.column(column) {
+    for column in bounds.columns() {
+        {
+            let ttb = map.column(column);
             ttb.fold(None, |mut max_height, data| {
                 let row = data.0;
                 if let Some(&height) = data.1 {
@@ -116,19 +73,20 @@ pub fn star_one() -> u64 {
                         Some(max) => {
                             if height > max {
                                 max_height = Some(height);
-                                visible.set(row, column, true);
+                                visible.insert((row, column), true);
                             }
                         }
                         None => {
                             max_height = Some(height);
-                            visible.set(row, column, true);
+                            visible.insert((row, column), true);
                         }
                     }
                 }
                 max_height
             });
         }
-        if let Some(ttb) = map.column(column) {
+        {
+            let ttb = map.column(column);
             ttb.rev().fold(None, |mut max_height, data| {
                 let row = data.0;
                 if let Some(&height) = data.1 {
@@ -136,12 +94,12 @@ pub fn star_one() -> u64 {
                         Some(max) => {
                             if height > max {
                                 max_height = Some(height);
-                                visible.set(row, column, true);
+                                visible.insert((row, column), true);
                             }
                         }
                         None => {
                             max_height = Some(height);
-                            visible.set(row, column, true);
+                            visible.insert((row, column), true);
                         }
                     }
                 }
@@ -150,42 +108,23 @@ pub fn star_one() -> u64 {
         }
     }
 
-    let mut s = 0;
-    for row in 0..visible.rows {
-        for column in 0..visible.columns {
-            if let Some(&true) = visible.get(row, column) {
-                s += 1;
-            }
-        }
-    }
-    s
+    Ok(visible.iter().filter(|(_, &visible)| visible).count() as u64)
 }
 
-pub fn star_two() -> u64 {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
+pub fn star_two(values: &str) -> Result<u64, ParseError> {
+    let map = parse(values)?;
 
-    let mut map = GridMap::<u8>::new();
+    let Some(bounds) = map.bounds() else {
+        return Ok(0);
+    };
+    let mut scores = Sparse::new();
 
-    for (row, line) in values.lines().enumerate() {
-        for (column, b) in line.bytes().enumerate() {
-            if b < 48 {
-                break;
-            }
-            map.set(row, column, b as u8 - 48);
-        }
-    }
+    for row in (bounds.top + 1)..(bounds.bottom - 1) {
+        for column in (bounds.left + 1)..(bounds.right - 1) {
+            scores.insert((row, column), {
+                let &house_height = map.get((row, column)).unwrap();
 
-    let mut scores = GridMap::new();
-
-    for row in 1..(map.rows - 1) {
-        for column in 1..(map.columns - 1) {
-            scores.set(row, column, {
-                let &house_height = map.get(row, column).unwrap();
-
-                let ltr = map.row(row).unwrap().skip_while(|(c, _)| *c <= column);
+                let ltr = map.row(row).skip_while(|(c, _)| *c <= column);
                 let mut ltr_score = 0u64;
                 for (_, h) in ltr {
                     let Some(&h) = h else { continue };
@@ -195,11 +134,7 @@ pub fn star_two() -> u64 {
                     }
                 }
 
-                let rtl = map
-                    .row(row)
-                    .unwrap()
-                    .rev()
-                    .skip_while(|(c, _)| *c >= column);
+                let rtl = map.row(row).rev().skip_while(|(c, _)| *c >= column);
                 let mut rtl_score = 0u64;
                 for (_, h) in rtl {
                     let Some(&h) = h else { continue };
@@ -209,7 +144,7 @@ pub fn star_two() -> u64 {
                     }
                 }
 
-                let ttb = map.column(column).unwrap().skip_while(|(r, _)| *r <= row);
+                let ttb = map.column(column).skip_while(|(r, _)| *r <= row);
                 let mut ttb_score = 0u64;
                 for (_, h) in ttb {
                     let Some(&h) = h else { continue };
@@ -219,11 +154,7 @@ pub fn star_two() -> u64 {
                     }
                 }
 
-                let btt = map
-                    .column(column)
-                    .unwrap()
-                    .rev()
-                    .skip_while(|(r, _)| *r >= row);
+                let btt = map.column(column).rev().skip_while(|(r, _)| *r >= row);
                 let mut btt_score = 0u64;
                 for (_, h) in btt {
                     let Some(&h) = h else { continue };
@@ -238,17 +169,27 @@ pub fn star_two() -> u64 {
         }
     }
 
-    let mut s = 0u64;
-    for row in 1..scores.rows {
-        for column in 1..scores.columns {
-            if let Some(&score) = scores.get(row, column) {
-                if score > s {
-                    s = score;
-                }
-            }
-        }
+    Ok(scores.iter().map(|(_, &score)| score).max().unwrap_or(0))
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        8
+    }
+
+    fn title(&self) -> &'static str {
+        "Treetop Tree House"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
     }
-    s
 }
 
 #[cfg(test)]
@@ -257,11 +198,11 @@ mod tests {
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 21);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(21));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 8);
+        assert_eq!(star_two(include_str!("mock.txt")), Ok(8));
     }
 }
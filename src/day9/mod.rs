@@ -1,40 +1,67 @@
+use crate::grid::{Bounds, Point};
+use crate::parse::{self, Line, ParseError};
+use crate::render::{Colour, Glyph, Painter, Recorder};
+use crate::solution::{Answer, Solution};
+use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 
-pub fn star_one() -> usize {
-    #[cfg(test)]
-    let values = include_str!("mock.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
+/// Reads a motion such as `R 4` into the unit step it is made of, and how many times.
+fn parse_motion(line: &Line) -> Result<((isize, isize), usize), ParseError> {
+    let mut tokens = line.tokens();
+    let direction = match tokens.expect("direction")? {
+        "R" => (0, 1),
+        "L" => (0, -1),
+        "U" => (1, 0),
+        "D" => (-1, 0),
+        token => return Err(line.error(token, format!("unrecognised direction `{token}`"))),
+    };
+    let steps = tokens.parse::<usize>("number of steps")?;
+    tokens.end()?;
+    Ok((direction, steps))
+}
+
+/// Draws the rope, from its tail to its head, over the positions visited by the tail.
+/// Positions are `(up, right)` so they are flipped upside down into the frame.
+fn draw(painter: &Painter, knots: &[(isize, isize)], visited: &BTreeSet<(isize, isize)>) -> String {
+    let flip = |&(up, right): &(isize, isize)| -> Point { (-up, right) };
+    let bounds = Bounds::of(knots.iter().chain(visited).map(flip)).unwrap();
+
+    let mut overlay = BTreeMap::from([((0, 0), Glyph::from('s'))]);
+    // knots closer to the head are drawn over the others
+    for (i, knot) in knots.iter().enumerate() {
+        let glyph = match knots.len() - 1 - i {
+            0 => Glyph::coloured('H', Colour::Red),
+            _ if knots.len() == 2 => Glyph::coloured('T', Colour::Yellow),
+            n => Glyph::coloured(char::from_digit(n as u32, 10).unwrap(), Colour::Yellow),
+        };
+        overlay.insert(flip(knot), glyph);
+    }
+
+    painter.frame(bounds, |point| match overlay.get(&point) {
+        Some(&glyph) => glyph,
+        None if visited.contains(&(-point.0, point.1)) => Glyph::coloured('#', Colour::Cyan),
+        None => '.'.into(),
+    })
+}
+
+pub fn star_one(values: &str) -> Result<usize, ParseError> {
+    pull(values, &mut Recorder::off())
+}
 
+/// Pulls a rope of two knots, drawing it after each step of the head.
+fn pull(values: &str, recorder: &mut Recorder) -> Result<usize, ParseError> {
+    let mut tick = 0;
     let mut head = (0isize, 0isize);
     let mut tail = (0isize, 0isize);
     let mut visited = BTreeSet::new();
     visited.insert((tail.0, tail.1));
+    recorder.step(tick, |painter| draw(painter, &[tail, head], &visited));
 
-    for line in values.lines() {
-        let mut tokens = line.split_ascii_whitespace();
-        let directon = tokens.next().expect("there should be a direction");
-        let steps = tokens
-            .next()
-            .expect("there should be a number of steps")
-            .parse::<usize>()
-            .expect("steps should be a number");
+    for line in parse::lines(values) {
+        let (direction, steps) = parse_motion(&line)?;
         for _ in 0..steps {
-            match directon {
-                "R" => {
-                    head.1 += 1;
-                }
-                "L" => {
-                    head.1 -= 1;
-                }
-                "U" => {
-                    head.0 += 1;
-                }
-                "D" => {
-                    head.0 -= 1;
-                }
-                _ => unreachable!("unrecognised direction"),
-            }
+            head.0 += direction.0;
+            head.1 += direction.1;
             if tail.1 + 1 < head.1 {
                 tail.1 += 1;
                 if tail.0 < head.0 {
@@ -72,50 +99,36 @@ pub fn star_one() -> usize {
                 }
             }
             visited.insert((tail.0, tail.1));
+            tick += 1;
+            recorder.step(tick, |painter| draw(painter, &[tail, head], &visited));
         }
     }
 
-    visited.len()
+    recorder.last(tick, |painter| draw(painter, &[tail, head], &visited));
+    Ok(visited.len())
 }
 
-pub fn star_two() -> usize {
-    #[cfg(test)]
-    let values = include_str!("mock2.txt");
-    #[cfg(not(test))]
-    let values = include_str!("input.txt");
+pub fn star_two(values: &str) -> Result<usize, ParseError> {
+    pull_long(values, &mut Recorder::off())
+}
 
+/// Pulls a rope of ten knots, drawing it after each step of the head.
+fn pull_long(values: &str, recorder: &mut Recorder) -> Result<usize, ParseError> {
+    let mut tick = 0;
     let mut knots = [(0isize, 0isize); 10];
     let mut visited = BTreeSet::new();
     visited.insert((knots[0].0, knots[0].1));
+    recorder.step(tick, |painter| draw(painter, &knots, &visited));
 
-    for line in values.lines() {
-        let mut tokens = line.split_ascii_whitespace();
-        let directon = tokens.next().expect("there should be a direction");
-        let steps = tokens
-            .next()
-            .expect("there should be a number of steps")
-            .parse::<usize>()
-            .expect("steps should be a number");
+    for line in parse::lines(values) {
+        let (direction, steps) = parse_motion(&line)?;
         for _ in 0..steps {
             for i in (0usize..9).rev() {
                 let mut head = knots[i + 1];
                 let mut tail = knots[i];
                 if i == 8 {
-                    match directon {
-                        "R" => {
-                            head.1 += 1;
-                        }
-                        "L" => {
-                            head.1 -= 1;
-                        }
-                        "U" => {
-                            head.0 += 1;
-                        }
-                        "D" => {
-                            head.0 -= 1;
-                        }
-                        _ => unreachable!("unrecognised direction"),
-                    }
+                    head.0 += direction.0;
+                    head.1 += direction.1;
                 }
                 if tail.1 + 1 < head.1 {
                     tail.1 += 1;
@@ -159,10 +172,50 @@ pub fn star_two() -> usize {
                 knots[i + 1] = head;
                 knots[i] = tail;
             }
+            tick += 1;
+            recorder.step(tick, |painter| draw(painter, &knots, &visited));
         }
     }
 
-    visited.len()
+    recorder.last(tick, |painter| draw(painter, &knots, &visited));
+    Ok(visited.len())
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    fn day(&self) -> u8 {
+        9
+    }
+
+    fn title(&self) -> &'static str {
+        "Rope Bridge"
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError> {
+        star_one(input).map(Answer::from)
+    }
+
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        Some(star_two(input).map(Answer::from))
+    }
+
+    fn renders(&self) -> bool {
+        true
+    }
+
+    fn render(
+        &self,
+        part: u8,
+        input: &str,
+        recorder: &mut Recorder,
+    ) -> Option<Result<Answer, ParseError>> {
+        match part {
+            1 => Some(pull(input, recorder).map(Answer::from)),
+            2 => Some(pull_long(input, recorder).map(Answer::from)),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -171,11 +224,11 @@ mod tests {
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(), 13);
+        assert_eq!(star_one(include_str!("mock.txt")), Ok(13));
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(), 36);
+        assert_eq!(star_two(include_str!("mock2.txt")), Ok(36));
     }
 }
@@ -0,0 +1,439 @@
+use crate::parse::{self, Line, ParseError};
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// A position on a grid, as `(row, column)`: rows grow downwards and columns rightwards.
+pub type Point = (isize, isize);
+
+/// The four orthogonal neighbours of a point: up, down, left and right.
+pub fn neighbours4((row, column): Point) -> [Point; 4] {
+    [
+        (row - 1, column),
+        (row + 1, column),
+        (row, column - 1),
+        (row, column + 1),
+    ]
+}
+
+/// The eight neighbours of a point, diagonals included, in row-major order.
+pub fn neighbours8((row, column): Point) -> [Point; 8] {
+    [
+        (row - 1, column - 1),
+        (row - 1, column),
+        (row - 1, column + 1),
+        (row, column - 1),
+        (row, column + 1),
+        (row + 1, column - 1),
+        (row + 1, column),
+        (row + 1, column + 1),
+    ]
+}
+
+/// The smallest rectangle containing some points, its bottom and right edges excluded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bounds {
+    pub top: isize,
+    pub left: isize,
+    pub bottom: isize,
+    pub right: isize,
+}
+
+impl Bounds {
+    /// The bounds of a single point.
+    pub fn point((row, column): Point) -> Self {
+        Self {
+            top: row,
+            left: column,
+            bottom: row + 1,
+            right: column + 1,
+        }
+    }
+
+    /// The bounds of some points, if there are any.
+    pub fn of(points: impl IntoIterator<Item = Point>) -> Option<Self> {
+        points.into_iter().fold(None, |bounds, point| {
+            Some(match bounds {
+                Some(mut bounds) => {
+                    bounds.extend(point);
+                    bounds
+                }
+                None => Self::point(point),
+            })
+        })
+    }
+
+    /// Grows the bounds so that they contain `point`.
+    pub fn extend(&mut self, (row, column): Point) {
+        self.top = self.top.min(row);
+        self.left = self.left.min(column);
+        self.bottom = self.bottom.max(row + 1);
+        self.right = self.right.max(column + 1);
+    }
+
+    pub fn contains(&self, (row, column): Point) -> bool {
+        self.rows().contains(&row) && self.columns().contains(&column)
+    }
+
+    /// Whether `point` lies on the outermost rows or columns.
+    pub fn on_edge(&self, (row, column): Point) -> bool {
+        row == self.top || row + 1 == self.bottom || column == self.left || column + 1 == self.right
+    }
+
+    pub fn rows(&self) -> Range<isize> {
+        self.top..self.bottom
+    }
+
+    pub fn columns(&self) -> Range<isize> {
+        self.left..self.right
+    }
+
+    pub fn height(&self) -> usize {
+        (self.bottom - self.top) as usize
+    }
+
+    pub fn width(&self) -> usize {
+        (self.right - self.left) as usize
+    }
+
+    pub fn area(&self) -> usize {
+        self.height() * self.width()
+    }
+}
+
+/// A two-dimensional map from points to cells, some of which may be empty.
+pub trait Grid {
+    type Cell;
+
+    fn get(&self, point: Point) -> Option<&Self::Cell>;
+
+    fn get_mut(&mut self, point: Point) -> Option<&mut Self::Cell>;
+
+    /// Fills a cell, returning its previous content.
+    fn insert(&mut self, point: Point, value: Self::Cell) -> Option<Self::Cell>;
+
+    /// Empties a cell, returning its previous content.
+    fn remove(&mut self, point: Point) -> Option<Self::Cell>;
+
+    /// The bounds of the filled cells, if any.
+    fn bounds(&self) -> Option<Bounds>;
+
+    /// How many cells are filled.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The filled cells, in row-major order.
+    fn iter(&self) -> impl DoubleEndedIterator<Item = (Point, &Self::Cell)>;
+
+    fn contains(&self, point: Point) -> bool {
+        self.get(point).is_some()
+    }
+
+    /// The filled orthogonal neighbours of a point.
+    fn neighbours4(&self, point: Point) -> impl Iterator<Item = (Point, &Self::Cell)> {
+        neighbours4(point)
+            .into_iter()
+            .filter_map(|point| Some((point, self.get(point)?)))
+    }
+
+    /// The filled neighbours of a point, diagonals included.
+    fn neighbours8(&self, point: Point) -> impl Iterator<Item = (Point, &Self::Cell)> {
+        neighbours8(point)
+            .into_iter()
+            .filter_map(|point| Some((point, self.get(point)?)))
+    }
+
+    /// The cells of a row, from left to right within the bounds.
+    fn row(&self, row: isize) -> impl DoubleEndedIterator<Item = (isize, Option<&Self::Cell>)> {
+        let columns = self.bounds().map_or(0..0, |bounds| bounds.columns());
+        columns.map(move |column| (column, self.get((row, column))))
+    }
+
+    /// The cells of a column, from top to bottom within the bounds.
+    fn column(
+        &self,
+        column: isize,
+    ) -> impl DoubleEndedIterator<Item = (isize, Option<&Self::Cell>)> {
+        let rows = self.bounds().map_or(0..0, |bounds| bounds.rows());
+        rows.map(move |row| (row, self.get((row, column))))
+    }
+}
+
+/// A grid storing only its filled cells, suited to maps that are mostly empty or unbounded.
+#[derive(Clone, Debug)]
+pub struct Sparse<T> {
+    cells: BTreeMap<Point, T>,
+    bounds: Option<Bounds>,
+}
+
+impl<T> Sparse<T> {
+    pub fn new() -> Self {
+        Self {
+            cells: BTreeMap::new(),
+            bounds: None,
+        }
+    }
+}
+
+impl<T> Default for Sparse<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Grid for Sparse<T> {
+    type Cell = T;
+
+    fn get(&self, point: Point) -> Option<&T> {
+        self.cells.get(&point)
+    }
+
+    fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.cells.get_mut(&point)
+    }
+
+    fn insert(&mut self, point: Point, value: T) -> Option<T> {
+        match &mut self.bounds {
+            Some(bounds) => bounds.extend(point),
+            None => self.bounds = Some(Bounds::point(point)),
+        }
+        self.cells.insert(point, value)
+    }
+
+    fn remove(&mut self, point: Point) -> Option<T> {
+        let value = self.cells.remove(&point)?;
+        // only a cell on the edge can make the bounds shrink
+        if self.bounds.is_some_and(|bounds| bounds.on_edge(point)) {
+            self.bounds = Bounds::of(self.cells.keys().copied());
+        }
+        Some(value)
+    }
+
+    fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn iter(&self) -> impl DoubleEndedIterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(&point, value)| (point, value))
+    }
+}
+
+impl<T> FromIterator<(Point, T)> for Sparse<T> {
+    fn from_iter<I: IntoIterator<Item = (Point, T)>>(iter: I) -> Self {
+        let mut grid = Self::new();
+        for (point, value) in iter {
+            grid.insert(point, value);
+        }
+        grid
+    }
+}
+
+/// A grid storing every cell of a fixed area, suited to maps that are mostly filled.
+#[derive(Clone, Debug)]
+pub struct Dense<T> {
+    area: Bounds,
+    cells: Vec<Option<T>>,
+    len: usize,
+    bounds: Option<Bounds>,
+}
+
+impl<T> Dense<T> {
+    /// An empty grid able to hold the cells within `area`.
+    pub fn new(area: Bounds) -> Self {
+        Self {
+            area,
+            cells: (0..area.area()).map(|_| None).collect(),
+            len: 0,
+            bounds: None,
+        }
+    }
+
+    /// The area the grid can hold cells in, filled or not.
+    pub fn area(&self) -> Bounds {
+        self.area
+    }
+
+    fn index(&self, point: Point) -> Option<usize> {
+        if self.area.contains(point) {
+            let row = (point.0 - self.area.top) as usize;
+            let column = (point.1 - self.area.left) as usize;
+            Some(row * self.area.width() + column)
+        } else {
+            None
+        }
+    }
+
+    fn point(&self, index: usize) -> Point {
+        let width = self.area.width();
+        (
+            self.area.top + (index / width) as isize,
+            self.area.left + (index % width) as isize,
+        )
+    }
+}
+
+impl<T> Grid for Dense<T> {
+    type Cell = T;
+
+    fn get(&self, point: Point) -> Option<&T> {
+        self.cells[self.index(point)?].as_ref()
+    }
+
+    fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        let index = self.index(point)?;
+        self.cells[index].as_mut()
+    }
+
+    /// Panics if `point` lies outside of the area of the grid.
+    fn insert(&mut self, point: Point, value: T) -> Option<T> {
+        let Some(index) = self.index(point) else {
+            panic!("{point:?} lies outside of the grid area {:?}", self.area);
+        };
+        match &mut self.bounds {
+            Some(bounds) => bounds.extend(point),
+            None => self.bounds = Some(Bounds::point(point)),
+        }
+        let previous = self.cells[index].replace(value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    fn remove(&mut self, point: Point) -> Option<T> {
+        let index = self.index(point)?;
+        let value = self.cells[index].take()?;
+        self.len -= 1;
+        // only a cell on the edge can make the bounds shrink
+        if self.bounds.is_some_and(|bounds| bounds.on_edge(point)) {
+            self.bounds = Bounds::of(self.iter().map(|(point, _)| point));
+        }
+        Some(value)
+    }
+
+    fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn iter(&self) -> impl DoubleEndedIterator<Item = (Point, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| Some((self.point(index), value.as_ref()?)))
+    }
+}
+
+impl<T> FromIterator<(Point, T)> for Dense<T> {
+    /// The area of the grid is the bounds of the given cells.
+    fn from_iter<I: IntoIterator<Item = (Point, T)>>(iter: I) -> Self {
+        let cells = iter.into_iter().collect::<Vec<_>>();
+        let area = Bounds::of(cells.iter().map(|&(point, _)| point)).unwrap_or(Bounds {
+            top: 0,
+            left: 0,
+            bottom: 0,
+            right: 0,
+        });
+        let mut grid = Self::new(area);
+        for (point, value) in cells {
+            grid.insert(point, value);
+        }
+        grid
+    }
+}
+
+/// Reads a character map, one row per line, starting at `(0, 0)`.
+/// `cell` turns each character into the content of its cell, possibly leaving it empty, or
+/// rejects it with a message.
+pub fn parse<T, G>(
+    values: &str,
+    cell: impl FnMut(Point, char) -> Result<Option<T>, String>,
+) -> Result<G, ParseError>
+where
+    G: FromIterator<(Point, T)>,
+{
+    from_lines(parse::lines(values), cell)
+}
+
+/// Reads a character map from the given lines, as `parse` does.
+pub fn from_lines<'a, T, G>(
+    lines: impl Iterator<Item = Line<'a>>,
+    mut cell: impl FnMut(Point, char) -> Result<Option<T>, String>,
+) -> Result<G, ParseError>
+where
+    G: FromIterator<(Point, T)>,
+{
+    let mut cells = vec![];
+    for (row, line) in lines.enumerate() {
+        for (column, (offset, c)) in line.text.char_indices().enumerate() {
+            let point = (row as isize, column as isize);
+            match cell(point, c) {
+                Ok(Some(value)) => cells.push((point, value)),
+                Ok(None) => {}
+                Err(message) => return Err(line.error_at(offset, message)),
+            }
+        }
+    }
+    Ok(cells.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = "#.#\n..#\n";
+
+    fn walls(_: Point, c: char) -> Result<Option<()>, String> {
+        match c {
+            '#' => Ok(Some(())),
+            '.' => Ok(None),
+            _ => Err(format!("unexpected `{c}`")),
+        }
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        let sparse: Sparse<()> = parse(MAP, walls).unwrap();
+        let dense: Dense<()> = parse(MAP, walls).unwrap();
+        assert!(sparse.iter().eq(dense.iter()));
+        assert_eq!(sparse.bounds(), dense.bounds());
+        assert_eq!(sparse.len(), 3);
+        assert_eq!(dense.neighbours4((1, 2)).count(), 1);
+        assert_eq!(dense.neighbours8((1, 1)).count(), 3);
+        let column = sparse.column(2).map(|(_, cell)| cell.is_some());
+        assert_eq!(column.collect::<Vec<_>>(), [true, true]);
+    }
+
+    #[test]
+    fn test_bounds_shrink() {
+        let mut grid: Sparse<()> = parse(MAP, walls).unwrap();
+        grid.insert((-1, 5), ());
+        assert_eq!(
+            grid.bounds().map(|b| (b.rows(), b.columns())),
+            Some((-1..2, 0..6))
+        );
+        grid.remove((-1, 5));
+        grid.remove((0, 0));
+        assert_eq!(
+            grid.bounds().map(|b| (b.rows(), b.columns())),
+            Some((0..2, 2..3))
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = parse::<(), Sparse<()>>("#.\n.x\n", walls)
+            .map(|_| ())
+            .unwrap_err();
+        assert_eq!(err, ParseError::new(2, 2, "unexpected `x`"));
+    }
+}
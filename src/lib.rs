@@ -0,0 +1,64 @@
+//! Solutions to the Advent of Code 2022, along with the domain types of every day.
+//!
+//! Each `dayN` module exposes `star_one` and, but on the last day, `star_two` over the puzzle
+//! input, and a `Solver` implementing [`solution::Solution`]; [`registry::SOLUTIONS`] lists
+//! them all in order. The `aoc2022` binary is a command-line driver over this library.
+
+/// Day 1: Calorie Counting.
+pub mod day1;
+/// Day 10: Cathode-Ray Tube.
+pub mod day10;
+/// Day 11: Monkey in the Middle.
+pub mod day11;
+/// Day 12: Hill Climbing Algorithm.
+pub mod day12;
+/// Day 13: Distress Signal.
+pub mod day13;
+/// Day 14: Regolith Reservoir.
+pub mod day14;
+/// Day 15: Beacon Exclusion Zone.
+pub mod day15;
+/// Day 16: Proboscidea Volcanium.
+pub mod day16;
+/// Day 17: Pyroclastic Flow.
+pub mod day17;
+/// Day 18: Boiling Boulders.
+pub mod day18;
+/// Day 19: Not Enough Minerals.
+pub mod day19;
+/// Day 2: Rock Paper Scissors.
+pub mod day2;
+/// Day 20: Grove Positioning System.
+pub mod day20;
+/// Day 21: Monkey Math.
+pub mod day21;
+/// Day 22: Monkey Map.
+pub mod day22;
+/// Day 23: Unstable Diffusion.
+pub mod day23;
+/// Day 24: Blizzard Basin.
+pub mod day24;
+/// Day 25: Full of Hot Air.
+pub mod day25;
+/// Day 3: Rucksack Reorganization.
+pub mod day3;
+/// Day 4: Camp Cleanup.
+pub mod day4;
+/// Day 5: Supply Stacks.
+pub mod day5;
+/// Day 6: Tuning Trouble.
+pub mod day6;
+/// Day 7: No Space Left On Device.
+pub mod day7;
+/// Day 8: Treetop Tree House.
+pub mod day8;
+/// Day 9: Rope Bridge.
+pub mod day9;
+pub mod grid;
+pub mod json;
+pub mod parse;
+pub mod registry;
+pub mod render;
+pub mod solution;
+#[cfg(test)]
+mod testing;
@@ -1,101 +1,496 @@
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-// mod day15;
-mod day16;
-mod day17;
-mod day18;
-// mod day19;
-mod day2;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+mod answers;
+mod bench;
+mod cli;
+mod replay;
 
-fn main() {
-    println!("1.1: {}", day1::star_one());
-    println!("1.2: {}", day1::star_two());
+use answers::Answers;
+use aoc2022::day7::shell::Shell;
+use aoc2022::day7::Filesystem;
+use aoc2022::json;
+use aoc2022::registry;
+use aoc2022::render::{Painter, Recorder};
+use aoc2022::solution::{Answer, Solution};
+use cli::{
+    AnswerFormat, Bench, Command, Days, Format, Generate, Input, Replay, Run, Scan, ScanFormat,
+    Selection, Verify,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use replay::Projector;
+use std::io::{BufRead, BufReader, IsTerminal, Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Reads the puzzle input of the given day from the requested source.
+fn input(source: &Input, day: u8) -> std::io::Result<String> {
+    match source {
+        Input::Default => std::fs::read_to_string(format!("src/day{day}/input.txt")),
+        Input::File(path) => std::fs::read_to_string(path),
+        Input::Stdin => {
+            let mut values = String::new();
+            std::io::stdin().read_to_string(&mut values)?;
+            Ok(values)
+        }
+    }
+}
+
+/// Opens the puzzle input of the given day for reading it incrementally.
+fn open(source: &Input, day: u8) -> std::io::Result<Box<dyn BufRead>> {
+    match source {
+        Input::Default => {
+            let file = std::fs::File::open(format!("src/day{day}/input.txt"))?;
+            Ok(Box::new(BufReader::new(file)))
+        }
+        Input::File(path) => Ok(Box::new(BufReader::new(std::fs::File::open(path)?))),
+        Input::Stdin => Ok(Box::new(std::io::stdin().lock())),
+    }
+}
+
+/// The selected days.
+fn solutions(selection: &Selection) -> Vec<&'static dyn Solution> {
+    match selection.days {
+        Days::All => registry::SOLUTIONS
+            .iter()
+            .copied()
+            .filter(|solution| !(selection.skip_slow && solution.slow()))
+            .collect(),
+        Days::One(n) => registry::get(n).into_iter().collect(),
+    }
+}
+
+/// Whether a day lacking the given part is an error: only when both were asked for.
+fn missing_part(selection: &Selection, day: u8, part: u8) -> bool {
+    if let (Days::One(_), Some(_)) = (&selection.days, selection.part) {
+        eprintln!("error: day {day} has no part {part}");
+        return true;
+    }
+    false
+}
+
+/// Visits the selected days along with their input, then each selected part of them.
+/// `visit` returns whether to go on with the next part of the same day, or `None` if the day
+/// has no such part.
+/// Returns whether every selected input could be read and visited successfully.
+fn for_each_part(
+    selection: &Selection,
+    mut visit: impl FnMut(&dyn Solution, u8, &str) -> Option<bool>,
+) -> bool {
+    let mut success = true;
+
+    for solution in solutions(selection) {
+        let day = solution.day();
+        let values = match input(&selection.input, day) {
+            Ok(values) => values,
+            Err(err) => {
+                eprintln!("{day}: cannot read input: {err}");
+                success = false;
+                continue;
+            }
+        };
+
+        for part in 1..=2 {
+            if selection.part.is_some_and(|p| p != part) {
+                continue;
+            }
+            let Some(visited) = visit(solution, part, &values) else {
+                success &= !missing_part(selection, day, part);
+                break;
+            };
+            if !visited {
+                success = false;
+                // both parts read the same input, so the other would fail alike
+                break;
+            }
+        }
+    }
 
-    println!("2.1: {}", day2::star_one());
-    println!("2.2: {}", day2::star_two());
+    success
+}
 
-    println!("3.1: {}", day3::star_one());
-    println!("3.2: {}", day3::star_two());
+/// Formats the answer to a part as a JSON object, along with the time taken to solve it.
+/// Screens also get their pixels, as rows of booleans.
+fn record(day: u8, part: u8, answer: &Answer, text: &str, elapsed: Duration) -> String {
+    let mut fields = vec![
+        ("day", day.to_string()),
+        ("part", part.to_string()),
+        ("answer", json::string(text.trim_start())),
+        (
+            "elapsed_ms",
+            format!("{:.3}", elapsed.as_secs_f64() * 1000.0),
+        ),
+    ];
+    if let Answer::Screen { pixels, .. } = answer {
+        let rows = pixels
+            .iter()
+            .map(|row| json::array(&row.iter().map(bool::to_string).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        fields.push(("image", json::array(&rows)));
+    }
+    json::object(&fields)
+}
 
-    println!("4.1: {}", day4::star_one());
-    println!("4.2: {}", day4::star_two());
+/// Solves the selected days and parts, printing one answer per line, after the drawings of
+/// their states if requested, or every answer at once as JSON.
+fn run(options: &Run) -> bool {
+    let mut records = vec![];
+    let mut report = |day, part, answer: Answer, elapsed| {
+        let raster = options.raster.then(|| answer.raster()).flatten();
+        let text = raster.unwrap_or_else(|| answer.to_string());
+        match options.format {
+            AnswerFormat::Text => println!("{day}.{part}: {text}"),
+            AnswerFormat::Json => records.push(record(day, part, &answer, &text, elapsed)),
+        }
+    };
 
-    println!("5.1: {}", day5::star_one());
-    println!("5.2: {}", day5::star_two());
+    let success = match options.stream {
+        true => stream(&options.selection, report),
+        false => for_each_part(&options.selection, |solution, part, values| {
+            let day = solution.day();
+            let start = Instant::now();
+            let answer = match &options.render {
+                Some(render) if solution.renders() => {
+                    let painter = Painter { ansi: render.ansi };
+                    let mut sink = |step, frame| println!("{day}.{part}, step {step}:\n{frame}");
+                    let mut recorder = Recorder::new(painter, render.every, &mut sink);
+                    solution.render(part, values, &mut recorder)
+                }
+                _ => solution.part(part, values),
+            };
+            match answer {
+                Some(Ok(answer)) => report(day, part, answer, start.elapsed()),
+                Some(Err(err)) => {
+                    eprintln!("error: day {day}: {}", err.diagnostic(values));
+                    return Some(false);
+                }
+                None => return None,
+            }
+            Some(true)
+        }),
+    };
 
-    println!("6.1: {}", day6::star_one());
-    println!("6.2: {}", day6::star_two());
+    if let AnswerFormat::Json = options.format {
+        println!("{}", json::array(&records));
+    }
+    success
+}
 
-    println!("7.1: {}", day7::star_one());
-    println!("7.2: {}", day7::star_two());
+/// Solves the selected days and parts like `run`, reading each input anew for every part as
+/// it is solved rather than beforehand. Errors come without the offending line, which has
+/// been read past by then.
+fn stream(selection: &Selection, mut report: impl FnMut(u8, u8, Answer, Duration)) -> bool {
+    let mut success = true;
 
-    println!("8.1: {}", day8::star_one());
-    println!("8.2: {}", day8::star_two());
+    for solution in solutions(selection) {
+        let day = solution.day();
+        for part in 1..=2 {
+            if selection.part.is_some_and(|p| p != part) {
+                continue;
+            }
+            let mut reader = match open(&selection.input, day) {
+                Ok(reader) => reader,
+                Err(err) => {
+                    eprintln!("{day}: cannot read input: {err}");
+                    success = false;
+                    break;
+                }
+            };
+            let start = Instant::now();
+            match solution.stream(part, &mut reader) {
+                Some(Ok(answer)) => report(day, part, answer, start.elapsed()),
+                Some(Err(err)) => {
+                    eprintln!("error: day {day}: {err}");
+                    success = false;
+                    break;
+                }
+                None => {
+                    success &= !missing_part(selection, day, part);
+                    break;
+                }
+            }
+        }
+    }
 
-    println!("9.1: {}", day9::star_one());
-    println!("9.2: {}", day9::star_two());
+    success
+}
 
-    println!("10.1: {}", day10::star_one());
-    println!("10.2: {}", day10::star_two());
+/// Shows every state of the selected simulations, then their answer. Days which do not
+/// simulate anything only get their answer printed.
+fn replay(options: &Replay) -> bool {
+    let mut projector = match Projector::new(&options.playback) {
+        Ok(projector) => projector,
+        Err(err) => {
+            eprintln!("error: cannot prepare the replay: {err}");
+            return false;
+        }
+    };
 
-    println!("11.1: {}", day11::star_one());
-    println!("11.2: {}", day11::star_two());
+    let success = for_each_part(&options.selection, |solution, part, values| {
+        let day = solution.day();
+        let painter = Painter { ansi: options.ansi };
+        let mut sink = |step, frame: String| projector.show(day, part, step, &frame);
+        let mut recorder = Recorder::new(painter, Some(options.every), &mut sink);
+        match solution.render(part, values, &mut recorder) {
+            Some(Ok(answer)) => println!("{day}.{part}: {answer}"),
+            Some(Err(err)) => {
+                eprintln!("error: day {day}: {}", err.diagnostic(values));
+                return Some(false);
+            }
+            None => return None,
+        }
+        Some(true)
+    });
 
-    println!("12.1: {}", day12::star_one());
-    println!("12.2: {}", day12::star_two());
+    if let Err(err) = projector.finish() {
+        eprintln!("error: cannot show the frames: {err}");
+        return false;
+    }
+    success
+}
 
-    println!("13.1: {}", day13::star_one());
-    println!("13.2: {}", day13::star_two());
+/// Times the selected days and parts, printing the results once they are all measured.
+fn bench(options: &Bench) -> bool {
+    let mut reports = vec![];
+    let success = for_each_part(&options.selection, |solution, part, values| {
+        let day = solution.day();
+        eprintln!("benchmarking {day}.{part}...");
+        match bench::measure(solution, part, values, options.iterations) {
+            Some(Ok(report)) => reports.push(report),
+            Some(Err(err)) => {
+                eprintln!("error: day {day}: {}", err.diagnostic(values));
+                return Some(false);
+            }
+            None => return None,
+        }
+        Some(true)
+    });
 
-    println!("14.1: {}", day14::star_one());
-    println!("14.2: {}", day14::star_two());
+    match options.format {
+        Format::Markdown => print!("{}", bench::markdown(&reports)),
+        Format::Json => println!("{}", bench::json(&reports)),
+    }
+    success
+}
 
-    // println!("15.1: {}", day15::star_one());
-    // println!("15.2: {}", day15::star_two());
+/// Checks the selected days and parts against the expected answers, printing one verdict per
+/// line then a summary. Unknown answers are reported but only mismatches are failures.
+fn verify(options: &Verify) -> bool {
+    let answers = match std::fs::read_to_string(&options.answers) {
+        Ok(values) => match Answers::parse(&values) {
+            Ok(answers) => answers,
+            Err(err) => {
+                let path = options.answers.display();
+                eprintln!("error: {path}: {}", err.diagnostic(&values));
+                return false;
+            }
+        },
+        Err(err) => {
+            eprintln!("error: cannot read {}: {err}", options.answers.display());
+            return false;
+        }
+    };
 
-    println!("16.1: {}", day16::star_one());
-    println!("16.2: {}", day16::star_two());
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+    let success = for_each_part(&options.selection, |solution, part, values| {
+        let day = solution.day();
+        let answer = match solution.part(part, values) {
+            Some(Ok(answer)) => answer.to_string(),
+            Some(Err(err)) => {
+                eprintln!("error: day {day}: {}", err.diagnostic(values));
+                failed += 1;
+                return Some(false);
+            }
+            None => return None,
+        };
+        // multi-line answers are compared regardless of their surrounding blank lines
+        match answers.get(day, part) {
+            Some(expected) if expected.trim() == answer.trim() => {
+                println!("{day}.{part}: pass");
+                passed += 1;
+            }
+            Some(expected) => {
+                println!("{day}.{part}: FAIL, expected {expected}, got {answer}");
+                failed += 1;
+            }
+            None => {
+                println!("{day}.{part}: missing, got {answer}");
+                missing += 1;
+            }
+        }
+        Some(true)
+    });
 
-    println!("17.1: {}", day17::star_one());
-    println!("17.2: {}", day17::star_two());
+    println!("{passed} passed, {failed} failed, {missing} missing");
+    success && failed == 0
+}
 
-    println!("18.1: {}", day18::star_one());
-    println!("18.2: {}", day18::star_two());
+/// Prints a random input for the selected day, the same for the same seed and size.
+fn generate(options: &Generate) -> bool {
+    let day = options.day;
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    match registry::get(day).and_then(|solution| solution.generate(&mut rng, options.size)) {
+        Some(values) => print!("{values}"),
+        None => {
+            eprintln!("error: day {day} has no generator");
+            return false;
+        }
+    }
+    true
+}
 
-    // println!("19.1: {}", day19::star_one());
-    // println!("19.2: {}", day19::star_two());
+/// Walks a directory on disk into a day 7 filesystem, reporting failures.
+fn scan_dir(path: &Path) -> Option<Filesystem> {
+    match Filesystem::scan(path) {
+        Ok(filesystem) => Some(filesystem),
+        Err(err) => {
+            eprintln!("error: cannot read {}: {err}", path.display());
+            None
+        }
+    }
+}
 
-    println!("20.1: {}", day20::star_one());
-    println!("20.2: {}", day20::star_two());
+/// Explores the filesystem of a day 7 transcript, or of a directory on disk, with the commands
+/// of a script, or typed at a prompt if stdin is a terminal. Returns whether every command
+/// succeeded.
+fn shell(options: &cli::Shell) -> bool {
+    let filesystem = match &options.dir {
+        Some(dir) => scan_dir(dir),
+        None => {
+            let path = options
+                .input
+                .as_deref()
+                .unwrap_or(Path::new("src/day7/input.txt"));
+            match std::fs::read_to_string(path) {
+                Ok(values) => match Filesystem::parse(&values) {
+                    Ok(filesystem) => Some(filesystem),
+                    Err(err) => {
+                        eprintln!("error: {}: {}", path.display(), err.diagnostic(&values));
+                        None
+                    }
+                },
+                Err(err) => {
+                    eprintln!("error: cannot read {}: {err}", path.display());
+                    None
+                }
+            }
+        }
+    };
+    let Some(filesystem) = filesystem else {
+        return false;
+    };
 
-    println!("21.1: {}", day21::star_one());
-    println!("21.2: {}", day21::star_two());
+    let script: Box<dyn BufRead> = match &options.batch {
+        Some(path) => match std::fs::File::open(path) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(err) => {
+                eprintln!("error: cannot read {}: {err}", path.display());
+                return false;
+            }
+        },
+        None => Box::new(std::io::stdin().lock()),
+    };
+    let prompt = options.batch.is_none() && std::io::stdin().is_terminal();
 
-    println!("22.1: {}", day22::star_one());
-    println!("22.2: {}", day22::star_two());
+    let mut shell = Shell::new(filesystem);
+    let mut success = true;
+    let mut lines = script.lines();
+    loop {
+        if prompt {
+            print!("{} $ ", shell.cwd());
+            let _ = std::io::stdout().flush();
+        }
+        let line = match lines.next() {
+            Some(Ok(line)) if line.trim() != "exit" => line,
+            Some(Err(err)) => {
+                eprintln!("error: cannot read the commands: {err}");
+                return false;
+            }
+            _ => break,
+        };
+        let output = shell.execute(&line);
+        print!("{}", output.stdout);
+        eprint!("{}", output.stderr);
+        success &= output.success();
+    }
+    success
+}
 
-    println!("23.1: {}", day23::star_one());
-    println!("23.2: {}", day23::star_two());
+/// Prints the day 7 answers over a directory on disk, or its whole tree.
+fn scan(options: &Scan) -> bool {
+    let Some(filesystem) = scan_dir(&options.dir) else {
+        return false;
+    };
+    match options.format {
+        ScanFormat::Answers => {
+            let small = filesystem.small_folders(options.limit);
+            println!("folders of at most {} bytes: {small}", options.limit);
+            match filesystem.smallest_freeing(options.capacity, options.needed) {
+                Some(size) => println!("smallest folder to delete: {size}"),
+                None => {
+                    eprintln!(
+                        "error: {} bytes cannot be freed on a disk of {}",
+                        options.needed, options.capacity
+                    );
+                    return false;
+                }
+            }
+        }
+        ScanFormat::Transcript => print!("{}", filesystem.transcript()),
+        ScanFormat::Json => println!("{}", filesystem.json()),
+    }
+    true
+}
 
-    println!("24.1: {}", day24::star_one());
-    println!("24.2: {}", day24::star_two());
+fn main() {
+    let command = match Command::parse(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
 
-    println!("25.1: {}", day25::star_one());
+    match command {
+        Command::Help => println!("{}", cli::USAGE),
+        Command::List => {
+            for solution in registry::SOLUTIONS {
+                let slow = if solution.slow() { " (slow)" } else { "" };
+                println!("{:>2}: {}{slow}", solution.day(), solution.title());
+            }
+        }
+        Command::Run(options) => {
+            if !run(&options) {
+                std::process::exit(1);
+            }
+        }
+        Command::Replay(options) => {
+            if !replay(&options) {
+                std::process::exit(1);
+            }
+        }
+        Command::Bench(options) => {
+            if !bench(&options) {
+                std::process::exit(1);
+            }
+        }
+        Command::Verify(options) => {
+            if !verify(&options) {
+                std::process::exit(1);
+            }
+        }
+        Command::Generate(options) => {
+            if !generate(&options) {
+                std::process::exit(1);
+            }
+        }
+        Command::Shell(options) => {
+            if !shell(&options) {
+                std::process::exit(1);
+            }
+        }
+        Command::Scan(options) => {
+            if !scan(&options) {
+                std::process::exit(1);
+            }
+        }
+    }
 }
@@ -0,0 +1,303 @@
+use std::fmt;
+use std::io::BufRead;
+use std::str::FromStr;
+
+/// An error found while parsing a puzzle input, pointing at where it happened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number; one past the last line for a truncated input.
+    pub line: usize,
+    /// 1-based column, counted in characters.
+    pub column: usize,
+    pub message: String,
+}
+
+pub type Result<T> = std::result::Result<T, ParseError>;
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Formats the error together with the offending line of the input, compiler-style.
+    pub fn diagnostic(&self, input: &str) -> String {
+        let mut out = self.to_string();
+        if let Some(text) = input.lines().nth(self.line - 1) {
+            let gutter = " ".repeat(self.line.to_string().len());
+            let padding = " ".repeat(self.column - 1);
+            out.push_str(&format!(
+                "\n{gutter} |\n{} | {text}\n{gutter} | {padding}^",
+                self.line
+            ));
+        }
+        out
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Splits the input in numbered lines, ignoring trailing whitespace (including CRs).
+pub fn lines(values: &str) -> Lines<'_> {
+    Lines {
+        inner: values.lines(),
+        number: 0,
+    }
+}
+
+pub struct Lines<'a> {
+    inner: std::str::Lines<'a>,
+    number: usize,
+}
+
+impl<'a> Lines<'a> {
+    /// Returns the next line, failing at the end of the input.
+    pub fn expect(&mut self, what: &str) -> Result<Line<'a>> {
+        let number = self.number + 1;
+        self.next().ok_or_else(|| {
+            ParseError::new(number, 1, format!("expected {what}, found end of input"))
+        })
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = Line<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let text = self.inner.next()?.trim_end();
+        self.number += 1;
+        Some(Line {
+            number: self.number,
+            text,
+        })
+    }
+}
+
+/// Reads the input line by line from `reader`, holding a single line at a time, for inputs
+/// too large to be read whole.
+pub fn stream<R: BufRead>(reader: R) -> Stream<R> {
+    Stream {
+        reader,
+        buffer: String::new(),
+        number: 0,
+    }
+}
+
+pub struct Stream<R> {
+    reader: R,
+    buffer: String,
+    number: usize,
+}
+
+impl<R: BufRead> Stream<R> {
+    /// Reads the next line, ignoring trailing whitespace like `lines`, or None at the end of
+    /// the input.
+    pub fn next_line(&mut self) -> Result<Option<Line<'_>>> {
+        let number = self.number + 1;
+        self.buffer.clear();
+        match self.reader.read_line(&mut self.buffer) {
+            Ok(0) => return Ok(None),
+            Ok(_) => {}
+            Err(err) => return Err(ParseError::new(number, 1, format!("cannot read: {err}"))),
+        }
+        self.number = number;
+        Ok(Some(Line {
+            number,
+            text: self.buffer.trim_end(),
+        }))
+    }
+
+    /// Reads the next line, failing at the end of the input.
+    pub fn expect(&mut self, what: &str) -> Result<Line<'_>> {
+        let number = self.number + 1;
+        self.next_line()?.ok_or_else(|| {
+            ParseError::new(number, 1, format!("expected {what}, found end of input"))
+        })
+    }
+}
+
+/// A single line of the input, able to produce errors pointing inside itself.
+#[derive(Clone, Copy)]
+pub struct Line<'a> {
+    pub number: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    /// An error located at the given byte offset of the line.
+    pub fn error_at(&self, offset: usize, message: impl Into<String>) -> ParseError {
+        let offset = offset.min(self.text.len());
+        let column = self.text[..offset].chars().count() + 1;
+        ParseError::new(self.number, column, message)
+    }
+
+    /// An error located at the start of `at`, which must be a slice of this line.
+    pub fn error(&self, at: &str, message: impl Into<String>) -> ParseError {
+        let offset = (at.as_ptr() as usize).saturating_sub(self.text.as_ptr() as usize);
+        self.error_at(offset, message)
+    }
+
+    /// An error located just past the end of `at`, which must be a slice of this line.
+    pub fn error_after(&self, at: &str, message: impl Into<String>) -> ParseError {
+        let offset = (at.as_ptr() as usize).saturating_sub(self.text.as_ptr() as usize);
+        self.error_at(offset + at.len(), message)
+    }
+
+    /// Removes `prefix` from `rest`, a trailing slice of this line.
+    pub fn strip_prefix(&self, rest: &'a str, prefix: &str) -> Result<&'a str> {
+        rest.strip_prefix(prefix)
+            .ok_or_else(|| self.error(rest, format!("expected `{prefix}`")))
+    }
+
+    /// Splits `rest`, a trailing slice of this line, around the first `delimiter`.
+    pub fn split_once(&self, rest: &'a str, delimiter: &str) -> Result<(&'a str, &'a str)> {
+        rest.split_once(delimiter)
+            .ok_or_else(|| self.error_after(rest, format!("expected `{delimiter}`")))
+    }
+
+    /// Parses `token`, a slice of this line, describing it as `what` on failure.
+    pub fn parse<T: FromStr>(&self, token: &'a str, what: &str) -> Result<T> {
+        token
+            .parse()
+            .map_err(|_| self.error(token, format!("expected {what}, found `{token}`")))
+    }
+
+    /// Splits the line on whitespace.
+    pub fn tokens(&self) -> Tokens<'a> {
+        Tokens {
+            line: *self,
+            inner: self.text.split_whitespace(),
+        }
+    }
+}
+
+/// The whitespace-separated tokens of a line.
+pub struct Tokens<'a> {
+    line: Line<'a>,
+    inner: std::str::SplitWhitespace<'a>,
+}
+
+impl<'a> Tokens<'a> {
+    /// Returns the next token, failing at the end of the line.
+    pub fn expect(&mut self, what: &str) -> Result<&'a str> {
+        let line = self.line;
+        self.inner
+            .next()
+            .ok_or_else(|| line.error_at(line.text.len(), format!("expected {what}")))
+    }
+
+    /// Parses the next token, failing at the end of the line.
+    pub fn parse<T: FromStr>(&mut self, what: &str) -> Result<T> {
+        let token = self.expect(what)?;
+        self.line.parse(token, what)
+    }
+
+    /// Consumes the next token, failing unless it is exactly `keyword`.
+    pub fn keyword(&mut self, keyword: &str) -> Result<()> {
+        let token = self.expect(&format!("`{keyword}`"))?;
+        if token == keyword {
+            Ok(())
+        } else {
+            Err(self
+                .line
+                .error(token, format!("expected `{keyword}`, found `{token}`")))
+        }
+    }
+
+    /// Returns the rest of the line from the next token on, spaces included, failing at the
+    /// end of the line.
+    pub fn rest(&mut self, what: &str) -> Result<&'a str> {
+        let token = self.expect(what)?;
+        let offset = token.as_ptr() as usize - self.line.text.as_ptr() as usize;
+        self.inner = "".split_whitespace();
+        Ok(&self.line.text[offset..])
+    }
+
+    /// Fails if there are tokens left on the line.
+    pub fn end(&mut self) -> Result<()> {
+        match self.inner.next() {
+            Some(token) => Err(self.line.error(token, format!("unexpected `{token}`"))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_location() {
+        let mut lines = lines("A Y\nB 1x\n");
+        lines.next();
+        let line = lines.next().unwrap();
+        let mut tokens = line.tokens();
+        tokens.keyword("B").unwrap();
+        let err = tokens.parse::<u8>("score").unwrap_err();
+        assert_eq!(err, ParseError::new(2, 3, "expected score, found `1x`"));
+        assert_eq!(
+            err.diagnostic("A Y\nB 1x\n"),
+            "line 2, column 3: expected score, found `1x`\n  |\n2 | B 1x\n  |   ^"
+        );
+    }
+
+    #[test]
+    fn test_stream() {
+        let values = "one \r\n\ntwo";
+        // a tiny buffer, for lines to span several reads
+        let mut stream = stream(std::io::BufReader::with_capacity(2, values.as_bytes()));
+        for line in lines(values) {
+            let streamed = stream.next_line().unwrap().unwrap();
+            assert_eq!((streamed.number, streamed.text), (line.number, line.text));
+        }
+        assert!(stream.next_line().unwrap().is_none());
+        let err = stream.expect("fourth line").map(|_| ()).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(4, 1, "expected fourth line, found end of input")
+        );
+    }
+
+    #[test]
+    fn test_rest() {
+        let line = lines("123 My  Documents").next().unwrap();
+        let mut tokens = line.tokens();
+        tokens.expect("size").unwrap();
+        assert_eq!(tokens.rest("name"), Ok("My  Documents"));
+        assert_eq!(tokens.end(), Ok(()));
+        let err = tokens.rest("name").unwrap_err();
+        assert_eq!(err, ParseError::new(1, 18, "expected name"));
+    }
+
+    #[test]
+    fn test_end_of_input() {
+        let mut lines = lines("only\n");
+        lines.expect("first line").unwrap();
+        let err = lines.expect("second line").map(|_| ()).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(2, 1, "expected second line, found end of input")
+        );
+    }
+}
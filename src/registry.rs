@@ -0,0 +1,51 @@
+use crate::solution::Solution;
+use crate::*;
+
+/// Every day of the calendar, in order.
+pub static SOLUTIONS: [&dyn Solution; 25] = [
+    &day1::Solver,
+    &day2::Solver,
+    &day3::Solver,
+    &day4::Solver,
+    &day5::Solver,
+    &day6::Solver,
+    &day7::Solver,
+    &day8::Solver,
+    &day9::Solver,
+    &day10::Solver,
+    &day11::Solver,
+    &day12::Solver,
+    &day13::Solver,
+    &day14::Solver,
+    &day15::Solver,
+    &day16::Solver,
+    &day17::Solver,
+    &day18::Solver,
+    &day19::Solver,
+    &day20::Solver,
+    &day21::Solver,
+    &day22::Solver,
+    &day23::Solver,
+    &day24::Solver,
+    &day25::Solver,
+];
+
+/// Looks up the solution of the given day.
+pub fn get(day: u8) -> Option<&'static dyn Solution> {
+    SOLUTIONS
+        .iter()
+        .copied()
+        .find(|solution| solution.day() == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_in_order() {
+        for (index, solution) in SOLUTIONS.iter().enumerate() {
+            assert_eq!(solution.day() as usize, index + 1);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use crate::grid::{Bounds, Point};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Colour {
+    Red,
+    Green,
+    Yellow,
+    Cyan,
+    Grey,
+}
+
+impl Colour {
+    fn ansi(self) -> &'static str {
+        match self {
+            Colour::Red => "\x1b[31m",
+            Colour::Green => "\x1b[32m",
+            Colour::Yellow => "\x1b[33m",
+            Colour::Cyan => "\x1b[36m",
+            Colour::Grey => "\x1b[90m",
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// A character of a frame, possibly coloured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Glyph {
+    pub c: char,
+    pub colour: Option<Colour>,
+}
+
+impl Glyph {
+    pub fn coloured(c: char, colour: Colour) -> Self {
+        Self {
+            c,
+            colour: Some(colour),
+        }
+    }
+}
+
+impl From<char> for Glyph {
+    fn from(c: char) -> Self {
+        Self { c, colour: None }
+    }
+}
+
+/// Turns states into text frames, with or without ANSI colours.
+#[derive(Clone, Copy, Debug)]
+pub struct Painter {
+    pub ansi: bool,
+}
+
+impl Painter {
+    /// Draws the area within `bounds`, one line per row, each ending with a newline.
+    pub fn frame(&self, bounds: Bounds, glyph: impl Fn(Point) -> Glyph) -> String {
+        let mut out = String::with_capacity(bounds.height() * (bounds.width() + 1));
+        for row in bounds.rows() {
+            let glyphs = bounds.columns().map(|column| glyph((row, column)));
+            out.push_str(&self.line(glyphs));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Draws a single line, sharing the colour codes between consecutive glyphs.
+    pub fn line(&self, glyphs: impl IntoIterator<Item = Glyph>) -> String {
+        let mut out = String::new();
+        let mut current = None;
+        for glyph in glyphs {
+            if self.ansi && glyph.colour != current {
+                out.push_str(glyph.colour.map_or(RESET, Colour::ansi));
+                current = glyph.colour;
+            }
+            out.push(glyph.c);
+        }
+        if current.is_some() {
+            out.push_str(RESET);
+        }
+        out
+    }
+}
+
+/// Receives the successive states of a simulation and draws those worth showing.
+pub struct Recorder<'a> {
+    painter: Painter,
+    /// Also draw every that many steps, on top of the final state.
+    every: Option<usize>,
+    sink: Option<&'a mut dyn FnMut(usize, String)>,
+    /// The last step drawn, so that the final state is not drawn twice.
+    drawn: Option<usize>,
+}
+
+impl<'a> Recorder<'a> {
+    /// Hands the frames to `sink`, along with their step.
+    pub fn new(
+        painter: Painter,
+        every: Option<usize>,
+        sink: &'a mut dyn FnMut(usize, String),
+    ) -> Self {
+        Self {
+            painter,
+            every,
+            sink: Some(sink),
+            drawn: None,
+        }
+    }
+
+    /// A recorder drawing nothing, for when the states are not to be shown.
+    pub fn off() -> Self {
+        Self {
+            painter: Painter { ansi: false },
+            every: None,
+            sink: None,
+            drawn: None,
+        }
+    }
+
+    /// Draws the state reached at `step` if it is one of the intermediate states to show.
+    /// `draw` is only called when needed, so it may be expensive.
+    pub fn step(&mut self, step: usize, draw: impl FnOnce(&Painter) -> String) {
+        if self.every.is_some_and(|every| step.is_multiple_of(every)) {
+            self.last(step, draw);
+        }
+    }
+
+    /// Draws the final state, reached at `step`, unless it was just drawn as an intermediate one.
+    pub fn last(&mut self, step: usize, draw: impl FnOnce(&Painter) -> String) {
+        if self.drawn == Some(step) {
+            return;
+        }
+        if let Some(sink) = &mut self.sink {
+            sink(step, draw(&self.painter));
+            self.drawn = Some(step);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glyph((row, column): Point) -> Glyph {
+        if row == column {
+            Glyph::coloured('#', Colour::Red)
+        } else {
+            '.'.into()
+        }
+    }
+
+    #[test]
+    fn test_frame() {
+        let bounds = Bounds::of([(0, 0), (1, 2)]).unwrap();
+        assert_eq!(Painter { ansi: false }.frame(bounds, glyph), "#..\n.#.\n");
+        assert_eq!(
+            Painter { ansi: true }.frame(bounds, glyph),
+            "\x1b[31m#\x1b[0m..\n.\x1b[31m#\x1b[0m.\n"
+        );
+    }
+
+    #[test]
+    fn test_recorder() {
+        let mut frames = vec![];
+        let mut sink = |step, frame| frames.push((step, frame));
+        let mut recorder = Recorder::new(Painter { ansi: false }, Some(2), &mut sink);
+        for step in 1..=3 {
+            recorder.step(step, |_| step.to_string());
+        }
+        recorder.last(3, |_| "end".to_string());
+        recorder.last(3, |_| "again".to_string());
+        assert_eq!(frames, [(2, "2".to_string()), (3, "end".to_string())]);
+    }
+}
@@ -0,0 +1,96 @@
+use crate::cli::Playback;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Clears the terminal and moves the cursor to its top left corner.
+const CLEAR: &str = "\x1b[2J\x1b[H";
+
+/// Shows the frames of the simulations as they are drawn, either one after the other in the
+/// terminal at a steady pace, or as files in a directory. Frames are never kept in memory,
+/// as there can be tens of thousands of them.
+pub struct Projector<'a> {
+    playback: &'a Playback,
+    /// When the next frame is due in the terminal.
+    due: Option<Instant>,
+    /// The first error met while showing the frames, after which they are dropped.
+    error: Option<io::Error>,
+}
+
+impl<'a> Projector<'a> {
+    /// Prepares the playback, creating its directory if needed.
+    pub fn new(playback: &'a Playback) -> io::Result<Self> {
+        if let Playback::Directory(dir) = playback {
+            std::fs::create_dir_all(dir)?;
+        }
+        Ok(Self {
+            playback,
+            due: None,
+            error: None,
+        })
+    }
+
+    /// Shows the frame drawn at `step` of the given part.
+    pub fn show(&mut self, day: u8, part: u8, step: usize, frame: &str) {
+        if self.error.is_some() {
+            return;
+        }
+        let result = match self.playback {
+            Playback::Terminal { fps } => self.play(*fps, day, part, step, frame),
+            Playback::Directory(dir) => std::fs::write(frame_path(dir, day, part, step), frame),
+        };
+        if let Err(err) = result {
+            self.error = Some(err);
+        }
+    }
+
+    fn play(&mut self, fps: f64, day: u8, part: u8, step: usize, frame: &str) -> io::Result<()> {
+        let now = Instant::now();
+        let due = self.due.unwrap_or(now);
+        if due > now {
+            std::thread::sleep(due - now);
+        }
+        // frames late because they took long to draw are not caught up on
+        self.due = Some(due.max(now) + Duration::from_secs_f64(1.0 / fps));
+
+        let mut out = io::stdout().lock();
+        write!(out, "{CLEAR}{day}.{part}, step {step}:\n{frame}")?;
+        out.flush()
+    }
+
+    /// Ends the playback, reporting the first error met if any.
+    pub fn finish(self) -> io::Result<()> {
+        self.error.map_or(Ok(()), Err)
+    }
+}
+
+/// Where the frame drawn at `step` of the given part is written within `dir`, named so that
+/// the frames are listed in order.
+pub fn frame_path(dir: &Path, day: u8, part: u8, step: usize) -> PathBuf {
+    dir.join(format!("day{day:02}-part{part}-{step:06}.txt"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directory() {
+        let dir = std::env::temp_dir().join(format!("aoc2022-replay-{}", std::process::id()));
+        let playback = Playback::Directory(dir.clone());
+        let mut projector = Projector::new(&playback).unwrap();
+        projector.show(14, 2, 7, "o\n");
+        projector.show(14, 2, 12, "oo\n");
+        projector.finish().unwrap();
+
+        let mut names = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["day14-part2-000007.txt", "day14-part2-000012.txt"]);
+        let frame = std::fs::read_to_string(frame_path(&dir, 14, 2, 12)).unwrap();
+        assert_eq!(frame, "oo\n");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
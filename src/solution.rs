@@ -0,0 +1,137 @@
+use crate::parse::ParseError;
+use crate::render::Recorder;
+use rand::rngs::StdRng;
+use std::fmt;
+use std::io::BufRead;
+
+/// The answer to one part of a puzzle, whatever the type the solver computes it as.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Unsigned(u64),
+    Signed(i64),
+    Text(String),
+    /// Letters drawn on a screen, along with its pixels row by row, lit when `true`.
+    Screen {
+        letters: String,
+        pixels: Vec<Vec<bool>>,
+    },
+}
+
+impl Answer {
+    /// The pixels of a screen, one line per row starting on a line of its own, for the rows
+    /// to line up after a label.
+    pub fn raster(&self) -> Option<String> {
+        let Answer::Screen { pixels, .. } = self else {
+            return None;
+        };
+        let rows = pixels.iter().map(|row| {
+            let row = row.iter().map(|&lit| if lit { '#' } else { '.' });
+            format!("\n{}", row.collect::<String>())
+        });
+        Some(rows.collect())
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(value: u64) -> Self {
+        Self::Unsigned(value)
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        Self::Unsigned(value as u64)
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(value: i64) -> Self {
+        Self::Signed(value)
+    }
+}
+
+impl From<isize> for Answer {
+    fn from(value: isize) -> Self {
+        Self::Signed(value as i64)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Unsigned(value) => write!(f, "{value}"),
+            Answer::Signed(value) => write!(f, "{value}"),
+            Answer::Text(value) => write!(f, "{value}"),
+            Answer::Screen { letters, .. } => write!(f, "{letters}"),
+        }
+    }
+}
+
+/// A day of the calendar, able to solve its puzzle from the raw input.
+pub trait Solution: Sync {
+    fn day(&self) -> u8;
+
+    fn title(&self) -> &'static str;
+
+    fn part_one(&self, input: &str) -> Result<Answer, ParseError>;
+
+    /// `None` for the last day, which has a single part. This is the only place telling how
+    /// many parts a day has.
+    fn part_two(&self, input: &str) -> Option<Result<Answer, ParseError>> {
+        let _ = input;
+        None
+    }
+
+    /// Whether solving the real input takes long enough to be worth skipping.
+    fn slow(&self) -> bool {
+        false
+    }
+
+    /// Solves the given part, counting from 1, or `None` if the puzzle has no such part.
+    fn part(&self, part: u8, input: &str) -> Option<Result<Answer, ParseError>> {
+        match part {
+            1 => Some(self.part_one(input)),
+            2 => self.part_two(input),
+            _ => None,
+        }
+    }
+
+    /// Whether `render` draws the states of a simulation.
+    fn renders(&self) -> bool {
+        false
+    }
+
+    /// Solves the given part like `part`, handing the states it goes through to `recorder`.
+    fn render(
+        &self,
+        part: u8,
+        input: &str,
+        recorder: &mut Recorder,
+    ) -> Option<Result<Answer, ParseError>> {
+        let _ = recorder;
+        self.part(part, input)
+    }
+
+    /// Solves the given part like `part`, reading the input from `reader`. Days which can
+    /// read it line by line do, so that it never has to be held whole; others read it first.
+    fn stream(&self, part: u8, reader: &mut dyn BufRead) -> Option<Result<Answer, ParseError>> {
+        let mut input = String::new();
+        if let Err(err) = reader.read_to_string(&mut input) {
+            return Some(Err(ParseError::new(1, 1, format!("cannot read: {err}"))));
+        }
+        self.part(part, &input)
+    }
+
+    /// Writes a random input in the format of the puzzle, made of about `size` of the items
+    /// it lists, if the day has a generator.
+    fn generate(&self, rng: &mut StdRng, size: usize) -> Option<String> {
+        let _ = (rng, size);
+        None
+    }
+}
@@ -0,0 +1,72 @@
+//! Property and differential testing: the days check their solvers against random inputs,
+//! generated from fixed seeds so that any failure can be replayed.
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fmt::Debug;
+
+/// Checks that `property` holds for the inputs generated from the seeds `0..cases`, panicking
+/// with the seed and input of the first counterexample.
+pub fn check(
+    cases: u64,
+    generate: impl Fn(&mut StdRng) -> String,
+    property: impl Fn(&str) -> Result<(), String>,
+) {
+    for seed in 0..cases {
+        let input = generate(&mut StdRng::seed_from_u64(seed));
+        if let Err(err) = property(&input) {
+            panic!("seed {seed}: {err}, for input:\n{input}");
+        }
+    }
+}
+
+/// Checks that `solver` agrees with `reference`, a slower but obviously correct implementation,
+/// on the inputs generated from the seeds `0..cases`.
+pub fn differential<T: Debug + PartialEq>(
+    cases: u64,
+    generate: impl Fn(&mut StdRng) -> String,
+    solver: impl Fn(&str) -> T,
+    reference: impl Fn(&str) -> T,
+) {
+    check(cases, generate, |input| {
+        let (got, expected) = (solver(input), reference(input));
+        if got == expected {
+            Ok(())
+        } else {
+            Err(format!("expected {expected:?}, got {got:?}"))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    fn numbers(rng: &mut StdRng) -> String {
+        (0..rng.gen_range(1..10))
+            .map(|_| format!("{}\n", rng.gen_range(0..100)))
+            .collect()
+    }
+
+    #[test]
+    fn test_differential() {
+        let sum = |input: &str| {
+            input
+                .lines()
+                .map(|n| n.parse::<u32>().unwrap())
+                .sum::<u32>()
+        };
+        differential(20, numbers, sum, |input| {
+            input
+                .lines()
+                .fold(0, |sum, n| sum + n.parse::<u32>().unwrap())
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "seed 0: expected")]
+    fn test_counterexample() {
+        differential(20, numbers, |input| input.len(), |_| 0);
+    }
+}